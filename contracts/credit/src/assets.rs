@@ -0,0 +1,90 @@
+//! Per-asset liquidity routing and limits.
+//!
+//! A line with `asset: None` uses the global `LiquidityToken` / `LiquiditySource`, or the
+//! lending pool once one is set (see the `lending_pool` module). A line
+//! with `asset: Some(token)` is drawn from and repaid to that asset's configured
+//! liquidity source, and its terms must fit the asset's `AssetConfig`.
+
+use soroban_sdk::{Address, Env};
+
+use crate::types::AssetConfig;
+use crate::{lending_pool, portfolio, CreditError, DataKey, MAX_INTEREST_RATE_BPS};
+
+pub fn config(env: &Env, asset: &Address) -> Option<AssetConfig> {
+    env.storage()
+        .instance()
+        .get(&DataKey::AssetConfig(asset.clone()))
+}
+
+fn require_config(env: &Env, asset: &Address) -> Result<AssetConfig, CreditError> {
+    config(env, asset).ok_or(CreditError::AssetNotConfigured)
+}
+
+/// Whether `config` is internally consistent.
+pub fn is_valid(config: &AssetConfig) -> bool {
+    config.max_borrower_limit >= 0
+        && config.max_outstanding >= 0
+        && config.min_interest_rate_bps <= config.max_interest_rate_bps
+        && config.max_interest_rate_bps <= MAX_INTEREST_RATE_BPS
+}
+
+/// Token and liquidity source that a line in `asset` draws from and repays to. The token
+/// is `None` when no global liquidity token is configured.
+pub fn liquidity(
+    env: &Env,
+    asset: &Option<Address>,
+) -> Result<(Option<Address>, Address), CreditError> {
+    match asset {
+        Some(asset) => {
+            let config = require_config(env, asset)?;
+            Ok((Some(asset.clone()), config.liquidity_source))
+        }
+        None => {
+            let instance = env.storage().instance();
+            let source = lending_pool::for_asset(env, &None)
+                .or_else(|| instance.get(&DataKey::LiquiditySource))
+                .unwrap_or(env.current_contract_address());
+            Ok((instance.get(&DataKey::LiquidityToken), source))
+        }
+    }
+}
+
+/// Check a line's limit and rate against its asset's configuration.
+pub fn check_terms(
+    env: &Env,
+    asset: &Option<Address>,
+    credit_limit: i128,
+    interest_rate_bps: u32,
+) -> Result<(), CreditError> {
+    let Some(asset) = asset else {
+        return Ok(());
+    };
+    let config = require_config(env, asset)?;
+    if interest_rate_bps < config.min_interest_rate_bps
+        || interest_rate_bps > config.max_interest_rate_bps
+    {
+        return Err(CreditError::InvalidInterestRate);
+    }
+    if config.max_borrower_limit > 0 && credit_limit > config.max_borrower_limit {
+        return Err(CreditError::BorrowerLimitCapExceeded);
+    }
+    Ok(())
+}
+
+/// Check a draw of `amount` against the asset's outstanding cap.
+pub fn check_draw(env: &Env, asset: &Option<Address>, amount: i128) -> Result<(), CreditError> {
+    let Some(asset) = asset else {
+        return Ok(());
+    };
+    let cap = require_config(env, asset)?.max_outstanding;
+    if cap == 0 {
+        return Ok(());
+    }
+    let outstanding = portfolio::pool_utilized(env, &Some(asset.clone()))
+        .checked_add(amount)
+        .ok_or(CreditError::Overflow)?;
+    if outstanding > cap {
+        return Err(CreditError::OutstandingCapExceeded);
+    }
+    Ok(())
+}
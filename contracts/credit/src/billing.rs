@@ -0,0 +1,275 @@
+//! Billing cycles, statements and minimum payments.
+//!
+//! Once the admin sets a `BillingConfig`, every line runs fixed-length cycles starting
+//! when it is opened (or first touched, for lines that predate billing). When a cycle
+//! ends, a statement snapshots the balance owed and sets a minimum payment due
+//! `due_after_secs` later. Cycles are closed lazily, one statement per ended cycle, the
+//! next time the line is touched; `accrue_interest` lets keepers close them on time.
+//!
+//! A minimum payment left unpaid when the next statement closes is charged the late fee,
+//! unless `assess_late_fee` already charged it for that statement. The unpaid amount is
+//! carried into the new statement's minimum, and the line is marked past due from the
+//! missed due date (see the `delinquency` module).
+
+use soroban_sdk::{symbol_short, Address, Env};
+
+use crate::events::{
+    publish_fee_charged, publish_statement_closed, FeeChargedEvent, StatementClosedEvent,
+};
+use crate::types::{BillingConfig, CreditLineData, CreditStatus, Statement};
+use crate::{delinquency, fees, interest, ttl, CreditError, DataKey, MAX_INTEREST_RATE_BPS};
+
+/// Basis-point denominator (10000 = 100%).
+const BPS_DENOMINATOR: i128 = 10_000;
+
+pub fn config(env: &Env) -> Option<BillingConfig> {
+    env.storage().instance().get(&DataKey::BillingConfig)
+}
+
+/// Whether `config` is internally consistent. Payments must fall due before the next
+/// statement closes, so each statement is settled or marked missed exactly once.
+pub fn is_valid(config: &BillingConfig) -> bool {
+    config.period_secs > 0
+        && config.due_after_secs <= config.period_secs
+        && config.min_payment_bps <= MAX_INTEREST_RATE_BPS
+        && config.min_payment_floor >= 0
+}
+
+pub fn load(env: &Env, borrower: &Address) -> Option<Statement> {
+    let key = DataKey::Statement(borrower.clone());
+    let statement = env.storage().persistent().get(&key)?;
+    ttl::extend_persistent(env, &key);
+    Some(statement)
+}
+
+fn save(env: &Env, borrower: &Address, statement: &Statement) {
+    let key = DataKey::Statement(borrower.clone());
+    env.storage().persistent().set(&key, statement);
+    ttl::extend_persistent(env, &key);
+}
+
+fn new_cycle(now: u64) -> Statement {
+    Statement {
+        cycle_start: now,
+        closed_at: 0,
+        statement_balance: 0,
+        minimum_due: 0,
+        due_ts: 0,
+        late_fee_charged: false,
+    }
+}
+
+/// Start a fresh billing cycle for a newly opened line. No-op while billing is off.
+pub fn start(env: &Env, borrower: &Address) {
+    if config(env).is_some() {
+        save(env, borrower, &new_cycle(env.ledger().timestamp()));
+    }
+}
+
+/// Extend the TTL of `borrower`'s statement, if any.
+pub fn extend(env: &Env, borrower: &Address) {
+    let key = DataKey::Statement(borrower.clone());
+    if env.storage().persistent().has(&key) {
+        ttl::extend_persistent(env, &key);
+    }
+}
+
+/// Total owed on the line: principal, accrued interest and fees.
+fn balance(line: &CreditLineData) -> Result<i128, CreditError> {
+    line.utilized_amount
+        .checked_add(line.accrued_interest)
+        .and_then(|v| v.checked_add(line.accrued_fees))
+        .ok_or(CreditError::Overflow)
+}
+
+/// Minimum payment on `balance`: `min_payment_bps` of it or the floor, whichever is
+/// larger, plus any minimum carried over, never more than the balance itself.
+fn minimum_payment(
+    config: &BillingConfig,
+    balance: i128,
+    carried: i128,
+) -> Result<i128, CreditError> {
+    let share = balance
+        .checked_mul(config.min_payment_bps as i128)
+        .map(|v| v / BPS_DENOMINATOR)
+        .ok_or(CreditError::Overflow)?;
+    let minimum = share
+        .max(config.min_payment_floor)
+        .checked_add(carried)
+        .ok_or(CreditError::Overflow)?;
+    Ok(minimum.min(balance).max(0))
+}
+
+/// Whether the statement's minimum payment is still unpaid when the next statement
+/// closes at `close`. The due date never falls after `close`, so a minimum due exactly at
+/// the cycle boundary counts as missed rather than rolling forward.
+fn is_missed(statement: &Statement, close: u64) -> bool {
+    statement.closed_at != 0 && statement.minimum_due > 0 && close >= statement.due_ts
+}
+
+/// Cycles closed one by one in a single roll; a line idle for longer closes the rest
+/// together, missing the late fees of the cycles in between.
+const MAX_CYCLES_PER_ROLL: u64 = 64;
+
+/// Result of closing the cycles of a statement that have ended.
+pub enum Advance {
+    /// The cycle in progress has not ended.
+    Open,
+    Closed,
+    /// At least one minimum payment went unpaid; carries the earliest missed due date.
+    Missed(u64),
+}
+
+/// Close every cycle of `statement` that has ended by `now`, one statement at a time,
+/// charging `late_fee` to `line` for each minimum that was missed, unless
+/// `assess_late_fee` already charged it. Past `MAX_CYCLES_PER_ROLL` closes, the remaining
+/// cycles close together.
+fn advance(
+    config: &BillingConfig,
+    statement: &mut Statement,
+    line: &mut CreditLineData,
+    late_fee: i128,
+    now: u64,
+) -> Result<Advance, CreditError> {
+    let mut outcome = Advance::Open;
+    let mut closes = 0;
+    loop {
+        let elapsed = now.saturating_sub(statement.cycle_start);
+        if elapsed < config.period_secs {
+            return Ok(outcome);
+        }
+        closes += 1;
+        let cycles = if closes < MAX_CYCLES_PER_ROLL {
+            1
+        } else {
+            elapsed / config.period_secs
+        };
+        let close = statement.cycle_start + cycles * config.period_secs;
+        let carried = if is_missed(statement, close) {
+            if !statement.late_fee_charged {
+                fees::charge(line, late_fee)?;
+            }
+            if !matches!(outcome, Advance::Missed(_)) {
+                outcome = Advance::Missed(statement.due_ts);
+            }
+            statement.minimum_due
+        } else {
+            if matches!(outcome, Advance::Open) {
+                outcome = Advance::Closed;
+            }
+            0
+        };
+        let balance = balance(line)?;
+        *statement = Statement {
+            cycle_start: close,
+            closed_at: close,
+            statement_balance: balance,
+            minimum_due: minimum_payment(config, balance, carried)?,
+            due_ts: close + config.due_after_secs,
+            late_fee_charged: false,
+        };
+    }
+}
+
+/// Close any ended cycles of `line`, charging late fees to it for missed minimums.
+/// Interest must already be accrued. Lines without a statement start their first cycle
+/// now; Closed lines are not billed.
+pub fn roll(env: &Env, line: &mut CreditLineData) -> Result<(), CreditError> {
+    let Some(config) = config(env) else {
+        return Ok(());
+    };
+    if line.status == CreditStatus::Closed {
+        return Ok(());
+    }
+    let now = env.ledger().timestamp();
+    let Some(mut statement) = load(env, &line.borrower) else {
+        save(env, &line.borrower, &new_cycle(now));
+        return Ok(());
+    };
+    let fees_before = line.accrued_fees;
+    match advance(&config, &mut statement, line, fees::late_fee(env), now)? {
+        Advance::Open => return Ok(()),
+        Advance::Closed => {}
+        Advance::Missed(due_ts) => delinquency::mark_missed(env, &line.borrower, due_ts),
+    }
+    save(env, &line.borrower, &statement);
+
+    let late_fee = line.accrued_fees - fees_before;
+    if late_fee > 0 {
+        publish_fee_charged(
+            env,
+            symbol_short!("late_fee"),
+            FeeChargedEvent {
+                borrower: line.borrower.clone(),
+                fee: late_fee,
+                accrued_fees: line.accrued_fees,
+                timestamp: now,
+            },
+        );
+    }
+    publish_statement_closed(
+        env,
+        StatementClosedEvent {
+            borrower: line.borrower.clone(),
+            statement_balance: statement.statement_balance,
+            minimum_due: statement.minimum_due,
+            due_ts: statement.due_ts,
+        },
+    );
+    Ok(())
+}
+
+/// Count `amount` repaid by `borrower` towards the minimum payment due.
+pub fn record_payment(env: &Env, borrower: &Address, amount: i128) {
+    if let Some(mut statement) = load(env, borrower) {
+        if statement.minimum_due > 0 {
+            statement.minimum_due = (statement.minimum_due - amount).max(0);
+            save(env, borrower, &statement);
+        }
+    }
+}
+
+/// Charge the late fee to `line` if its latest statement's minimum payment is past due
+/// and has not been charged yet. Returns the fee charged. Billing must already be rolled.
+pub fn charge_late_fee(env: &Env, line: &mut CreditLineData) -> Result<i128, CreditError> {
+    let Some(mut statement) = load(env, &line.borrower) else {
+        return Ok(0);
+    };
+    let fee = fees::late_fee(env);
+    let past_due = statement.closed_at != 0
+        && statement.minimum_due > 0
+        && env.ledger().timestamp() > statement.due_ts;
+    if fee == 0 || !past_due || statement.late_fee_charged {
+        return Ok(0);
+    }
+    fees::charge(line, fee)?;
+    statement.late_fee_charged = true;
+    save(env, &line.borrower, &statement);
+    Ok(fee)
+}
+
+/// `line`'s statement as it would stand if touched now, without writing anything, and
+/// how its ended cycles would close. `None` while billing is off or for Closed lines.
+pub fn current(
+    env: &Env,
+    line: &CreditLineData,
+) -> Result<Option<(Statement, Advance)>, CreditError> {
+    let Some(config) = config(env) else {
+        return Ok(None);
+    };
+    if line.status == CreditStatus::Closed {
+        return Ok(None);
+    }
+    let now = env.ledger().timestamp();
+    let Some(mut statement) = load(env, &line.borrower) else {
+        return Ok(Some((new_cycle(now), Advance::Open)));
+    };
+    let mut line = line.clone();
+    let pending = interest::pending_interest(&line, now)?;
+    line.accrued_interest = line
+        .accrued_interest
+        .checked_add(pending)
+        .ok_or(CreditError::Overflow)?;
+    let outcome = advance(&config, &mut statement, &mut line, fees::late_fee(env), now)?;
+    Ok(Some((statement, outcome)))
+}
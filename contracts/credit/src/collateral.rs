@@ -0,0 +1,176 @@
+//! Collateral deposits and valuation.
+//!
+//! Borrowers deposit tokens into the contract against their credit line. Each collateral
+//! token has a `CollateralConfig` with a price, a minimum collateral ratio and a lower
+//! liquidation ratio, and may have an `OracleConfig` whose feed prices it instead (see the
+//! `oracle` module); a deposit's borrowing power is its value divided by the minimum
+//! ratio. Withdrawals must leave the line's debt (principal, interest and fees) within the
+//! remaining borrowing power, and secured lines may only draw up to it. A secured line only
+//! becomes liquidatable once its debt exceeds the value divided by the liquidation ratio.
+
+use soroban_sdk::{Address, Env, Vec};
+
+use crate::types::{CollateralConfig, CollateralPosition, CreditLineData};
+use crate::{interest, oracle, ttl, CreditError, DataKey};
+
+/// Scale of `CollateralConfig::price`: liquidity token units per collateral unit, times
+/// 10^7.
+pub const PRICE_SCALE: i128 = 10_000_000;
+
+/// Basis-point denominator (10000 = 100%).
+const BPS_DENOMINATOR: i128 = 10_000;
+
+pub fn config(env: &Env, token: &Address) -> Option<CollateralConfig> {
+    env.storage()
+        .instance()
+        .get(&DataKey::CollateralConfig(token.clone()))
+}
+
+pub fn require_config(env: &Env, token: &Address) -> Result<CollateralConfig, CreditError> {
+    config(env, token).ok_or(CreditError::CollateralNotSupported)
+}
+
+/// Whether `config` is usable: a positive price and a liquidation ratio of at least 100%
+/// that does not exceed the minimum ratio.
+pub fn is_valid(config: &CollateralConfig) -> bool {
+    config.price > 0
+        && config.liquidation_ratio_bps >= BPS_DENOMINATOR as u32
+        && config.liquidation_ratio_bps <= config.min_collateral_ratio_bps
+}
+
+pub fn balance(env: &Env, borrower: &Address, token: &Address) -> i128 {
+    let key = DataKey::Collateral(borrower.clone(), token.clone());
+    match env.storage().persistent().get(&key) {
+        Some(amount) => {
+            ttl::extend_persistent(env, &key);
+            amount
+        }
+        None => 0,
+    }
+}
+
+/// Tokens `borrower` has ever deposited.
+pub fn tokens(env: &Env, borrower: &Address) -> Vec<Address> {
+    env.storage()
+        .persistent()
+        .get(&DataKey::CollateralTokens(borrower.clone()))
+        .unwrap_or(Vec::new(env))
+}
+
+/// Set `borrower`'s deposit of `token` to `amount`.
+pub fn set_balance(env: &Env, borrower: &Address, token: &Address, amount: i128) {
+    let persistent = env.storage().persistent();
+    let tokens_key = DataKey::CollateralTokens(borrower.clone());
+    let mut tokens = tokens(env, borrower);
+    if !tokens.contains(token) {
+        tokens.push_back(token.clone());
+        persistent.set(&tokens_key, &tokens);
+    }
+    ttl::extend_persistent(env, &tokens_key);
+    let key = DataKey::Collateral(borrower.clone(), token.clone());
+    persistent.set(&key, &amount);
+    ttl::extend_persistent(env, &key);
+}
+
+/// Extend the TTL of every collateral record of `borrower` and its secured flag.
+pub fn extend(env: &Env, borrower: &Address) {
+    let secured_key = DataKey::SecuredLine(borrower.clone());
+    if env.storage().persistent().has(&secured_key) {
+        ttl::extend_persistent(env, &secured_key);
+    }
+    let tokens_key = DataKey::CollateralTokens(borrower.clone());
+    if !env.storage().persistent().has(&tokens_key) {
+        return;
+    }
+    ttl::extend_persistent(env, &tokens_key);
+    for token in tokens(env, borrower).iter() {
+        ttl::extend_persistent(env, &DataKey::Collateral(borrower.clone(), token));
+    }
+}
+
+/// Current price of `token`, scaled by `PRICE_SCALE`: its oracle price if it has an oracle,
+/// otherwise the fixed price in `config`.
+pub fn price(env: &Env, token: &Address, config: &CollateralConfig) -> Result<i128, CreditError> {
+    match oracle::config(env, token) {
+        Some(oracle_config) => oracle::price(env, token, &oracle_config),
+        None => Ok(config.price),
+    }
+}
+
+/// Value of `amount` of a token at `price`, in liquidity token units, rounded down.
+pub fn value_of(price: i128, amount: i128) -> Result<i128, CreditError> {
+    amount
+        .checked_mul(price)
+        .map(|v| v / PRICE_SCALE)
+        .ok_or(CreditError::Overflow)
+}
+
+/// Debt `value` of collateral covers at `ratio_bps`, rounded down.
+fn debt_covered(value: i128, ratio_bps: u32) -> Result<i128, CreditError> {
+    value
+        .checked_mul(BPS_DENOMINATOR)
+        .map(|v| v / ratio_bps as i128)
+        .ok_or(CreditError::Overflow)
+}
+
+/// Everything owed on `line` at `now`, including interest not yet accrued.
+pub fn debt(line: &CreditLineData, now: u64) -> Result<i128, CreditError> {
+    let pending = interest::pending_interest(line, now)?;
+    line.utilized_amount
+        .checked_add(line.accrued_interest)
+        .and_then(|v| v.checked_add(line.accrued_fees))
+        .and_then(|v| v.checked_add(pending))
+        .ok_or(CreditError::Overflow)
+}
+
+/// Value, borrowing power and liquidation threshold of `borrower`'s collateral against
+/// `debt`. Tokens whose config was removed count for nothing.
+pub fn position(
+    env: &Env,
+    borrower: &Address,
+    debt: i128,
+) -> Result<CollateralPosition, CreditError> {
+    let mut collateral_value: i128 = 0;
+    let mut borrowing_power: i128 = 0;
+    let mut liquidation_threshold: i128 = 0;
+    for token in tokens(env, borrower).iter() {
+        let Some(config) = config(env, &token) else {
+            continue;
+        };
+        let price = price(env, &token, &config)?;
+        let value = value_of(price, balance(env, borrower, &token))?;
+        collateral_value = collateral_value
+            .checked_add(value)
+            .ok_or(CreditError::Overflow)?;
+        borrowing_power = borrowing_power
+            .checked_add(debt_covered(value, config.min_collateral_ratio_bps)?)
+            .ok_or(CreditError::Overflow)?;
+        liquidation_threshold = liquidation_threshold
+            .checked_add(debt_covered(value, config.liquidation_ratio_bps)?)
+            .ok_or(CreditError::Overflow)?;
+    }
+    Ok(CollateralPosition {
+        collateral_value,
+        borrowing_power,
+        liquidation_threshold,
+        debt,
+    })
+}
+
+/// Whether a line is secured, so its draws are limited by its borrowing power.
+pub fn is_secured(env: &Env, borrower: &Address) -> bool {
+    env.storage()
+        .persistent()
+        .get(&DataKey::SecuredLine(borrower.clone()))
+        .unwrap_or(false)
+}
+
+pub fn set_secured(env: &Env, borrower: &Address, secured: bool) {
+    let key = DataKey::SecuredLine(borrower.clone());
+    if secured {
+        env.storage().persistent().set(&key, &true);
+        ttl::extend_persistent(env, &key);
+    } else {
+        env.storage().persistent().remove(&key);
+    }
+}
@@ -0,0 +1,177 @@
+//! Delinquency tracking from billing due dates.
+//!
+//! A line is past due from the due date of the oldest statement whose minimum payment is
+//! still unpaid, and stays past due until `minimum_due` (which carries unpaid minimums
+//! forward) is paid off. Days past due map to buckets; each bucket change emits an event.
+//! Lines more than the configured grace period past due can be defaulted by anyone
+//! through `process_delinquency`.
+
+use soroban_sdk::{contracttype, Address, Env};
+
+use crate::billing::{self, Advance};
+use crate::events::{publish_delinquency_changed, DelinquencyEvent};
+use crate::types::{CreditLineData, CreditStatus, Delinquency, DelinquencyBucket, Statement};
+use crate::{ttl, CreditError, DataKey};
+
+/// Seconds in a day, for days past due.
+const SECONDS_PER_DAY: u64 = 86_400;
+
+/// Stored delinquency state of a line.
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct DelinquencyRecord {
+    /// Due date of a missed minimum that was carried into a later statement; 0 if none.
+    pub past_due_since: u64,
+    /// Bucket reported by the last update.
+    pub bucket: DelinquencyBucket,
+}
+
+fn load(env: &Env, borrower: &Address) -> DelinquencyRecord {
+    let key = DataKey::Delinquency(borrower.clone());
+    match env.storage().persistent().get(&key) {
+        Some(record) => {
+            ttl::extend_persistent(env, &key);
+            record
+        }
+        None => DelinquencyRecord {
+            past_due_since: 0,
+            bucket: DelinquencyBucket::Current,
+        },
+    }
+}
+
+fn save(env: &Env, borrower: &Address, record: &DelinquencyRecord) {
+    let key = DataKey::Delinquency(borrower.clone());
+    env.storage().persistent().set(&key, record);
+    ttl::extend_persistent(env, &key);
+}
+
+/// Forget any delinquency of a line being opened afresh.
+pub fn reset(env: &Env, borrower: &Address) {
+    env.storage()
+        .persistent()
+        .remove(&DataKey::Delinquency(borrower.clone()));
+}
+
+/// Extend the TTL of `borrower`'s delinquency record, if any.
+pub fn extend(env: &Env, borrower: &Address) {
+    let key = DataKey::Delinquency(borrower.clone());
+    if env.storage().persistent().has(&key) {
+        ttl::extend_persistent(env, &key);
+    }
+}
+
+/// Record that the minimum due at `due_ts` went unpaid and was carried forward. Keeps
+/// the earliest missed due date while the line stays past due.
+pub fn mark_missed(env: &Env, borrower: &Address, due_ts: u64) {
+    let mut record = load(env, borrower);
+    if record.past_due_since == 0 {
+        record.past_due_since = due_ts;
+        save(env, borrower, &record);
+    }
+}
+
+/// Due date the line has been past due since, if the minimum is unpaid past it.
+fn past_due_since(statement: &Statement, carried_since: u64, now: u64) -> Option<u64> {
+    if statement.closed_at == 0 || statement.minimum_due == 0 {
+        return None;
+    }
+    let since = if carried_since != 0 {
+        carried_since
+    } else {
+        statement.due_ts
+    };
+    (now > since).then_some(since)
+}
+
+fn bucket_for(days_past_due: u32) -> DelinquencyBucket {
+    match days_past_due {
+        0 => DelinquencyBucket::Current,
+        1..=29 => DelinquencyBucket::PastDue,
+        30..=59 => DelinquencyBucket::Days30,
+        60..=89 => DelinquencyBucket::Days60,
+        _ => DelinquencyBucket::Days90,
+    }
+}
+
+fn assess(statement: &Statement, carried_since: u64, now: u64) -> Delinquency {
+    match past_due_since(statement, carried_since, now) {
+        Some(since) => {
+            // Any time past the due date counts as at least one day.
+            let days = (now - since).div_ceil(SECONDS_PER_DAY).min(u32::MAX as u64) as u32;
+            Delinquency {
+                bucket: bucket_for(days),
+                days_past_due: days,
+                past_due_since: since,
+            }
+        }
+        None => Delinquency {
+            bucket: DelinquencyBucket::Current,
+            days_past_due: 0,
+            past_due_since: 0,
+        },
+    }
+}
+
+/// Re-assess `line` against its stored statement, which must already be rolled forward,
+/// and emit an event if its bucket changed. `None` while the line is not billed.
+pub fn update(env: &Env, line: &CreditLineData) -> Option<Delinquency> {
+    if line.status == CreditStatus::Closed {
+        return None;
+    }
+    let statement = billing::load(env, &line.borrower)?;
+    let mut record = load(env, &line.borrower);
+    let now = env.ledger().timestamp();
+    let delinquency = assess(&statement, record.past_due_since, now);
+
+    let cured = delinquency.past_due_since == 0 && record.past_due_since != 0;
+    let previous = record.bucket;
+    if cured || previous != delinquency.bucket {
+        record.past_due_since = if cured { 0 } else { record.past_due_since };
+        record.bucket = delinquency.bucket;
+        save(env, &line.borrower, &record);
+    }
+    if previous != delinquency.bucket {
+        publish_delinquency_changed(
+            env,
+            DelinquencyEvent {
+                borrower: line.borrower.clone(),
+                previous,
+                bucket: delinquency.bucket,
+                days_past_due: delinquency.days_past_due,
+                timestamp: now,
+            },
+        );
+    }
+    Some(delinquency)
+}
+
+/// `line`'s delinquency as it would stand if touched now, without writing anything.
+/// `None` while billing is off or for Closed lines.
+pub fn current(env: &Env, line: &CreditLineData) -> Result<Option<Delinquency>, CreditError> {
+    let Some((statement, outcome)) = billing::current(env, line)? else {
+        return Ok(None);
+    };
+    let mut carried_since = env
+        .storage()
+        .persistent()
+        .get::<_, DelinquencyRecord>(&DataKey::Delinquency(line.borrower.clone()))
+        .map_or(0, |record| record.past_due_since);
+    if let (0, Advance::Missed(due_ts)) = (carried_since, outcome) {
+        carried_since = due_ts;
+    }
+    Ok(Some(assess(
+        &statement,
+        carried_since,
+        env.ledger().timestamp(),
+    )))
+}
+
+/// Seconds a line must stay past due before `process_delinquency` defaults it; 0 disables
+/// automatic default.
+pub fn default_grace_period(env: &Env) -> u64 {
+    env.storage()
+        .instance()
+        .get(&DataKey::DefaultGracePeriod)
+        .unwrap_or(0)
+}
@@ -1,9 +1,9 @@
 //! Event types and topic constants for the Credit contract.
 //! Stable event schemas for indexing and analytics.
 
-use soroban_sdk::{contracttype, symbol_short, Address, Env, Symbol};
+use soroban_sdk::{contracttype, symbol_short, Address, BytesN, Env, Symbol, Vec};
 
-use crate::types::CreditStatus;
+use crate::types::{CreditStatus, DelinquencyBucket, RiskTier, Role};
 
 /// Event emitted when a credit line lifecycle event occurs (opened, suspend, closed, default).
 #[contracttype]
@@ -18,13 +18,19 @@ pub struct CreditLineEvent {
 }
 
 /// Event emitted when a borrower repays credit.
-/// Used for indexing and analytics (borrower, amount, new utilized amount, timestamp).
+/// Used for indexing and analytics (borrower, amount applied and its split across
+/// fees, interest and principal, new utilized amount, remaining fee balance, timestamp).
 #[contracttype]
 #[derive(Clone, Debug, Eq, PartialEq)]
 pub struct RepaymentEvent {
     pub borrower: Address,
     pub amount: i128,
+    pub fees_paid: i128,
+    pub interest_paid: i128,
+    pub principal_paid: i128,
     pub new_utilized_amount: i128,
+    /// Fee balance left after the repayment.
+    pub accrued_fees: i128,
     pub timestamp: u64,
 }
 
@@ -34,20 +40,175 @@ pub struct RepaymentEvent {
 pub struct RiskParametersUpdatedEvent {
     pub borrower: Address,
     pub credit_limit: i128,
+    /// Rate the line now accrues at, which a rate model may have replaced.
     pub interest_rate_bps: u32,
     pub risk_score: u32,
 }
 
+/// Event emitted when a line moves between risk tiers, identified by their position in the
+/// configured list.
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct TierChangedEvent {
+    pub borrower: Address,
+    pub risk_score: u32,
+    /// Tier before the change; `None` for new lines or scores outside every tier.
+    pub old_tier: Option<u32>,
+    pub new_tier: Option<u32>,
+}
+
 /// Event emitted when a borrower draws credit.
 #[contracttype]
 #[derive(Clone, Debug, Eq, PartialEq)]
 pub struct DrawnEvent {
     pub borrower: Address,
     pub amount: i128,
+    /// Draw fee added to the line's fee balance.
+    pub fee: i128,
+    /// Fee balance after the draw.
+    pub accrued_fees: i128,
     pub new_utilized_amount: i128,
     pub timestamp: u64,
 }
 
+/// Event emitted when interest is accrued on a credit line.
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct InterestAccruedEvent {
+    pub borrower: Address,
+    pub interest: i128,
+    pub accrued_interest: i128,
+    pub timestamp: u64,
+}
+
+/// Event emitted when an origination or late fee is charged to a credit line.
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct FeeChargedEvent {
+    pub borrower: Address,
+    pub fee: i128,
+    pub accrued_fees: i128,
+    pub timestamp: u64,
+}
+
+/// Event emitted when a billing cycle ends and a new statement is issued.
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct StatementClosedEvent {
+    pub borrower: Address,
+    pub statement_balance: i128,
+    pub minimum_due: i128,
+    pub due_ts: u64,
+}
+
+/// Event emitted when a line moves between delinquency buckets.
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct DelinquencyEvent {
+    pub borrower: Address,
+    pub previous: DelinquencyBucket,
+    pub bucket: DelinquencyBucket,
+    pub days_past_due: u32,
+    pub timestamp: u64,
+}
+
+/// Event emitted when a term loan is originated.
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct TermLoanOriginatedEvent {
+    pub borrower: Address,
+    pub principal: i128,
+    pub interest_rate_bps: u32,
+    pub term_periods: u32,
+    pub period_secs: u64,
+    pub installment: i128,
+}
+
+/// Event emitted when a term loan installment is paid or the loan is paid off.
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct TermLoanPaymentEvent {
+    pub borrower: Address,
+    pub amount: i128,
+    pub principal_paid: i128,
+    pub interest_paid: i128,
+    pub remaining_principal: i128,
+    pub installments_paid: u32,
+    pub timestamp: u64,
+}
+
+/// Event emitted when a borrower deposits or withdraws collateral.
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct CollateralEvent {
+    pub borrower: Address,
+    pub token: Address,
+    pub amount: i128,
+    /// The borrower's deposit of `token` afterwards.
+    pub balance: i128,
+}
+
+/// Event emitted when a liquidator repays part of a line's debt and seizes collateral.
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct LiquidationEvent {
+    pub borrower: Address,
+    pub liquidator: Address,
+    /// Amount repaid, split below as in `RepaymentEvent`.
+    pub repaid: i128,
+    pub fees_paid: i128,
+    pub interest_paid: i128,
+    pub principal_paid: i128,
+    /// Value of the collateral seized, in liquidity token units.
+    pub collateral_seized: i128,
+    pub new_utilized_amount: i128,
+    pub timestamp: u64,
+}
+
+/// Event emitted when the admin writes off what a Defaulted or Closed line owes.
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct WriteOffEvent {
+    pub borrower: Address,
+    pub principal: i128,
+    pub interest: i128,
+    pub fees: i128,
+    pub timestamp: u64,
+}
+
+/// Event emitted when the admin proposes a new admin.
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct AdminProposedEvent {
+    pub admin: Address,
+    pub pending_admin: Address,
+}
+
+/// Event emitted when a pending admin accepts and becomes the admin.
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct AdminTransferredEvent {
+    pub previous_admin: Address,
+    pub new_admin: Address,
+}
+
+/// Event emitted when the admin grants or revokes a role.
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct RoleChangedEvent {
+    pub role: Role,
+    pub account: Address,
+    pub admin: Address,
+}
+
+/// Event emitted when the contract is paused or unpaused.
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct PauseChangedEvent {
+    pub paused: bool,
+    pub caller: Address,
+}
+
 /// Publish a credit line lifecycle event.
 pub fn publish_credit_line_event(env: &Env, topic: (Symbol, Symbol), event: CreditLineEvent) {
     env.events().publish(topic, event);
@@ -70,3 +231,119 @@ pub fn publish_risk_parameters_updated(env: &Env, event: RiskParametersUpdatedEv
     env.events()
         .publish((symbol_short!("credit"), symbol_short!("risk_upd")), event);
 }
+
+/// Publish a risk tier change event.
+pub fn publish_tier_changed(env: &Env, event: TierChangedEvent) {
+    env.events()
+        .publish((symbol_short!("credit"), symbol_short!("tier_chg")), event);
+}
+
+/// Publish an interest accrued event.
+pub fn publish_interest_accrued(env: &Env, event: InterestAccruedEvent) {
+    env.events()
+        .publish((symbol_short!("credit"), symbol_short!("accrue")), event);
+}
+
+/// Publish a fee charged event under `(credit, kind)`, e.g. `orig_fee` or `late_fee`.
+pub fn publish_fee_charged(env: &Env, kind: Symbol, event: FeeChargedEvent) {
+    env.events().publish((symbol_short!("credit"), kind), event);
+}
+
+/// Publish a statement closed event.
+pub fn publish_statement_closed(env: &Env, event: StatementClosedEvent) {
+    env.events()
+        .publish((symbol_short!("credit"), symbol_short!("statement")), event);
+}
+
+/// Publish a delinquency bucket change event.
+pub fn publish_delinquency_changed(env: &Env, event: DelinquencyEvent) {
+    env.events()
+        .publish((symbol_short!("credit"), symbol_short!("delinq")), event);
+}
+
+/// Publish a term loan originated event.
+pub fn publish_term_loan_originated(env: &Env, event: TermLoanOriginatedEvent) {
+    env.events()
+        .publish((symbol_short!("credit"), symbol_short!("loan_orig")), event);
+}
+
+/// Publish a term loan payment event under `(credit, kind)`: `loan_pay` for an installment,
+/// `loan_off` for an early payoff.
+pub fn publish_term_loan_payment(env: &Env, kind: Symbol, event: TermLoanPaymentEvent) {
+    env.events().publish((symbol_short!("credit"), kind), event);
+}
+
+/// Publish a collateral event under `(credit, kind)`: `coll_dep`, `coll_wd`, or `coll_seiz`
+/// when a liquidation seizes it.
+pub fn publish_collateral(env: &Env, kind: Symbol, event: CollateralEvent) {
+    env.events().publish((symbol_short!("credit"), kind), event);
+}
+
+/// Publish a liquidation event.
+pub fn publish_liquidation(env: &Env, event: LiquidationEvent) {
+    env.events()
+        .publish((symbol_short!("credit"), symbol_short!("liquidate")), event);
+}
+
+/// Publish a write-off event.
+pub fn publish_write_off(env: &Env, event: WriteOffEvent) {
+    env.events()
+        .publish((symbol_short!("credit"), symbol_short!("write_off")), event);
+}
+
+/// Publish an admin proposed event.
+pub fn publish_admin_proposed(env: &Env, event: AdminProposedEvent) {
+    env.events()
+        .publish((symbol_short!("credit"), symbol_short!("adm_prop")), event);
+}
+
+/// Publish an admin transferred event.
+pub fn publish_admin_transferred(env: &Env, event: AdminTransferredEvent) {
+    env.events()
+        .publish((symbol_short!("credit"), symbol_short!("adm_acpt")), event);
+}
+
+/// Publish a role granted event.
+pub fn publish_role_granted(env: &Env, event: RoleChangedEvent) {
+    env.events()
+        .publish((symbol_short!("credit"), symbol_short!("role_grnt")), event);
+}
+
+/// Publish a paused or unpaused event, depending on `event.paused`.
+pub fn publish_pause_changed(env: &Env, event: PauseChangedEvent) {
+    let topic = if event.paused {
+        symbol_short!("paused")
+    } else {
+        symbol_short!("unpaused")
+    };
+    env.events()
+        .publish((symbol_short!("credit"), topic), event);
+}
+
+/// Publish a role revoked event.
+pub fn publish_role_revoked(env: &Env, event: RoleChangedEvent) {
+    env.events()
+        .publish((symbol_short!("credit"), symbol_short!("role_rvk")), event);
+}
+
+/// Publish a contract upgraded event carrying the new WASM hash.
+pub fn publish_upgraded(env: &Env, new_wasm_hash: BytesN<32>) {
+    env.events().publish(
+        (symbol_short!("credit"), symbol_short!("upgraded")),
+        new_wasm_hash,
+    );
+}
+
+/// Publish a risk tiers updated event carrying the new tiers.
+pub fn publish_risk_tiers_set(env: &Env, tiers: Vec<RiskTier>) {
+    env.events()
+        .publish((symbol_short!("credit"), symbol_short!("tiers_set")), tiers);
+}
+
+/// Publish a migration completed event carrying the new schema version.
+pub fn publish_migrated(env: &Env, schema_version: u32) {
+    env.events().publish(
+        (symbol_short!("credit"), symbol_short!("migrated")),
+        schema_version,
+    );
+}
@@ -0,0 +1,76 @@
+//! Protocol-wide exposure caps, set by the admin and checked whenever credit grows.
+//!
+//! A cap of zero is disabled. Limit caps are checked when a line is opened or its limit is
+//! raised; lowering a limit is always allowed, even while the book is above a cap. The
+//! reserve cap is checked on draws and bounds principal outstanding in the drawn asset,
+//! after the draw, to a share of that asset's liquidity source balance before it.
+
+use soroban_sdk::{Address, Env};
+
+use crate::types::ExposureCaps;
+use crate::{portfolio, CreditError, DataKey};
+
+/// Basis-point denominator (10000 = 100%).
+const BPS_DENOMINATOR: i128 = 10_000;
+
+pub fn caps(env: &Env) -> ExposureCaps {
+    env.storage()
+        .instance()
+        .get(&DataKey::ExposureCaps)
+        .unwrap_or(ExposureCaps {
+            max_outstanding_reserve_bps: 0,
+            max_borrower_limit: 0,
+            max_total_committed_limit: 0,
+        })
+}
+
+/// Check a line's limit moving from `old_limit` to `new_limit`. `old_limit` is the amount
+/// the line currently counts towards the committed total (zero for new or Closed lines).
+pub fn check_limit_change(env: &Env, old_limit: i128, new_limit: i128) -> Result<(), CreditError> {
+    if new_limit <= old_limit {
+        return Ok(());
+    }
+    let caps = caps(env);
+    if caps.max_borrower_limit > 0 && new_limit > caps.max_borrower_limit {
+        return Err(CreditError::BorrowerLimitCapExceeded);
+    }
+    if caps.max_total_committed_limit > 0 {
+        let committed = portfolio::totals(env)
+            .credit_limit
+            .checked_sub(old_limit)
+            .and_then(|v| v.checked_add(new_limit))
+            .ok_or(CreditError::Overflow)?;
+        if committed > caps.max_total_committed_limit {
+            return Err(CreditError::CommittedLimitCapExceeded);
+        }
+    }
+    Ok(())
+}
+
+/// Check a draw of `amount` in `asset` against the reserve cap, given the reserve balance
+/// before it.
+pub fn check_draw(
+    env: &Env,
+    asset: &Option<Address>,
+    amount: i128,
+    reserve_balance: i128,
+) -> Result<(), CreditError> {
+    let bps = caps(env).max_outstanding_reserve_bps;
+    if bps == 0 {
+        return Ok(());
+    }
+    let outstanding = portfolio::pool_utilized(env, asset)
+        .checked_add(amount)
+        .ok_or(CreditError::Overflow)?;
+    // outstanding <= reserve * bps / 10000, without rounding the right-hand side.
+    let lhs = outstanding
+        .checked_mul(BPS_DENOMINATOR)
+        .ok_or(CreditError::Overflow)?;
+    let rhs = reserve_balance
+        .checked_mul(bps as i128)
+        .ok_or(CreditError::Overflow)?;
+    if lhs > rhs {
+        return Err(CreditError::OutstandingCapExceeded);
+    }
+    Ok(())
+}
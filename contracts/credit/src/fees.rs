@@ -0,0 +1,62 @@
+//! Fee schedule and fee charging.
+//!
+//! Fees are added to a line's `accrued_fees` balance rather than collected up front, so
+//! borrowers pay them through `repay_credit`, where the repayment waterfall settles fees
+//! first. The fee share of each repayment goes to the configured fee collector.
+
+use soroban_sdk::Env;
+
+use crate::types::{CreditLineData, FeeConfig};
+use crate::{CreditError, DataKey, MAX_INTEREST_RATE_BPS};
+
+/// Basis-point denominator (10000 = 100%).
+const BPS_DENOMINATOR: i128 = 10_000;
+
+pub fn config(env: &Env) -> Option<FeeConfig> {
+    env.storage().instance().get(&DataKey::FeeConfig)
+}
+
+/// Whether `config` is internally consistent. Percentage fees may not exceed 100%.
+pub fn is_valid(config: &FeeConfig) -> bool {
+    config.origination_fee_bps <= MAX_INTEREST_RATE_BPS
+        && config.draw_fee_bps <= MAX_INTEREST_RATE_BPS
+        && config.late_fee >= 0
+}
+
+/// `bps` of `amount`, rounded down.
+fn bps_of(amount: i128, bps: u32) -> Result<i128, CreditError> {
+    amount
+        .checked_mul(bps as i128)
+        .map(|v| v / BPS_DENOMINATOR)
+        .ok_or(CreditError::Overflow)
+}
+
+/// Origination fee for a line opened with `credit_limit`.
+pub fn origination_fee(env: &Env, credit_limit: i128) -> Result<i128, CreditError> {
+    match config(env) {
+        Some(config) => bps_of(credit_limit, config.origination_fee_bps),
+        None => Ok(0),
+    }
+}
+
+/// Fee on a draw of `amount`.
+pub fn draw_fee(env: &Env, amount: i128) -> Result<i128, CreditError> {
+    match config(env) {
+        Some(config) => bps_of(amount, config.draw_fee_bps),
+        None => Ok(0),
+    }
+}
+
+/// Flat fee for a missed payment.
+pub fn late_fee(env: &Env) -> i128 {
+    config(env).map_or(0, |config| config.late_fee)
+}
+
+/// Add `fee` to the line's fee balance.
+pub fn charge(line: &mut CreditLineData, fee: i128) -> Result<(), CreditError> {
+    line.accrued_fees = line
+        .accrued_fees
+        .checked_add(fee)
+        .ok_or(CreditError::Overflow)?;
+    Ok(())
+}
@@ -0,0 +1,52 @@
+//! Time-based interest accrual for credit lines.
+//!
+//! Interest is simple (non-compounding) and accrues on `utilized_amount` only. Accrued
+//! interest is tracked separately in `CreditLineData::accrued_interest` so principal and
+//! interest can be reported and repaid independently.
+
+use soroban_sdk::Env;
+
+use crate::types::CreditLineData;
+use crate::CreditError;
+
+/// Seconds in a 365-day year, used to annualize `interest_rate_bps`.
+pub const SECONDS_PER_YEAR: u64 = 31_536_000;
+
+/// Basis-point denominator (10000 = 100%).
+const BPS_DENOMINATOR: i128 = 10_000;
+
+/// Interest owed on `principal` at an annual `rate_bps` over `elapsed` seconds, rounded down.
+pub fn interest_for(principal: i128, rate_bps: u32, elapsed: u64) -> Result<i128, CreditError> {
+    if principal <= 0 || rate_bps == 0 || elapsed == 0 {
+        return Ok(0);
+    }
+    principal
+        .checked_mul(rate_bps as i128)
+        .and_then(|v| v.checked_mul(elapsed as i128))
+        .map(|v| v / (BPS_DENOMINATOR * SECONDS_PER_YEAR as i128))
+        .ok_or(CreditError::Overflow)
+}
+
+/// Interest accrued since `last_accrual_ts` that has not yet been added to the line.
+pub fn pending_interest(line: &CreditLineData, now: u64) -> Result<i128, CreditError> {
+    let elapsed = now.saturating_sub(line.last_accrual_ts);
+    interest_for(line.utilized_amount, line.interest_rate_bps, elapsed)
+}
+
+/// Accrue interest up to the current ledger timestamp. Returns the newly accrued amount.
+///
+/// When principal is outstanding but the elapsed interest rounds down to zero, the
+/// accrual timestamp is left untouched so that frequent calls cannot erase interest.
+pub fn accrue(env: &Env, line: &mut CreditLineData) -> Result<i128, CreditError> {
+    let now = env.ledger().timestamp();
+    let interest = pending_interest(line, now)?;
+    if interest == 0 && line.utilized_amount > 0 && line.interest_rate_bps > 0 {
+        return Ok(0);
+    }
+    line.accrued_interest = line
+        .accrued_interest
+        .checked_add(interest)
+        .ok_or(CreditError::Overflow)?;
+    line.last_accrual_ts = now;
+    Ok(interest)
+}
@@ -0,0 +1,60 @@
+//! Lending pool backing lines in the global liquidity token.
+//!
+//! Once the admin sets a lending pool, it replaces the global `LiquiditySource`: draws and
+//! term loans in the global token borrow from the pool, and repayments are paid to the
+//! pool's address and reported back, with their principal part, so lenders' shares stay
+//! priced correctly. Principal written off is reported as a loss. Lines in other assets
+//! keep their own liquidity sources.
+
+use soroban_sdk::{contractclient, Address, Env};
+
+use crate::DataKey;
+
+/// The lending pool entrypoints the contract calls. Only the generated client is used
+/// outside tests; the pool contract implements the interface.
+#[allow(dead_code)]
+#[contractclient(name = "LendingPoolClient")]
+pub trait LendingPool {
+    /// Token the pool lends.
+    fn token(env: Env) -> Address;
+    /// Tokens the pool holds and can lend.
+    fn available_liquidity(env: Env) -> i128;
+    /// Principal lent out and not yet repaid.
+    fn borrowed(env: Env) -> i128;
+    /// Pay `amount` of the pool's tokens to `recipient`.
+    fn borrow(env: Env, recipient: Address, amount: i128);
+    /// Record `amount` paid to the pool's address, of which `principal` repays principal.
+    fn record_repayment(env: Env, principal: i128, amount: i128);
+    /// Record `principal` lent out that will not be repaid.
+    fn record_loss(env: Env, principal: i128);
+}
+
+/// The lending pool that `asset` is drawn from, if any. Only the global token (`None`) can
+/// be pooled.
+pub fn for_asset(env: &Env, asset: &Option<Address>) -> Option<Address> {
+    match asset {
+        Some(_) => None,
+        None => env.storage().instance().get(&DataKey::LendingPool),
+    }
+}
+
+/// Report `amount` of `asset` paid to its pool, if it has one, of which `principal` repays
+/// principal.
+pub fn record_repayment(env: &Env, asset: &Option<Address>, principal: i128, amount: i128) {
+    if amount <= 0 {
+        return;
+    }
+    if let Some(pool) = for_asset(env, asset) {
+        LendingPoolClient::new(env, &pool).record_repayment(&principal, &amount);
+    }
+}
+
+/// Report `principal` of `asset` written off to its pool, if it has one.
+pub fn record_loss(env: &Env, asset: &Option<Address>, principal: i128) {
+    if principal <= 0 {
+        return;
+    }
+    if let Some(pool) = for_asset(env, asset) {
+        LendingPoolClient::new(env, &pool).record_loss(&principal);
+    }
+}
@@ -3,25 +3,79 @@
 
 //! Creditra credit contract: credit lines, draw/repay, risk parameters.
 //!
+//! # Errors
+//! Every entrypoint returns `Result<_, CreditError>`. Failures surface to clients as
+//! `Error(Contract, #code)` with the stable numeric codes of [`CreditError`].
+//!
 //! # Reentrancy
 //! Soroban token transfers (e.g. Stellar Asset Contract) do not invoke callbacks back into
 //! the caller. This contract uses a reentrancy guard on draw_credit and repay_credit as a
 //! defense-in-depth measure; if a token or future integration ever called back, the guard
 //! would revert.
-
+//!
+//! # Status
+//! `CreditStatus` changes and the operations each status permits are defined in one place,
+//! the `status` module's transition and operation tables. Every entrypoint that touches a
+//! line consults them, so e.g. Suspended and Defaulted lines cannot draw and Defaulted lines
+//! cannot go back to Active.
+//!
+//! # Interest
+//! Interest accrues on `utilized_amount` from the ledger timestamp at the annual
+//! `interest_rate_bps`, and is tracked separately from principal in `accrued_interest`.
+//! Any entrypoint that touches a line accrues it up to the current ledger first.
+//!
+//! # Billing
+//! With a `BillingConfig` set, lines run fixed-length billing cycles. Touching a line also
+//! closes any cycles that have ended, issuing a statement with a minimum payment and due
+//! date; see the `billing` module.
+
+mod assets;
+mod billing;
+mod collateral;
+mod delinquency;
 mod events;
+mod exposure;
+mod fees;
+mod interest;
+mod lending_pool;
+mod liquidation;
+mod migration;
+mod oracle;
+mod portfolio;
+mod rate_model;
+mod registry;
+mod repayment;
+mod risk_tiers;
+mod status;
+mod term_loan;
+mod ttl;
 mod types;
 
 use soroban_sdk::{
-    contract, contractimpl, contracttype, symbol_short, token, Address, Env, Symbol,
+    contract, contracterror, contractimpl, contracttype, symbol_short, token, Address, BytesN, Env,
+    Symbol, Vec,
 };
 
 use events::{
-    publish_credit_line_event, publish_drawn_event, publish_repayment_event,
-    publish_risk_parameters_updated, CreditLineEvent, DrawnEvent, RepaymentEvent,
-    RiskParametersUpdatedEvent,
+    publish_admin_proposed, publish_admin_transferred, publish_collateral,
+    publish_credit_line_event, publish_drawn_event, publish_fee_charged, publish_interest_accrued,
+    publish_liquidation, publish_migrated, publish_pause_changed, publish_repayment_event,
+    publish_risk_parameters_updated, publish_risk_tiers_set, publish_role_granted,
+    publish_role_revoked, publish_term_loan_originated, publish_term_loan_payment,
+    publish_tier_changed, publish_upgraded, publish_write_off, AdminProposedEvent,
+    AdminTransferredEvent, CollateralEvent, CreditLineEvent, DrawnEvent, FeeChargedEvent,
+    InterestAccruedEvent, LiquidationEvent, PauseChangedEvent, RepaymentEvent,
+    RiskParametersUpdatedEvent, RoleChangedEvent, TermLoanOriginatedEvent, TermLoanPaymentEvent,
+    TierChangedEvent, WriteOffEvent,
+};
+use repayment::RepaymentAllocation;
+use status::Operation;
+use types::{
+    AssetConfig, BillingConfig, CollateralConfig, CollateralPosition, CreditLineData, CreditStatus,
+    Delinquency, ExposureCaps, FeeConfig, Installment, LiquidationConfig, OracleConfig,
+    PortfolioStats, RateBreakdown, RateModel, RiskParameters, RiskTier, Role, Statement, TermLoan,
+    TtlConfig,
 };
-use types::{CreditLineData, CreditStatus};
 
 /// Maximum interest rate in basis points (100%).
 const MAX_INTEREST_RATE_BPS: u32 = 10_000;
@@ -29,65 +83,615 @@ const MAX_INTEREST_RATE_BPS: u32 = 10_000;
 /// Maximum risk score (0–100 scale).
 const MAX_RISK_SCORE: u32 = 100;
 
-/// Instance storage key for reentrancy guard.
-fn reentrancy_key(env: &Env) -> Symbol {
-    Symbol::new(env, "reentrancy")
-}
-
-/// Instance storage key for admin.
-fn admin_key(env: &Env) -> Symbol {
-    Symbol::new(env, "admin")
-}
-
-fn require_admin(env: &Env) -> Address {
-    env.storage()
-        .instance()
-        .get(&admin_key(env))
-        .expect("admin not set")
-}
-
-#[contracttype]
-#[derive(Debug, Clone, PartialEq)]
+/// Errors returned by Credit contract entrypoints. Codes are part of the public interface
+/// and must never be renumbered.
+#[contracterror]
+#[derive(Copy, Clone, Debug, Eq, PartialEq, PartialOrd, Ord)]
+#[repr(u32)]
 pub enum CreditError {
+    /// No credit line exists for the borrower.
     CreditLineNotFound = 1,
+    /// The credit line's status does not permit the operation.
     InvalidCreditStatus = 2,
+    /// Amount must be strictly positive.
     InvalidAmount = 3,
+    /// Not enough utilization for the operation.
     InsufficientUtilization = 4,
+    /// Caller is not allowed to perform the operation.
     Unauthorized = 5,
+    /// Contract has not been initialized (admin not set).
+    NotInitialized = 6,
+    /// Contract has already been initialized.
+    AlreadyInitialized = 7,
+    /// Draw would exceed the line's credit limit.
+    CreditLimitExceeded = 8,
+    /// Liquidity reserve cannot cover the requested draw.
+    InsufficientLiquidity = 9,
+    /// Arithmetic overflow.
+    Overflow = 10,
+    /// Reentrant call detected.
+    Reentrancy = 11,
+    /// The credit line is closed.
+    CreditLineClosed = 12,
+    /// Borrower already has an Active credit line.
+    ActiveCreditLineExists = 13,
+    /// Credit limit is out of range.
+    InvalidCreditLimit = 14,
+    /// Interest rate exceeds the maximum.
+    InvalidInterestRate = 15,
+    /// Risk score exceeds the maximum.
+    InvalidRiskScore = 16,
+    /// New credit limit is below the line's utilized amount.
+    CreditLimitBelowUtilized = 17,
+    /// An outstanding balance blocks the operation: the line's, when a borrower closes it
+    /// or it is reopened, or principal in the global token, when the lending pool changes.
+    UtilizationNotZero = 18,
+    /// No admin transfer is pending.
+    NoPendingAdmin = 19,
+    /// The credit line is suspended.
+    CreditLineSuspended = 20,
+    /// The credit line is defaulted.
+    CreditLineDefaulted = 21,
+    /// The requested status change is not allowed from the line's current status.
+    InvalidStatusTransition = 22,
+    /// The contract is paused; new credit cannot be opened or drawn.
+    ContractPaused = 23,
+    /// More borrowers were passed to `migrate` than `MAX_MIGRATION_BATCH`.
+    MigrationBatchTooLarge = 24,
+    /// A TTL threshold exceeds its extension target, or a target exceeds the network maximum.
+    InvalidTtlConfig = 25,
+    /// The credit limit exceeds the per-borrower cap.
+    BorrowerLimitCapExceeded = 26,
+    /// The sum of credit limits would exceed the total committed limit cap.
+    CommittedLimitCapExceeded = 27,
+    /// Total principal outstanding would exceed the reserve cap.
+    OutstandingCapExceeded = 28,
+    /// An exposure cap is negative.
+    InvalidExposureCaps = 29,
+    /// The asset has no `AssetConfig`.
+    AssetNotConfigured = 30,
+    /// An `AssetConfig` has a negative cap or an inverted or out-of-range rate band.
+    InvalidAssetConfig = 31,
+    /// A `FeeConfig` has a percentage fee above 10000 bps or a negative late fee.
+    InvalidFeeConfig = 32,
+    /// A `BillingConfig` has a zero period, a due date beyond the period, a minimum payment
+    /// above 10000 bps or a negative floor.
+    InvalidBillingConfig = 33,
+    /// The borrower already has a term loan with principal outstanding.
+    TermLoanExists = 34,
+    /// The borrower has no term loan with principal outstanding.
+    TermLoanNotFound = 35,
+    /// Term loan principal, rate, number of periods or period length is out of range.
+    InvalidLoanTerms = 36,
+    /// The token has no `CollateralConfig`.
+    CollateralNotSupported = 37,
+    /// The borrower has less of the token deposited than the amount withdrawn.
+    InsufficientCollateral = 38,
+    /// The withdrawal would leave the line's debt above the collateral's borrowing power.
+    CollateralRatioTooLow = 39,
+    /// A `CollateralConfig` has a non-positive price or a ratio below 10000 bps.
+    InvalidCollateralConfig = 40,
+    /// A `LiquidationConfig` has a close factor outside 1..=10000 bps or a discount above
+    /// 5000 bps.
+    InvalidLiquidationConfig = 41,
+    /// The line is neither Defaulted nor undercollateralized, or has no debt the
+    /// collateral can pay for.
+    LineNotLiquidatable = 42,
+    /// The collateral token's price feed has no positive price for it.
+    OraclePriceUnavailable = 43,
+    /// The price feed's latest price is older than the configured maximum age.
+    StalePrice = 44,
+    /// The price feed's latest price moved from its previous one by more than the
+    /// configured maximum deviation.
+    PriceDeviationTooHigh = 45,
+    /// An `OracleConfig` has a zero maximum age.
+    InvalidOracleConfig = 46,
+    /// A `RateModel` has an optimal utilization outside 1..=10000 bps or a component above
+    /// 10000 bps.
+    InvalidRateModel = 47,
+    /// Risk tiers overlap, are out of order, exceed `MAX_TIERS` or have a score above 100,
+    /// a negative cap or a spread above 10000 bps.
+    InvalidRiskTiers = 48,
+    /// Risk tiers are set and none covers the risk score.
+    NoRiskTier = 49,
+    /// The credit limit or a draw is above the caps of the risk score's tier, or the
+    /// interest rate is below its spread.
+    RiskTierExceeded = 50,
 }
 
-impl From<CreditError> for soroban_sdk::Error {
-    fn from(val: CreditError) -> Self {
-        soroban_sdk::Error::from_contract_error(val as u32)
-    }
+/// Stored admin, falling back to the `Symbol` key used before schema version 3.
+fn read_admin(env: &Env) -> Option<Address> {
+    let instance = env.storage().instance();
+    instance
+        .get(&DataKey::Admin)
+        .or_else(|| instance.get(&migration::legacy_admin_key(env)))
 }
 
-fn require_admin_auth(env: &Env) -> Address {
-    let admin = require_admin(env);
+fn require_admin(env: &Env) -> Result<Address, CreditError> {
+    let admin = read_admin(env).ok_or(CreditError::NotInitialized)?;
+    ttl::extend_instance(env);
+    Ok(admin)
+}
+
+fn require_admin_auth(env: &Env) -> Result<Address, CreditError> {
+    let admin = require_admin(env)?;
     admin.require_auth();
-    admin
+    Ok(admin)
 }
 
+/// Every key the contract stores, instance and persistent alike.
 #[contracttype]
 #[derive(Clone, Debug, Eq, PartialEq)]
 pub enum DataKey {
+    Admin,
+    /// Set while a guarded entrypoint is executing.
+    Reentrancy,
+    /// Persistent credit line record of a borrower.
+    CreditLine(Address),
     LiquidityToken,
     LiquiditySource,
+    /// Admin proposed via `propose_admin`, awaiting `accept_admin`.
+    PendingAdmin,
+    /// Whether an account holds a role.
+    Role(Role, Address),
+    /// Global emergency pause flag.
+    Paused,
+    /// Storage schema version the stored data is known to match.
+    SchemaVersion,
+    /// TTL thresholds set by the admin; see `ttl::DEFAULT_TTL_CONFIG`.
+    TtlConfig,
+    /// Number of borrowers ever registered.
+    BorrowerCount,
+    /// Borrower at a position of the global registry list.
+    Borrower(u32),
+    /// Number of lines currently in a status.
+    StatusCount(CreditStatus),
+    /// Borrower at a position of the registry list for a status.
+    StatusBorrower(CreditStatus, u32),
+    /// A borrower's positions in the registry lists.
+    RegistryEntry(Address),
+    /// Running portfolio totals; see the `portfolio` module.
+    PortfolioTotals,
+    /// Exposure caps set by the admin.
+    ExposureCaps,
+    /// Liquidity and risk settings of an asset.
+    AssetConfig(Address),
+    /// Principal outstanding on lines in an asset.
+    AssetUtilized(Address),
+    /// Sum of `AssetUtilized` over all assets.
+    AssetUtilizedTotal,
+    /// Fee schedule set by the admin.
+    FeeConfig,
+    /// Billing cycle settings set by the admin.
+    BillingConfig,
+    /// Persistent billing state of a borrower's line.
+    Statement(Address),
+    /// Persistent delinquency state of a borrower's line.
+    Delinquency(Address),
+    /// Seconds past due after which `process_delinquency` defaults a line.
+    DefaultGracePeriod,
+    /// Persistent term loan of a borrower.
+    TermLoan(Address),
+    /// Valuation settings of a collateral token.
+    CollateralConfig(Address),
+    /// Persistent deposit of a borrower in a collateral token.
+    Collateral(Address, Address),
+    /// Persistent list of tokens a borrower has deposited.
+    CollateralTokens(Address),
+    /// Persistent flag marking a borrower's line as secured.
+    SecuredLine(Address),
+    /// Liquidation settings set by the admin.
+    LiquidationConfig,
+    /// Price feed of a collateral token.
+    OracleConfig(Address),
+    /// Lending pool backing lines in the global liquidity token.
+    LendingPool,
+    /// Utilization-based rate model set by the admin.
+    RateModel,
+    /// Risk tiers set by the admin.
+    RiskTiers,
+    /// Principal outstanding on term loans, all in the global liquidity token.
+    TermPrincipal,
+}
+
+fn has_role(env: &Env, role: Role, account: &Address) -> bool {
+    env.storage()
+        .instance()
+        .get(&DataKey::Role(role, account.clone()))
+        .unwrap_or(false)
+}
+
+/// Require that `caller` authorized this call and holds `role`.
+fn require_role(env: &Env, role: Role, caller: &Address) -> Result<(), CreditError> {
+    caller.require_auth();
+    if !has_role(env, role, caller) {
+        return Err(CreditError::Unauthorized);
+    }
+    ttl::extend_instance(env);
+    Ok(())
+}
+
+/// Require that `caller` authorized this call and is the admin or a guardian.
+fn require_admin_or_guardian(env: &Env, caller: &Address) -> Result<(), CreditError> {
+    caller.require_auth();
+    if *caller != require_admin(env)? && !has_role(env, Role::Guardian, caller) {
+        return Err(CreditError::Unauthorized);
+    }
+    Ok(())
+}
+
+fn is_paused(env: &Env) -> bool {
+    env.storage()
+        .instance()
+        .get(&DataKey::Paused)
+        .unwrap_or(false)
+}
+
+fn ensure_not_paused(env: &Env) -> Result<(), CreditError> {
+    if is_paused(env) {
+        return Err(CreditError::ContractPaused);
+    }
+    Ok(())
+}
+
+/// Set the pause flag and emit an event if it changed.
+fn set_paused(env: &Env, caller: Address, paused: bool) -> Result<(), CreditError> {
+    require_admin_or_guardian(env, &caller)?;
+    if is_paused(env) == paused {
+        return Ok(());
+    }
+    env.storage().instance().set(&DataKey::Paused, &paused);
+    publish_pause_changed(env, PauseChangedEvent { paused, caller });
+    Ok(())
+}
+
+/// Load the credit line stored for `borrower`, extending its TTL.
+/// Lines still under a legacy key or layout are upgraded in memory; `migrate` rewrites them.
+fn load_credit_line(env: &Env, borrower: &Address) -> Result<CreditLineData, CreditError> {
+    let stored =
+        migration::read_credit_line(env, borrower).ok_or(CreditError::CreditLineNotFound)?;
+    if stored.legacy_key {
+        ttl::extend_persistent(env, borrower);
+    } else {
+        ttl::extend_persistent(env, &DataKey::CreditLine(borrower.clone()));
+    }
+    Ok(stored.line)
+}
+
+/// Persist `credit_line` under `DataKey::CreditLine`, extending its TTL, updating the
+/// portfolio totals and dropping any copy left under the legacy bare-address key.
+///
+/// Lines are counted in the totals from the moment they are registered, so the caller must
+/// register a new line (`registry::record_status`) right after its first save.
+fn save_credit_line(env: &Env, credit_line: &CreditLineData) -> Result<(), CreditError> {
+    let borrower = &credit_line.borrower;
+    let before = if registry::is_registered(env, borrower) {
+        migration::read_credit_line(env, borrower).map(|stored| stored.line)
+    } else {
+        None
+    };
+    portfolio::apply(env, before.as_ref(), credit_line)?;
+
+    let persistent = env.storage().persistent();
+    let key = DataKey::CreditLine(borrower.clone());
+    persistent.set(&key, credit_line);
+    ttl::extend_persistent(env, &key);
+    if persistent.has(&credit_line.borrower) {
+        persistent.remove(&credit_line.borrower);
+    }
+    Ok(())
 }
 
 /// Assert reentrancy guard is not set; set it for the duration of the call.
-/// Caller must call clear_reentrancy_guard when done (on all paths).
-fn set_reentrancy_guard(env: &Env) {
-    let key = reentrancy_key(env);
-    let current: bool = env.storage().instance().get(&key).unwrap_or(false);
+/// Prefer `with_reentrancy_guard`, which clears the guard on all paths.
+fn set_reentrancy_guard(env: &Env) -> Result<(), CreditError> {
+    let current: bool = env
+        .storage()
+        .instance()
+        .get(&DataKey::Reentrancy)
+        .unwrap_or(false);
     if current {
-        panic!("reentrancy guard");
+        return Err(CreditError::Reentrancy);
     }
-    env.storage().instance().set(&key, &true);
+    env.storage().instance().set(&DataKey::Reentrancy, &true);
+    Ok(())
 }
 
 fn clear_reentrancy_guard(env: &Env) {
-    env.storage().instance().set(&reentrancy_key(env), &false);
+    env.storage().instance().set(&DataKey::Reentrancy, &false);
+}
+
+/// Run `f` while holding the reentrancy guard, clearing it whatever `f` returns.
+fn with_reentrancy_guard<T>(
+    env: &Env,
+    f: impl FnOnce() -> Result<T, CreditError>,
+) -> Result<T, CreditError> {
+    set_reentrancy_guard(env)?;
+    let result = f();
+    clear_reentrancy_guard(env);
+    result
+}
+
+/// Accrue interest on `credit_line` up to the current ledger and emit an event if any accrued.
+fn accrue_line_interest(env: &Env, credit_line: &mut CreditLineData) -> Result<(), CreditError> {
+    let interest = interest::accrue(env, credit_line)?;
+    if interest > 0 {
+        publish_interest_accrued(
+            env,
+            InterestAccruedEvent {
+                borrower: credit_line.borrower.clone(),
+                interest,
+                accrued_interest: credit_line.accrued_interest,
+                timestamp: credit_line.last_accrual_ts,
+            },
+        );
+    }
+    Ok(())
+}
+
+/// Bring `credit_line` up to the current ledger: accrue interest, re-price it under the rate
+/// model if one is set, close any billing cycles that have ended and re-assess its
+/// delinquency, which is returned while the line is billed.
+fn refresh_line(
+    env: &Env,
+    credit_line: &mut CreditLineData,
+) -> Result<Option<Delinquency>, CreditError> {
+    accrue_line_interest(env, credit_line)?;
+    rate_model::apply(env, credit_line)?;
+    billing::roll(env, credit_line)?;
+    Ok(delinquency::update(env, credit_line))
+}
+
+/// Publish a `(credit, tier_chg)` event if the tier of a line whose score was `old_score`
+/// (`None` for a new line) differs from the tier of `new_score`.
+fn publish_tier_change(env: &Env, borrower: &Address, old_score: Option<u32>, new_score: u32) {
+    let old_tier = old_score
+        .and_then(|score| risk_tiers::tier_for(env, score))
+        .map(|(index, _)| index);
+    let new_tier = risk_tiers::tier_for(env, new_score).map(|(index, _)| index);
+    if old_tier != new_tier {
+        publish_tier_changed(
+            env,
+            TierChangedEvent {
+                borrower: borrower.clone(),
+                risk_score: new_score,
+                old_tier,
+                new_tier,
+            },
+        );
+    }
+}
+
+/// Publish a lifecycle event carrying the line's current parameters.
+fn publish_status_event(env: &Env, event_type: Symbol, credit_line: &CreditLineData) {
+    publish_credit_line_event(
+        env,
+        (symbol_short!("credit"), event_type.clone()),
+        CreditLineEvent {
+            event_type,
+            borrower: credit_line.borrower.clone(),
+            status: credit_line.status,
+            credit_limit: credit_line.credit_limit,
+            interest_rate_bps: credit_line.interest_rate_bps,
+            risk_score: credit_line.risk_score,
+        },
+    );
+}
+
+/// Check `params` against the protocol bounds, the exposure caps and the line's current
+/// utilization.
+fn validate_risk_parameters(
+    env: &Env,
+    credit_line: &CreditLineData,
+    params: &RiskParameters,
+) -> Result<(), CreditError> {
+    if params.credit_limit < 0 {
+        return Err(CreditError::InvalidCreditLimit);
+    }
+    if params.credit_limit < credit_line.utilized_amount {
+        return Err(CreditError::CreditLimitBelowUtilized);
+    }
+    if params.interest_rate_bps > MAX_INTEREST_RATE_BPS {
+        return Err(CreditError::InvalidInterestRate);
+    }
+    if params.risk_score > MAX_RISK_SCORE {
+        return Err(CreditError::InvalidRiskScore);
+    }
+    risk_tiers::check_terms(
+        env,
+        params.risk_score,
+        params.credit_limit,
+        params.interest_rate_bps,
+    )?;
+    assets::check_terms(
+        env,
+        &credit_line.asset,
+        params.credit_limit,
+        params.interest_rate_bps,
+    )?;
+    exposure::check_limit_change(env, credit_line.credit_limit, params.credit_limit)
+}
+
+/// Shared body of `open_credit_line` and `open_asset_credit_line`.
+fn open_line(
+    env: &Env,
+    caller: Address,
+    borrower: Address,
+    asset: Option<Address>,
+    credit_limit: i128,
+    interest_rate_bps: u32,
+    risk_score: u32,
+) -> Result<(), CreditError> {
+    require_role(env, Role::RiskEngine, &caller)?;
+    ensure_not_paused(env)?;
+
+    if credit_limit <= 0 {
+        return Err(CreditError::InvalidCreditLimit);
+    }
+    if interest_rate_bps > MAX_INTEREST_RATE_BPS {
+        return Err(CreditError::InvalidInterestRate);
+    }
+    if risk_score > MAX_RISK_SCORE {
+        return Err(CreditError::InvalidRiskScore);
+    }
+
+    // Only a Closed line that owes nothing may be replaced; anything else still carries
+    // live state, and replacing a force-closed line would forgive its balance.
+    if let Ok(existing) = load_credit_line(env, &borrower) {
+        status::ensure_operation(existing.status, Operation::Reopen)?;
+        if existing.utilized_amount != 0
+            || existing.accrued_interest != 0
+            || existing.accrued_fees != 0
+        {
+            return Err(CreditError::UtilizationNotZero);
+        }
+    }
+    risk_tiers::check_terms(env, risk_score, credit_limit, interest_rate_bps)?;
+    assets::check_terms(env, &asset, credit_limit, interest_rate_bps)?;
+    exposure::check_limit_change(env, 0, credit_limit)?;
+    let origination_fee = fees::origination_fee(env, credit_limit)?;
+    let mut credit_line = CreditLineData {
+        borrower: borrower.clone(),
+        asset,
+        credit_limit,
+        utilized_amount: 0,
+        interest_rate_bps,
+        risk_score,
+        status: CreditStatus::Active,
+        accrued_interest: 0,
+        accrued_fees: origination_fee,
+        last_accrual_ts: env.ledger().timestamp(),
+    };
+    rate_model::apply(env, &mut credit_line)?;
+
+    save_credit_line(env, &credit_line)?;
+    registry::record_status(env, &borrower, CreditStatus::Active);
+    delinquency::reset(env, &borrower);
+    billing::start(env, &borrower);
+    publish_status_event(env, symbol_short!("opened"), &credit_line);
+    publish_tier_change(env, &credit_line.borrower, None, risk_score);
+    if origination_fee > 0 {
+        publish_fee_charged(
+            env,
+            symbol_short!("orig_fee"),
+            FeeChargedEvent {
+                borrower,
+                fee: origination_fee,
+                accrued_fees: credit_line.accrued_fees,
+                timestamp: credit_line.last_accrual_ts,
+            },
+        );
+    }
+    Ok(())
+}
+
+/// Pay `amount` from the liquidity source of `asset` to `recipient`, failing with
+/// `InsufficientLiquidity` if the source cannot cover it. `check` sees the source's
+/// balance before the transfer. A lending pool lends the amount itself. No-op when no
+/// liquidity token is configured.
+fn disburse(
+    env: &Env,
+    asset: &Option<Address>,
+    recipient: &Address,
+    amount: i128,
+    check: impl FnOnce(i128) -> Result<(), CreditError>,
+) -> Result<(), CreditError> {
+    let (token_address, reserve_address) = assets::liquidity(env, asset)?;
+    let Some(token_address) = token_address else {
+        return Ok(());
+    };
+    if let Some(pool) = lending_pool::for_asset(env, asset) {
+        let pool = lending_pool::LendingPoolClient::new(env, &pool);
+        let available = pool.available_liquidity();
+        if available < amount {
+            return Err(CreditError::InsufficientLiquidity);
+        }
+        check(available)?;
+        pool.borrow(recipient, &amount);
+        return Ok(());
+    }
+    let token_client = token::Client::new(env, &token_address);
+    let reserve_balance = token_client.balance(&reserve_address);
+    if reserve_balance < amount {
+        return Err(CreditError::InsufficientLiquidity);
+    }
+    check(reserve_balance)?;
+    token_client.transfer(&reserve_address, recipient, &amount);
+    Ok(())
+}
+
+/// Transfer a repayment from `payer`: the fee share to the fee collector (or the liquidity
+/// source if no fee schedule is set) and the rest to the liquidity source of `asset`, and
+/// report it to the lending pool, if any. No-op when no liquidity token is configured.
+fn settle_repayment(
+    env: &Env,
+    asset: &Option<Address>,
+    payer: &Address,
+    allocation: &RepaymentAllocation,
+) -> Result<(), CreditError> {
+    let (token_address, reserve_address) = assets::liquidity(env, asset)?;
+    let Some(token_address) = token_address else {
+        return Ok(());
+    };
+    let token_client = token::Client::new(env, &token_address);
+    let fee_collector =
+        fees::config(env).map_or(reserve_address.clone(), |config| config.fee_collector);
+    if allocation.fees > 0 {
+        token_client.transfer(payer, &fee_collector, &allocation.fees);
+    }
+    let mut to_reserve = allocation.total() - allocation.fees;
+    if to_reserve > 0 {
+        token_client.transfer(payer, &reserve_address, &to_reserve);
+    }
+    if fee_collector == reserve_address {
+        to_reserve += allocation.fees;
+    }
+    lending_pool::record_repayment(env, asset, allocation.principal, to_reserve);
+    Ok(())
+}
+
+/// Pay `amount` from `payer` to the liquidity source of `asset`, of which `principal`
+/// repays principal, and report it to the lending pool, if any. No-op when no liquidity
+/// token is configured.
+fn collect(
+    env: &Env,
+    asset: &Option<Address>,
+    payer: &Address,
+    amount: i128,
+    principal: i128,
+) -> Result<(), CreditError> {
+    let (token_address, reserve_address) = assets::liquidity(env, asset)?;
+    if let Some(token_address) = token_address.filter(|_| amount > 0) {
+        token::Client::new(env, &token_address).transfer(payer, &reserve_address, &amount);
+        lending_pool::record_repayment(env, asset, principal, amount);
+    }
+    Ok(())
+}
+
+/// Move `credit_line` to `to` if the state machine allows it, persist it and emit the
+/// `(credit, event_type)` lifecycle event.
+fn transition_status(
+    env: &Env,
+    credit_line: &mut CreditLineData,
+    to: CreditStatus,
+    event_type: Symbol,
+) -> Result<(), CreditError> {
+    status::ensure_transition(credit_line.status, to)?;
+    credit_line.status = to;
+    save_credit_line(env, credit_line)?;
+    registry::record_status(env, &credit_line.borrower, to);
+    publish_status_event(env, event_type, credit_line);
+    Ok(())
+}
+
+fn load_credit_lines(env: &Env, borrowers: Vec<Address>) -> Vec<CreditLineData> {
+    let mut lines = Vec::new(env);
+    for borrower in borrowers.iter() {
+        if let Ok(line) = load_credit_line(env, &borrower) {
+            lines.push_back(line);
+        }
+    }
+    lines
 }
 
 #[contract]
@@ -97,291 +701,1222 @@ pub struct Credit;
 impl Credit {
     /// @notice Initializes contract-level configuration.
     /// @dev Sets admin and defaults liquidity source to this contract address.
-    pub fn init(env: Env, admin: Address) -> () {
-        env.storage().instance().set(&admin_key(&env), &admin);
+    /// Can only be called once; fails with `AlreadyInitialized` afterwards.
+    pub fn init(env: Env, admin: Address) -> Result<(), CreditError> {
+        if read_admin(&env).is_some() {
+            return Err(CreditError::AlreadyInitialized);
+        }
+        env.storage().instance().set(&DataKey::Admin, &admin);
         env.storage()
             .instance()
             .set(&DataKey::LiquiditySource, &env.current_contract_address());
-        ()
+        env.storage()
+            .instance()
+            .set(&DataKey::SchemaVersion, &migration::SCHEMA_VERSION);
+        ttl::extend_instance(&env);
+        Ok(())
     }
 
-    /// @notice Sets the token contract used for reserve/liquidity checks and draw transfers.
-    /// @dev Admin-only.
-    pub fn set_liquidity_token(env: Env, token_address: Address) -> () {
-        require_admin_auth(&env);
+    /// @notice Replaces the contract code with the uploaded WASM `new_wasm_hash`.
+    /// @dev Admin-only. Storage is kept; if the new code bumps the schema version, the admin
+    /// then runs `migrate` in batches and `complete_migration`.
+    ///
+    /// Emits `(credit, upgraded)` with the new hash.
+    pub fn upgrade(env: Env, new_wasm_hash: BytesN<32>) -> Result<(), CreditError> {
+        require_admin_auth(&env)?;
+        env.deployer()
+            .update_current_contract_wasm(new_wasm_hash.clone());
+        publish_upgraded(&env, new_wasm_hash);
+        Ok(())
+    }
+
+    /// @notice Rewrites the stored credit lines of `borrowers` under the current key and
+    /// layout, registers them in the borrower index, and moves instance config off legacy
+    /// keys.
+    /// @dev Admin-only. Borrowers without a line or already migrated are skipped, so
+    /// batches can safely overlap or be retried. Returns how many lines were rewritten.
+    ///
+    /// # Errors
+    /// * `MigrationBatchTooLarge` if more than `MAX_MIGRATION_BATCH` borrowers are passed.
+    pub fn migrate(env: Env, borrowers: Vec<Address>) -> Result<u32, CreditError> {
+        require_admin_auth(&env)?;
+        if borrowers.len() > migration::MAX_MIGRATION_BATCH {
+            return Err(CreditError::MigrationBatchTooLarge);
+        }
+        migration::migrate_instance_keys(&env);
+        let mut migrated = 0;
+        for borrower in borrowers.iter() {
+            if let Some(stored) = migration::read_credit_line(&env, &borrower) {
+                let counted = registry::is_registered(&env, &borrower);
+                if stored.legacy_key || stored.legacy_layout || !counted {
+                    save_credit_line(&env, &stored.line)?;
+                    migrated += 1;
+                }
+                registry::record_status(&env, &borrower, stored.line.status);
+            }
+        }
+        Ok(migrated)
+    }
+
+    /// @notice Records that all stored data now matches this build's schema version.
+    /// @dev Admin-only. Call once every batch of `migrate` has run.
+    ///
+    /// Emits `(credit, migrated)` with the new schema version.
+    pub fn complete_migration(env: Env) -> Result<(), CreditError> {
+        require_admin_auth(&env)?;
         env.storage()
             .instance()
-            .set(&DataKey::LiquidityToken, &token_address);
-        ()
+            .set(&DataKey::SchemaVersion, &migration::SCHEMA_VERSION);
+        publish_migrated(&env, migration::SCHEMA_VERSION);
+        Ok(())
     }
 
-    /// @notice Sets the address that provides liquidity for draw operations.
-    /// @dev Admin-only. If unset, init config uses the contract address.
-    pub fn set_liquidity_source(env: Env, reserve_address: Address) -> () {
-        require_admin_auth(&env);
+    /// @notice Sets the TTL thresholds used to keep instance config and credit lines alive.
+    /// @dev Admin-only. Fails with `InvalidTtlConfig` if a threshold exceeds its target or a
+    /// target exceeds the network's maximum TTL.
+    pub fn set_ttl_config(env: Env, config: TtlConfig) -> Result<(), CreditError> {
+        require_admin_auth(&env)?;
+        if !ttl::is_valid(&env, &config) {
+            return Err(CreditError::InvalidTtlConfig);
+        }
+        env.storage().instance().set(&DataKey::TtlConfig, &config);
+        ttl::extend_instance(&env);
+        Ok(())
+    }
+
+    /// @notice Sets the protocol-wide exposure caps; zero disables a cap.
+    /// @dev Admin-only. Fails with `InvalidExposureCaps` if a limit cap is negative. New
+    /// caps apply to later opens, limit increases and draws; existing lines are untouched.
+    pub fn set_exposure_caps(env: Env, caps: ExposureCaps) -> Result<(), CreditError> {
+        require_admin_auth(&env)?;
+        if caps.max_borrower_limit < 0 || caps.max_total_committed_limit < 0 {
+            return Err(CreditError::InvalidExposureCaps);
+        }
+        env.storage().instance().set(&DataKey::ExposureCaps, &caps);
+        Ok(())
+    }
+
+    /// @notice Sets the liquidity source, caps and rate band for lines in `asset`.
+    /// @dev Admin-only. Fails with `InvalidAssetConfig` if a cap is negative or the rate
+    /// band is inverted or above 10000 bps. Rate and limit bounds apply to later opens and
+    /// risk updates; the liquidity source applies to every later draw and repayment.
+    pub fn set_asset_config(
+        env: Env,
+        asset: Address,
+        config: AssetConfig,
+    ) -> Result<(), CreditError> {
+        require_admin_auth(&env)?;
+        if !assets::is_valid(&config) {
+            return Err(CreditError::InvalidAssetConfig);
+        }
         env.storage()
             .instance()
-            .set(&DataKey::LiquiditySource, &reserve_address);
-        ()
+            .set(&DataKey::AssetConfig(asset), &config);
+        Ok(())
     }
 
-    /// Open a new credit line for a borrower (called by backend/risk engine).
-    ///
-    /// # Arguments
-    /// * `borrower` - The address of the borrower
-    /// * `credit_limit` - Maximum borrowable amount (must be > 0)
-    /// * `interest_rate_bps` - Annual interest rate in basis points (max 10000 = 100%)
-    /// * `risk_score` - Borrower risk score (0–100)
-    ///
-    /// # Panics
-    /// * If `credit_limit` <= 0
-    /// * If `interest_rate_bps` > 10000
-    /// * If `risk_score` > 100
-    /// * If an Active credit line already exists for the borrower
-    ///
-    /// # Events
-    /// Emits `(credit, opened)` with a `CreditLineEvent` payload.
-    pub fn open_credit_line(
+    /// @notice Sets the fee schedule and the address that receives fee repayments.
+    /// @dev Admin-only. Fails with `InvalidFeeConfig` if a percentage fee exceeds 10000 bps
+    /// or the late fee is negative. Fees already charged to lines are untouched.
+    pub fn set_fee_config(env: Env, config: FeeConfig) -> Result<(), CreditError> {
+        require_admin_auth(&env)?;
+        if !fees::is_valid(&config) {
+            return Err(CreditError::InvalidFeeConfig);
+        }
+        env.storage().instance().set(&DataKey::FeeConfig, &config);
+        Ok(())
+    }
+
+    /// @notice Sets the price, minimum collateral ratio and liquidation ratio of a collateral
+    /// token.
+    /// @dev Admin-only. Fails with `InvalidCollateralConfig` if the price is not positive, the
+    /// liquidation ratio is below 10000 bps or above the minimum ratio. Applies to every
+    /// existing deposit of the token.
+    pub fn set_collateral_config(
         env: Env,
-        borrower: Address,
-        credit_limit: i128,
-        interest_rate_bps: u32,
-        risk_score: u32,
-    ) {
-        assert!(credit_limit > 0, "credit_limit must be greater than zero");
-        assert!(
-            interest_rate_bps <= 10_000,
-            "interest_rate_bps cannot exceed 10000 (100%)"
-        );
-        assert!(risk_score <= 100, "risk_score must be between 0 and 100");
+        token: Address,
+        config: CollateralConfig,
+    ) -> Result<(), CreditError> {
+        require_admin_auth(&env)?;
+        if !collateral::is_valid(&config) {
+            return Err(CreditError::InvalidCollateralConfig);
+        }
+        env.storage()
+            .instance()
+            .set(&DataKey::CollateralConfig(token), &config);
+        Ok(())
+    }
 
-        // Prevent overwriting an existing Active credit line
-        if let Some(existing) = env
-            .storage()
-            .persistent()
-            .get::<Address, CreditLineData>(&borrower)
-        {
-            assert!(
-                existing.status != CreditStatus::Active,
-                "borrower already has an active credit line"
-            );
+    /// @notice Prices a collateral token from a SEP-40 price feed instead of its fixed price.
+    /// @dev Admin-only. Fails with `InvalidOracleConfig` if the maximum age is zero. Applies
+    /// to every existing deposit of the token.
+    pub fn set_oracle_config(
+        env: Env,
+        token: Address,
+        config: OracleConfig,
+    ) -> Result<(), CreditError> {
+        require_admin_auth(&env)?;
+        if !oracle::is_valid(&config) {
+            return Err(CreditError::InvalidOracleConfig);
         }
-        let credit_line = CreditLineData {
-            borrower: borrower.clone(),
-            credit_limit,
-            utilized_amount: 0,
-            interest_rate_bps,
-            risk_score,
-            status: CreditStatus::Active,
-        };
+        env.storage()
+            .instance()
+            .set(&DataKey::OracleConfig(token), &config);
+        Ok(())
+    }
 
-        env.storage().persistent().set(&borrower, &credit_line);
+    /// @notice Returns a collateral token to its fixed price.
+    /// @dev Admin-only.
+    pub fn remove_oracle_config(env: Env, token: Address) -> Result<(), CreditError> {
+        require_admin_auth(&env)?;
+        env.storage()
+            .instance()
+            .remove(&DataKey::OracleConfig(token));
+        Ok(())
+    }
 
-        publish_credit_line_event(
-            &env,
-            (symbol_short!("credit"), symbol_short!("opened")),
-            CreditLineEvent {
-                event_type: symbol_short!("opened"),
-                borrower: borrower.clone(),
-                status: CreditStatus::Active,
-                credit_limit,
-                interest_rate_bps,
-                risk_score,
-            },
-        );
+    /// @notice Returns the price feed of a collateral token, if it has one.
+    pub fn get_oracle_config(env: Env, token: Address) -> Option<OracleConfig> {
+        oracle::config(&env, &token)
     }
 
-    /// @notice Draws credit by transferring liquidity tokens to the borrower.
-    /// @dev Enforces status/limit/liquidity checks and uses a reentrancy guard.
-    pub fn draw_credit(env: Env, borrower: Address, amount: i128) -> () {
-        set_reentrancy_guard(&env);
-        borrower.require_auth();
-
-        if amount <= 0 {
-            clear_reentrancy_guard(&env);
-            panic!("amount must be positive");
-        }
+    /// @notice Returns the price a collateral token is valued at, scaled by 10^7.
+    /// @dev Fails with `CollateralNotSupported` if the token has no `CollateralConfig`, or
+    /// with `OraclePriceUnavailable`, `StalePrice` or `PriceDeviationTooHigh` if its feed's
+    /// price is rejected.
+    pub fn get_collateral_price(env: Env, token: Address) -> Result<i128, CreditError> {
+        let config = collateral::require_config(&env, &token)?;
+        collateral::price(&env, &token, &config)
+    }
 
-        let token_address: Option<Address> = env.storage().instance().get(&DataKey::LiquidityToken);
-        let reserve_address: Address = env
-            .storage()
+    /// @notice Returns the configuration of a collateral token, if it is accepted.
+    pub fn get_collateral_config(env: Env, token: Address) -> Option<CollateralConfig> {
+        collateral::config(&env, &token)
+    }
+
+    /// @notice Sets the close factor and collateral discount used by `liquidate`.
+    /// @dev Admin-only. Fails with `InvalidLiquidationConfig` if the close factor is outside
+    /// 1..=10000 bps or the discount exceeds 5000 bps.
+    pub fn set_liquidation_config(env: Env, config: LiquidationConfig) -> Result<(), CreditError> {
+        require_admin_auth(&env)?;
+        if !liquidation::is_valid(&config) {
+            return Err(CreditError::InvalidLiquidationConfig);
+        }
+        env.storage()
             .instance()
-            .get(&DataKey::LiquiditySource)
-            .unwrap_or(env.current_contract_address());
+            .set(&DataKey::LiquidationConfig, &config);
+        Ok(())
+    }
 
-        let mut credit_line: CreditLineData = env
-            .storage()
-            .persistent()
-            .get(&borrower)
-            .expect("Credit line not found");
+    /// @notice Returns the liquidation settings in effect.
+    pub fn get_liquidation_config(env: Env) -> LiquidationConfig {
+        liquidation::config(&env)
+    }
 
-        if credit_line.status == CreditStatus::Closed {
-            clear_reentrancy_guard(&env);
-            panic!("credit line is closed");
+    /// @notice Prices every credit line from its liquidity source's utilization and its risk
+    /// score instead of its static rate.
+    /// @dev Admin-only. Fails with `InvalidRateModel` if the optimal utilization is outside
+    /// 1..=10000 bps or a component exceeds 10000 bps. Lines take the new rate the next time
+    /// they are touched, after accruing at their previous rate.
+    pub fn set_rate_model(env: Env, model: RateModel) -> Result<(), CreditError> {
+        require_admin_auth(&env)?;
+        if !rate_model::is_valid(&model) {
+            return Err(CreditError::InvalidRateModel);
         }
+        env.storage().instance().set(&DataKey::RateModel, &model);
+        Ok(())
+    }
 
-        let updated_utilized = credit_line
-            .utilized_amount
-            .checked_add(amount)
-            .expect("overflow");
-
-        if updated_utilized > credit_line.credit_limit {
-            clear_reentrancy_guard(&env);
-            panic!("exceeds credit limit");
+    /// @notice Sets the risk tiers that bound the terms of lines by risk score; an empty
+    /// list removes them.
+    /// @dev Admin-only. Fails with `InvalidRiskTiers` if tiers overlap, are not in ascending
+    /// score order, exceed `MAX_TIERS` (10), or have a score above 100, a negative cap or a
+    /// rate above 10000 bps. New tiers apply to later opens, risk updates and draws;
+    /// existing terms are untouched. Emits `(credit, tiers_set)` with the new tiers.
+    pub fn set_risk_tiers(env: Env, tiers: Vec<RiskTier>) -> Result<(), CreditError> {
+        require_admin_auth(&env)?;
+        if !risk_tiers::is_valid(&tiers) {
+            return Err(CreditError::InvalidRiskTiers);
         }
+        env.storage().instance().set(&DataKey::RiskTiers, &tiers);
+        publish_risk_tiers_set(&env, tiers);
+        Ok(())
+    }
 
-        if let Some(token_address) = token_address {
-            let token_client = token::Client::new(&env, &token_address);
-            let reserve_balance = token_client.balance(&reserve_address);
-            if reserve_balance < amount {
-                clear_reentrancy_guard(&env);
-                panic!("Insufficient liquidity reserve for requested draw amount");
-            }
+    /// @notice Returns the risk tiers, in ascending score order.
+    pub fn get_risk_tiers(env: Env) -> Vec<RiskTier> {
+        risk_tiers::tiers(&env)
+    }
 
-            token_client.transfer(&reserve_address, &borrower, &amount);
-        }
+    /// @notice Returns lines to static rates. Each line keeps its last effective rate until
+    /// the risk engine updates it.
+    /// @dev Admin-only.
+    pub fn remove_rate_model(env: Env) -> Result<(), CreditError> {
+        require_admin_auth(&env)?;
+        env.storage().instance().remove(&DataKey::RateModel);
+        Ok(())
+    }
 
-        credit_line.utilized_amount = updated_utilized;
-        env.storage().persistent().set(&borrower, &credit_line);
-        let timestamp = env.ledger().timestamp();
-        publish_drawn_event(
-            &env,
-            DrawnEvent {
-                borrower,
-                amount,
-                new_utilized_amount: updated_utilized,
-                timestamp,
-            },
-        );
-        clear_reentrancy_guard(&env);
-        ()
+    /// @notice Returns the rate model, if one is set.
+    pub fn get_rate_model(env: Env) -> Option<RateModel> {
+        rate_model::model(&env)
     }
 
-    /// Repay credit (borrower).
-    /// Reverts if credit line does not exist, is Closed, or borrower has not authorized.
-    /// Reduces utilized_amount by amount (capped at 0). Emits RepaymentEvent.
-    pub fn repay_credit(env: Env, borrower: Address, amount: i128) {
-        set_reentrancy_guard(&env);
-        borrower.require_auth();
-        let mut credit_line: CreditLineData = env
-            .storage()
-            .persistent()
-            .get(&borrower)
-            .expect("Credit line not found");
+    /// @notice Returns the fee schedule, if one is set.
+    pub fn get_fee_config(env: Env) -> Option<FeeConfig> {
+        fees::config(&env)
+    }
 
-        if credit_line.borrower != borrower {
-            panic!("Borrower mismatch for credit line");
+    /// @notice Sets the billing cycle length, payment window and minimum payment rule.
+    /// @dev Admin-only. Fails with `InvalidBillingConfig` if the period is zero, payments
+    /// fall due after the next statement would close, the minimum exceeds 10000 bps or the
+    /// floor is negative. Lines start billing from the next time they are touched; changes
+    /// apply to cycles that end afterwards.
+    pub fn set_billing_config(env: Env, config: BillingConfig) -> Result<(), CreditError> {
+        require_admin_auth(&env)?;
+        if !billing::is_valid(&config) {
+            return Err(CreditError::InvalidBillingConfig);
         }
+        env.storage()
+            .instance()
+            .set(&DataKey::BillingConfig, &config);
+        Ok(())
+    }
 
-        if credit_line.status == CreditStatus::Closed {
-            clear_reentrancy_guard(&env);
-            panic!("credit line is closed");
-        }
-        if amount <= 0 {
-            clear_reentrancy_guard(&env);
-            panic!("amount must be positive");
-        }
-        let new_utilized = credit_line.utilized_amount.saturating_sub(amount).max(0);
-        credit_line.utilized_amount = new_utilized;
-        env.storage().persistent().set(&borrower, &credit_line);
+    /// @notice Sets how long a line may stay past due before `process_delinquency` defaults
+    /// it; 0 disables automatic default.
+    /// @dev Admin-only. Applies to lines already past due.
+    pub fn set_default_grace_period(env: Env, grace_period_secs: u64) -> Result<(), CreditError> {
+        require_admin_auth(&env)?;
+        env.storage()
+            .instance()
+            .set(&DataKey::DefaultGracePeriod, &grace_period_secs);
+        Ok(())
+    }
+
+    /// @notice Returns the default grace period in seconds; 0 when automatic default is off.
+    pub fn get_default_grace_period(env: Env) -> u64 {
+        delinquency::default_grace_period(&env)
+    }
+
+    /// @notice Returns the billing settings, if billing is enabled.
+    pub fn get_billing_config(env: Env) -> Option<BillingConfig> {
+        billing::config(&env)
+    }
+
+    /// @notice Returns the configuration of `asset`, if any.
+    pub fn get_asset_config(env: Env, asset: Address) -> Option<AssetConfig> {
+        assets::config(&env, &asset)
+    }
+
+    /// @notice Returns the exposure caps in effect.
+    pub fn get_exposure_caps(env: Env) -> ExposureCaps {
+        exposure::caps(&env)
+    }
+
+    /// @notice Returns the TTL thresholds in effect.
+    pub fn get_ttl_config(env: Env) -> TtlConfig {
+        ttl::config(&env)
+    }
+
+    /// @notice Extends the TTL of `borrower`'s credit line, term loan, their records and the
+    /// contract instance.
+    /// @dev Permissionless, so keepers can keep debtor records from being archived.
+    /// Fails with `CreditLineNotFound` if the borrower has neither a line nor a term loan.
+    pub fn bump_credit_line(env: Env, borrower: Address) -> Result<(), CreditError> {
+        let has_loan = term_loan::extend(&env, &borrower);
+        match load_credit_line(&env, &borrower) {
+            Err(CreditError::CreditLineNotFound) if has_loan => return Ok(()),
+            result => result?,
+        };
+        registry::extend(&env, &borrower);
+        billing::extend(&env, &borrower);
+        delinquency::extend(&env, &borrower);
+        collateral::extend(&env, &borrower);
+        Ok(())
+    }
+
+    /// @notice Schema version the stored data is known to match. Deployments that predate
+    /// version tracking report version 1.
+    pub fn get_schema_version(env: Env) -> u32 {
+        env.storage()
+            .instance()
+            .get(&DataKey::SchemaVersion)
+            .unwrap_or(migration::LEGACY_SCHEMA_VERSION)
+    }
 
-        let timestamp = env.ledger().timestamp();
-        publish_repayment_event(
+    /// @notice Proposes `new_admin` as the next admin (step one of a two-step transfer).
+    /// @dev Admin-only. Replaces any previously pending proposal. The current admin stays
+    /// in control until `new_admin` calls `accept_admin`.
+    ///
+    /// Emits `(credit, adm_prop)` with an `AdminProposedEvent` payload.
+    pub fn propose_admin(env: Env, new_admin: Address) -> Result<(), CreditError> {
+        let admin = require_admin_auth(&env)?;
+        env.storage()
+            .instance()
+            .set(&DataKey::PendingAdmin, &new_admin);
+        publish_admin_proposed(
             &env,
-            RepaymentEvent {
-                borrower: borrower.clone(),
-                amount,
-                new_utilized_amount: new_utilized,
-                timestamp,
+            AdminProposedEvent {
+                admin,
+                pending_admin: new_admin,
             },
         );
-        clear_reentrancy_guard(&env);
-        // TODO: accept token from borrower
+        Ok(())
     }
 
-    /// Update risk parameters for an existing credit line (admin only).
-    ///
-    /// # Arguments
-    /// * `borrower` - Borrower whose credit line to update.
-    /// * `credit_limit` - New credit limit (must be >= current utilized_amount and >= 0).
-    /// * `interest_rate_bps` - New interest rate in basis points (0 ..= 10000).
-    /// * `risk_score` - New risk score (0 ..= 100).
+    /// @notice Completes an admin transfer (step two). Must be authorized by the pending admin.
     ///
     /// # Errors
-    /// * Panics if caller is not the contract admin.
-    /// * Panics if no credit line exists for the borrower.
-    /// * Panics if bounds are violated (e.g. credit_limit < utilized_amount).
+    /// * `NoPendingAdmin` if no transfer has been proposed.
     ///
-    /// Emits a risk_updated event.
-    pub fn update_risk_parameters(
-        env: Env,
-        borrower: Address,
-        credit_limit: i128,
-        interest_rate_bps: u32,
-        risk_score: u32,
-    ) {
-        require_admin_auth(&env);
-
-        let mut credit_line: CreditLineData = env
+    /// Emits `(credit, adm_acpt)` with an `AdminTransferredEvent` payload.
+    pub fn accept_admin(env: Env) -> Result<(), CreditError> {
+        let pending_admin: Address = env
             .storage()
-            .persistent()
-            .get(&borrower)
-            .expect("Credit line not found");
-
-        if credit_limit < 0 {
-            panic!("credit_limit must be non-negative");
-        }
-        if credit_limit < credit_line.utilized_amount {
-            panic!("credit_limit cannot be less than utilized amount");
-        }
-        if interest_rate_bps > MAX_INTEREST_RATE_BPS {
-            panic!("interest_rate_bps exceeds maximum");
-        }
-        if risk_score > MAX_RISK_SCORE {
-            panic!("risk_score exceeds maximum");
-        }
+            .instance()
+            .get(&DataKey::PendingAdmin)
+            .ok_or(CreditError::NoPendingAdmin)?;
+        pending_admin.require_auth();
 
+        let previous_admin = require_admin(&env)?;
+        env.storage()
+            .instance()
+            .set(&DataKey::Admin, &pending_admin);
+        env.storage().instance().remove(&DataKey::PendingAdmin);
+
+        publish_admin_transferred(
+            &env,
+            AdminTransferredEvent {
+                previous_admin,
+                new_admin: pending_admin,
+            },
+        );
+        Ok(())
+    }
+
+    /// @notice Pauses the contract: `open_credit_line` and `draw_credit` fail with
+    /// `ContractPaused` until `unpause`. Repayment, closing and admin actions keep working.
+    /// @dev `caller` must be the admin or hold `Role::Guardian`. Idempotent; emits
+    /// `(credit, paused)` only when the flag changes.
+    pub fn pause(env: Env, caller: Address) -> Result<(), CreditError> {
+        set_paused(&env, caller, true)
+    }
+
+    /// @notice Lifts an emergency pause.
+    /// @dev `caller` must be the admin or hold `Role::Guardian`. Idempotent; emits
+    /// `(credit, unpaused)` only when the flag changes.
+    pub fn unpause(env: Env, caller: Address) -> Result<(), CreditError> {
+        set_paused(&env, caller, false)
+    }
+
+    /// @notice Whether the contract is paused.
+    pub fn is_paused(env: Env) -> bool {
+        is_paused(&env)
+    }
+
+    /// @notice Grants `role` to `account`.
+    /// @dev Admin-only. Idempotent; emits `(credit, role_grnt)` only when the role is newly
+    /// granted.
+    pub fn grant_role(env: Env, role: Role, account: Address) -> Result<(), CreditError> {
+        let admin = require_admin_auth(&env)?;
+        if has_role(&env, role, &account) {
+            return Ok(());
+        }
+        env.storage()
+            .instance()
+            .set(&DataKey::Role(role, account.clone()), &true);
+        publish_role_granted(
+            &env,
+            RoleChangedEvent {
+                role,
+                account,
+                admin,
+            },
+        );
+        Ok(())
+    }
+
+    /// @notice Revokes `role` from `account`.
+    /// @dev Admin-only. Idempotent; emits `(credit, role_rvk)` only when the role was held.
+    pub fn revoke_role(env: Env, role: Role, account: Address) -> Result<(), CreditError> {
+        let admin = require_admin_auth(&env)?;
+        if !has_role(&env, role, &account) {
+            return Ok(());
+        }
+        env.storage()
+            .instance()
+            .remove(&DataKey::Role(role, account.clone()));
+        publish_role_revoked(
+            &env,
+            RoleChangedEvent {
+                role,
+                account,
+                admin,
+            },
+        );
+        Ok(())
+    }
+
+    /// Whether `account` holds `role` (view function).
+    pub fn has_role(env: Env, role: Role, account: Address) -> bool {
+        has_role(&env, role, &account)
+    }
+
+    /// Current admin address (view function).
+    pub fn get_admin(env: Env) -> Result<Address, CreditError> {
+        require_admin(&env)
+    }
+
+    /// Admin proposed by `propose_admin` and not yet accepted, if any (view function).
+    pub fn get_pending_admin(env: Env) -> Option<Address> {
+        env.storage().instance().get(&DataKey::PendingAdmin)
+    }
+
+    /// @notice Sets the token contract used for reserve/liquidity checks and draw transfers.
+    /// @dev Admin-only.
+    pub fn set_liquidity_token(env: Env, token_address: Address) -> Result<(), CreditError> {
+        require_admin_auth(&env)?;
+        env.storage()
+            .instance()
+            .set(&DataKey::LiquidityToken, &token_address);
+        Ok(())
+    }
+
+    /// @notice Sets the address that provides liquidity for draw operations.
+    /// @dev Admin-only. If unset, init config uses the contract address.
+    pub fn set_liquidity_source(env: Env, reserve_address: Address) -> Result<(), CreditError> {
+        require_admin_auth(&env)?;
+        env.storage()
+            .instance()
+            .set(&DataKey::LiquiditySource, &reserve_address);
+        Ok(())
+    }
+
+    /// @notice Draws and repays lines in the global liquidity token through a lending pool.
+    /// @dev Admin-only. The pool's token becomes the global liquidity token, and the pool
+    /// takes the place of the liquidity source. Fails with `UtilizationNotZero` while any
+    /// line or term loan in the global token owes principal, since its repayments would go
+    /// to the new pool instead of the source that lent it.
+    pub fn set_lending_pool(env: Env, pool: Address) -> Result<(), CreditError> {
+        require_admin_auth(&env)?;
+        if portfolio::pool_utilized(&env, &None) > 0 {
+            return Err(CreditError::UtilizationNotZero);
+        }
+        let token = lending_pool::LendingPoolClient::new(&env, &pool).token();
+        let instance = env.storage().instance();
+        instance.set(&DataKey::LiquidityToken, &token);
+        instance.set(&DataKey::LendingPool, &pool);
+        Ok(())
+    }
+
+    /// @notice Returns the lending pool, if one is set.
+    pub fn get_lending_pool(env: Env) -> Option<Address> {
+        lending_pool::for_asset(&env, &None)
+    }
+
+    /// Open a new credit line for a borrower (risk engine only).
+    ///
+    /// # Arguments
+    /// * `caller` - Must hold `Role::RiskEngine` and authorize this call
+    /// * `borrower` - The address of the borrower
+    /// * `credit_limit` - Maximum borrowable amount (must be > 0)
+    /// * `interest_rate_bps` - Annual interest rate in basis points (max 10000 = 100%)
+    /// * `risk_score` - Borrower risk score (0–100)
+    ///
+    /// # Errors
+    /// * `Unauthorized` if `caller` does not hold `Role::RiskEngine`
+    /// * `InvalidCreditLimit` if `credit_limit` <= 0
+    /// * `InvalidInterestRate` if `interest_rate_bps` > 10000
+    /// * `InvalidRiskScore` if `risk_score` > 100
+    /// * `ActiveCreditLineExists` if an Active credit line already exists for the borrower
+    /// * `CreditLineSuspended` / `CreditLineDefaulted` if the existing line is in that status;
+    ///   only Closed lines may be replaced
+    /// * `UtilizationNotZero` if the existing Closed line still owes principal, interest or
+    ///   fees (e.g. it was force-closed by the admin)
+    /// * `ContractPaused` while the contract is paused
+    /// * `BorrowerLimitCapExceeded`, `CommittedLimitCapExceeded` if an exposure cap is breached
+    ///
+    /// # Events
+    /// Emits `(credit, opened)` with a `CreditLineEvent` payload, then `(credit, orig_fee)`
+    /// with a `FeeChargedEvent` if an origination fee is charged.
+    pub fn open_credit_line(
+        env: Env,
+        caller: Address,
+        borrower: Address,
+        credit_limit: i128,
+        interest_rate_bps: u32,
+        risk_score: u32,
+    ) -> Result<(), CreditError> {
+        open_line(
+            &env,
+            caller,
+            borrower,
+            None,
+            credit_limit,
+            interest_rate_bps,
+            risk_score,
+        )
+    }
+
+    /// Open a credit line drawn and repaid in `asset` (risk engine only).
+    ///
+    /// Takes the same arguments and checks as `open_credit_line`, plus:
+    ///
+    /// # Errors
+    /// * `AssetNotConfigured` if the admin has not called `set_asset_config` for `asset`
+    /// * `InvalidInterestRate` if `interest_rate_bps` is outside the asset's rate band
+    /// * `BorrowerLimitCapExceeded` if `credit_limit` exceeds the asset's per-borrower cap
+    pub fn open_asset_credit_line(
+        env: Env,
+        caller: Address,
+        borrower: Address,
+        asset: Address,
+        credit_limit: i128,
+        interest_rate_bps: u32,
+        risk_score: u32,
+    ) -> Result<(), CreditError> {
+        open_line(
+            &env,
+            caller,
+            borrower,
+            Some(asset),
+            credit_limit,
+            interest_rate_bps,
+            risk_score,
+        )
+    }
+
+    /// @notice Draws credit by transferring liquidity tokens to the borrower.
+    /// @dev Enforces status/limit/liquidity checks and uses a reentrancy guard. The draw
+    /// fee is added to the line's fee balance and does not count against the credit limit.
+    /// Secured lines may additionally only draw while their debt stays within the borrowing
+    /// power of their collateral.
+    ///
+    /// # Errors
+    /// * `InvalidAmount` if `amount` <= 0
+    /// * `CreditLineNotFound`
+    /// * `CreditLineSuspended`, `CreditLineDefaulted`, `CreditLineClosed` unless the line is Active
+    /// * `CreditLimitExceeded` if the draw would exceed the credit limit, or the borrowing
+    ///   power of a secured line
+    /// * `InsufficientLiquidity` if the liquidity source cannot cover the draw
+    /// * `OutstandingCapExceeded` if the draw would breach the reserve exposure cap
+    /// * `ContractPaused` while the contract is paused
+    /// * `Overflow`, `Reentrancy`
+    pub fn draw_credit(env: Env, borrower: Address, amount: i128) -> Result<(), CreditError> {
+        with_reentrancy_guard(&env, || {
+            borrower.require_auth();
+            ensure_not_paused(&env)?;
+
+            if amount <= 0 {
+                return Err(CreditError::InvalidAmount);
+            }
+
+            let mut credit_line = load_credit_line(&env, &borrower)?;
+            status::ensure_operation(credit_line.status, Operation::Draw)?;
+            refresh_line(&env, &mut credit_line)?;
+
+            let updated_utilized = credit_line
+                .utilized_amount
+                .checked_add(amount)
+                .ok_or(CreditError::Overflow)?;
+
+            if updated_utilized > credit_line.credit_limit {
+                return Err(CreditError::CreditLimitExceeded);
+            }
+            risk_tiers::check_draw(&env, credit_line.risk_score, amount)?;
+            if collateral::is_secured(&env, &borrower) {
+                let debt = collateral::debt(&credit_line, env.ledger().timestamp())?
+                    .checked_add(amount)
+                    .ok_or(CreditError::Overflow)?;
+                if debt > collateral::position(&env, &borrower, debt)?.borrowing_power {
+                    return Err(CreditError::CreditLimitExceeded);
+                }
+            }
+            assets::check_draw(&env, &credit_line.asset, amount)?;
+
+            disburse(
+                &env,
+                &credit_line.asset,
+                &borrower,
+                amount,
+                |reserve_balance| {
+                    exposure::check_draw(&env, &credit_line.asset, amount, reserve_balance)
+                },
+            )?;
+
+            let fee = fees::draw_fee(&env, amount)?;
+            fees::charge(&mut credit_line, fee)?;
+            credit_line.utilized_amount = updated_utilized;
+            save_credit_line(&env, &credit_line)?;
+            let timestamp = env.ledger().timestamp();
+            publish_drawn_event(
+                &env,
+                DrawnEvent {
+                    borrower: borrower.clone(),
+                    amount,
+                    fee,
+                    accrued_fees: credit_line.accrued_fees,
+                    new_utilized_amount: updated_utilized,
+                    timestamp,
+                },
+            );
+            Ok(())
+        })
+    }
+
+    /// Repay credit (borrower).
+    /// Accrues interest, then applies the payment to fees, interest and principal in that
+    /// order. Only the amount actually owed is applied; any excess is never pulled.
+    /// When a liquidity token is configured, the applied amount is transferred from the
+    /// borrower: the fee share to the fee collector (or the liquidity source if no fee
+    /// schedule is set) and the rest to the liquidity source. Emits RepaymentEvent with the
+    /// per-bucket split.
+    ///
+    /// # Errors
+    /// * `InvalidAmount` if `amount` <= 0
+    /// * `CreditLineNotFound`, `CreditLineClosed`, `Reentrancy`
+    pub fn repay_credit(env: Env, borrower: Address, amount: i128) -> Result<(), CreditError> {
+        with_reentrancy_guard(&env, || {
+            borrower.require_auth();
+            let mut credit_line = load_credit_line(&env, &borrower)?;
+
+            if credit_line.borrower != borrower {
+                return Err(CreditError::Unauthorized);
+            }
+
+            status::ensure_operation(credit_line.status, Operation::Repay)?;
+            if amount <= 0 {
+                return Err(CreditError::InvalidAmount);
+            }
+            refresh_line(&env, &mut credit_line)?;
+
+            let allocation = repayment::apply(&mut credit_line, amount);
+            let applied = allocation.total();
+            billing::record_payment(&env, &borrower, applied);
+            delinquency::update(&env, &credit_line);
+
+            settle_repayment(&env, &credit_line.asset, &borrower, &allocation)?;
+            save_credit_line(&env, &credit_line)?;
+
+            let timestamp = env.ledger().timestamp();
+            publish_repayment_event(
+                &env,
+                RepaymentEvent {
+                    borrower: borrower.clone(),
+                    amount: applied,
+                    fees_paid: allocation.fees,
+                    interest_paid: allocation.interest,
+                    principal_paid: allocation.principal,
+                    new_utilized_amount: credit_line.utilized_amount,
+                    accrued_fees: credit_line.accrued_fees,
+                    timestamp,
+                },
+            );
+            Ok(())
+        })
+    }
+
+    /// Update risk parameters for an existing credit line (risk engine only).
+    ///
+    /// # Arguments
+    /// * `caller` - Must hold `Role::RiskEngine` and authorize this call.
+    /// * `borrower` - Borrower whose credit line to update.
+    /// * `credit_limit` - New credit limit (must be >= current utilized_amount and >= 0).
+    /// * `interest_rate_bps` - New interest rate in basis points (0 ..= 10000).
+    /// * `risk_score` - New risk score (0 ..= 100).
+    ///
+    /// # Errors
+    /// * `Unauthorized` if `caller` does not hold `Role::RiskEngine`.
+    /// * `CreditLineNotFound` if no credit line exists for the borrower.
+    /// * `CreditLineDefaulted`, `CreditLineClosed` unless the line is Active or Suspended.
+    /// * `InvalidCreditLimit`, `CreditLimitBelowUtilized`, `InvalidInterestRate`,
+    ///   `InvalidRiskScore` if bounds are violated.
+    /// * `BorrowerLimitCapExceeded`, `CommittedLimitCapExceeded` if a limit increase breaches
+    ///   an exposure cap.
+    ///
+    /// Accrues interest at the old rate before applying the new parameters.
+    ///
+    /// Emits a risk_updated event.
+    pub fn update_risk_parameters(
+        env: Env,
+        caller: Address,
+        borrower: Address,
+        credit_limit: i128,
+        interest_rate_bps: u32,
+        risk_score: u32,
+    ) -> Result<(), CreditError> {
+        require_role(&env, Role::RiskEngine, &caller)?;
+
+        let mut credit_line = load_credit_line(&env, &borrower)?;
+        status::ensure_operation(credit_line.status, Operation::UpdateRisk)?;
+
+        validate_risk_parameters(
+            &env,
+            &credit_line,
+            &RiskParameters {
+                credit_limit,
+                interest_rate_bps,
+                risk_score,
+            },
+        )?;
+
+        refresh_line(&env, &mut credit_line)?;
+
+        let old_score = credit_line.risk_score;
         credit_line.credit_limit = credit_limit;
         credit_line.interest_rate_bps = interest_rate_bps;
         credit_line.risk_score = risk_score;
-        env.storage().persistent().set(&borrower, &credit_line);
+        rate_model::apply(&env, &mut credit_line)?;
+        save_credit_line(&env, &credit_line)?;
+        publish_tier_change(&env, &borrower, Some(old_score), risk_score);
 
         publish_risk_parameters_updated(
             &env,
             RiskParametersUpdatedEvent {
                 borrower: borrower.clone(),
                 credit_limit,
-                interest_rate_bps,
+                interest_rate_bps: credit_line.interest_rate_bps,
                 risk_score,
             },
         );
+        Ok(())
     }
 
-    /// Suspend a credit line (admin only).
-    /// Emits a CreditLineSuspended event.
-    pub fn suspend_credit_line(env: Env, borrower: Address) {
-        require_admin_auth(&env);
-        let mut credit_line: CreditLineData = env
-            .storage()
-            .persistent()
-            .get(&borrower)
-            .expect("Credit line not found");
+    /// Mark a borrower's line as secured or unsecured (risk engine only). Secured lines may
+    /// only draw up to the borrowing power of their collateral, in addition to their
+    /// credit limit.
+    ///
+    /// # Errors
+    /// * `Unauthorized` if `caller` does not hold `Role::RiskEngine`.
+    /// * `CreditLineNotFound` if the borrower has no line.
+    pub fn set_secured_line(
+        env: Env,
+        caller: Address,
+        borrower: Address,
+        secured: bool,
+    ) -> Result<(), CreditError> {
+        require_role(&env, Role::RiskEngine, &caller)?;
+        load_credit_line(&env, &borrower)?;
+        collateral::set_secured(&env, &borrower, secured);
+        Ok(())
+    }
 
-        credit_line.status = CreditStatus::Suspended;
-        env.storage().persistent().set(&borrower, &credit_line);
+    /// Deposit `amount` of a collateral token from the borrower into the contract
+    /// (borrower). A borrower may deposit before opening a line. Deposits only reduce risk,
+    /// so like repayments they keep working while the contract is paused.
+    ///
+    /// # Errors
+    /// * `InvalidAmount` if `amount` <= 0.
+    /// * `CollateralNotSupported` if the token has no `CollateralConfig`.
+    /// * `Overflow`, `Reentrancy`.
+    ///
+    /// Emits `(credit, coll_dep)` with a `CollateralEvent`.
+    pub fn deposit_collateral(
+        env: Env,
+        borrower: Address,
+        token: Address,
+        amount: i128,
+    ) -> Result<(), CreditError> {
+        with_reentrancy_guard(&env, || {
+            borrower.require_auth();
+            if amount <= 0 {
+                return Err(CreditError::InvalidAmount);
+            }
+            collateral::require_config(&env, &token)?;
+            let balance = collateral::balance(&env, &borrower, &token)
+                .checked_add(amount)
+                .ok_or(CreditError::Overflow)?;
+
+            token::Client::new(&env, &token).transfer(
+                &borrower,
+                &env.current_contract_address(),
+                &amount,
+            );
+            collateral::set_balance(&env, &borrower, &token, balance);
+            publish_collateral(
+                &env,
+                symbol_short!("coll_dep"),
+                CollateralEvent {
+                    borrower,
+                    token,
+                    amount,
+                    balance,
+                },
+            );
+            Ok(())
+        })
+    }
 
-        publish_credit_line_event(
-            &env,
-            (symbol_short!("credit"), symbol_short!("suspend")),
-            CreditLineEvent {
-                event_type: symbol_short!("suspend"),
+    /// Withdraw `amount` of a collateral token back to the borrower (borrower). The line's
+    /// debt, including interest not yet accrued, must stay within the borrowing power of
+    /// the remaining collateral. Collateral of a Defaulted line is frozen.
+    ///
+    /// # Errors
+    /// * `InvalidAmount` if `amount` <= 0.
+    /// * `InsufficientCollateral` if the borrower has less than `amount` deposited.
+    /// * `CreditLineDefaulted` if the borrower's line is Defaulted.
+    /// * `CollateralRatioTooLow` if the withdrawal would leave the debt undercollateralized.
+    /// * `ContractPaused`, `Overflow`, `Reentrancy`.
+    ///
+    /// Emits `(credit, coll_wd)` with a `CollateralEvent`.
+    pub fn withdraw_collateral(
+        env: Env,
+        borrower: Address,
+        token: Address,
+        amount: i128,
+    ) -> Result<(), CreditError> {
+        with_reentrancy_guard(&env, || {
+            borrower.require_auth();
+            ensure_not_paused(&env)?;
+            if amount <= 0 {
+                return Err(CreditError::InvalidAmount);
+            }
+            let deposited = collateral::balance(&env, &borrower, &token);
+            if deposited < amount {
+                return Err(CreditError::InsufficientCollateral);
+            }
+            let balance = deposited - amount;
+
+            collateral::set_balance(&env, &borrower, &token, balance);
+            if let Ok(credit_line) = load_credit_line(&env, &borrower) {
+                status::ensure_operation(credit_line.status, Operation::WithdrawCollateral)?;
+                let debt = collateral::debt(&credit_line, env.ledger().timestamp())?;
+                if debt > 0 && debt > collateral::position(&env, &borrower, debt)?.borrowing_power {
+                    return Err(CreditError::CollateralRatioTooLow);
+                }
+            }
+
+            token::Client::new(&env, &token).transfer(
+                &env.current_contract_address(),
+                &borrower,
+                &amount,
+            );
+            publish_collateral(
+                &env,
+                symbol_short!("coll_wd"),
+                CollateralEvent {
+                    borrower,
+                    token,
+                    amount,
+                    balance,
+                },
+            );
+            Ok(())
+        })
+    }
+
+    /// Repay up to `repay_amount` of a Defaulted or undercollateralized line's debt on the
+    /// borrower's behalf and seize collateral at the liquidation discount (liquidator).
+    ///
+    /// A line is undercollateralized when it is secured, has collateral and its debt
+    /// exceeds the liquidation threshold; unsecured lines are only liquidated once
+    /// Defaulted. The amount repaid is capped by the close factor and by the collateral
+    /// available, applied to fees, interest and principal like `repay_credit`, and paid by
+    /// the liquidator. Collateral worth `repaid / (1 - discount)` is then taken from the
+    /// borrower's deposits in deposit order. Returns the amount repaid.
+    ///
+    /// # Errors
+    /// * `InvalidAmount` if `repay_amount` <= 0.
+    /// * `CreditLineNotFound`, `CreditLineClosed`.
+    /// * `LineNotLiquidatable` if the line is healthy or has nothing to repay against
+    ///   collateral.
+    /// * `ContractPaused`, `Overflow`, `Reentrancy`.
+    ///
+    /// Emits `(credit, coll_seiz)` with a `CollateralEvent` per token seized, then
+    /// `(credit, liquidate)` with a `LiquidationEvent`.
+    pub fn liquidate(
+        env: Env,
+        liquidator: Address,
+        borrower: Address,
+        repay_amount: i128,
+    ) -> Result<i128, CreditError> {
+        with_reentrancy_guard(&env, || {
+            liquidator.require_auth();
+            ensure_not_paused(&env)?;
+            if repay_amount <= 0 {
+                return Err(CreditError::InvalidAmount);
+            }
+            let mut credit_line = load_credit_line(&env, &borrower)?;
+            status::ensure_operation(credit_line.status, Operation::Repay)?;
+            refresh_line(&env, &mut credit_line)?;
+
+            let timestamp = env.ledger().timestamp();
+            let debt = collateral::debt(&credit_line, timestamp)?;
+            let position = collateral::position(&env, &borrower, debt)?;
+            let secured = collateral::is_secured(&env, &borrower);
+            if !liquidation::is_liquidatable(&credit_line, &position, secured) {
+                return Err(CreditError::LineNotLiquidatable);
+            }
+            let config = liquidation::config(&env);
+            let repay = repay_amount.min(liquidation::max_repay(&config, &position)?);
+            if repay <= 0 {
+                return Err(CreditError::LineNotLiquidatable);
+            }
+
+            let allocation = repayment::apply(&mut credit_line, repay);
+            let repaid = allocation.total();
+            billing::record_payment(&env, &borrower, repaid);
+            delinquency::update(&env, &credit_line);
+            settle_repayment(&env, &credit_line.asset, &liquidator, &allocation)?;
+            save_credit_line(&env, &credit_line)?;
+
+            let collateral_seized = liquidation::seized_value(&config, repaid)?;
+            for (token, amount) in liquidation::plan_seizure(&env, &borrower, collateral_seized)? {
+                let balance = collateral::balance(&env, &borrower, &token) - amount;
+                collateral::set_balance(&env, &borrower, &token, balance);
+                token::Client::new(&env, &token).transfer(
+                    &env.current_contract_address(),
+                    &liquidator,
+                    &amount,
+                );
+                publish_collateral(
+                    &env,
+                    symbol_short!("coll_seiz"),
+                    CollateralEvent {
+                        borrower: borrower.clone(),
+                        token,
+                        amount,
+                        balance,
+                    },
+                );
+            }
+
+            publish_liquidation(
+                &env,
+                LiquidationEvent {
+                    borrower,
+                    liquidator,
+                    repaid,
+                    fees_paid: allocation.fees,
+                    interest_paid: allocation.interest,
+                    principal_paid: allocation.principal,
+                    collateral_seized,
+                    new_utilized_amount: credit_line.utilized_amount,
+                    timestamp,
+                },
+            );
+            Ok(repaid)
+        })
+    }
+
+    /// Originate an amortizing term loan and disburse its principal to the borrower (risk
+    /// engine only). The borrower must also authorize the call.
+    ///
+    /// The principal is paid from the global liquidity source, with the same reserve and
+    /// exposure checks as `draw_credit`, and counts towards principal outstanding in the
+    /// global token until it is repaid. It is repaid in `term_periods` equal installments,
+    /// one every `period_secs`, at the annual `interest_rate_bps`. A borrower may hold one
+    /// term loan at a time, alongside any credit line.
+    ///
+    /// # Errors
+    /// * `Unauthorized` if `caller` does not hold `Role::RiskEngine`.
+    /// * `InvalidLoanTerms` if `principal` <= 0, `interest_rate_bps` > 10000, `term_periods`
+    ///   is 0 or above `MAX_TERM_PERIODS`, or `period_secs` is 0.
+    /// * `TermLoanExists` if the borrower's previous loan is not repaid.
+    /// * `OutstandingCapExceeded` if the principal would breach the reserve cap.
+    /// * `Overflow` if the installment or the last due date does not fit.
+    /// * `InsufficientLiquidity`, `ContractPaused`, `Reentrancy`.
+    ///
+    /// Emits `(credit, loan_orig)` with a `TermLoanOriginatedEvent`.
+    pub fn originate_term_loan(
+        env: Env,
+        caller: Address,
+        borrower: Address,
+        principal: i128,
+        interest_rate_bps: u32,
+        term_periods: u32,
+        period_secs: u64,
+    ) -> Result<(), CreditError> {
+        with_reentrancy_guard(&env, || {
+            require_role(&env, Role::RiskEngine, &caller)?;
+            borrower.require_auth();
+            ensure_not_paused(&env)?;
+            if !term_loan::is_valid(principal, interest_rate_bps, term_periods, period_secs) {
+                return Err(CreditError::InvalidLoanTerms);
+            }
+            if term_loan::load(&env, &borrower).is_some_and(|loan| loan.outstanding_principal > 0) {
+                return Err(CreditError::TermLoanExists);
+            }
+
+            let installment = term_loan::installment_amount(
+                principal,
+                interest_rate_bps,
+                term_periods,
+                period_secs,
+            )?;
+            let loan = TermLoan {
                 borrower: borrower.clone(),
-                status: CreditStatus::Suspended,
-                credit_limit: credit_line.credit_limit,
-                interest_rate_bps: credit_line.interest_rate_bps,
-                risk_score: credit_line.risk_score,
+                principal,
+                outstanding_principal: principal,
+                interest_rate_bps,
+                term_periods,
+                period_secs,
+                installment,
+                installments_paid: 0,
+                start_ts: env.ledger().timestamp(),
+            };
+            // The whole schedule must fall due within the ledger's timestamp range.
+            term_loan::due_ts(&loan, term_periods)?;
+            assets::check_draw(&env, &None, principal)?;
+            disburse(&env, &None, &borrower, principal, |reserve_balance| {
+                exposure::check_draw(&env, &None, principal, reserve_balance)
+            })?;
+            term_loan::save(&env, &loan)?;
+            publish_term_loan_originated(
+                &env,
+                TermLoanOriginatedEvent {
+                    borrower,
+                    principal,
+                    interest_rate_bps,
+                    term_periods,
+                    period_secs,
+                    installment,
+                },
+            );
+            Ok(())
+        })
+    }
+
+    /// Pay the next installment of the borrower's term loan (borrower). Installments are
+    /// paid in schedule order and may be paid before they fall due; the payment is
+    /// transferred from the borrower to the liquidity source.
+    ///
+    /// # Errors
+    /// * `TermLoanNotFound` if the borrower has no loan or it is repaid.
+    /// * `Overflow`, `Reentrancy`.
+    ///
+    /// Emits `(credit, loan_pay)` with a `TermLoanPaymentEvent`.
+    pub fn pay_installment(env: Env, borrower: Address) -> Result<Installment, CreditError> {
+        with_reentrancy_guard(&env, || {
+            borrower.require_auth();
+            let mut loan = term_loan::load(&env, &borrower).ok_or(CreditError::TermLoanNotFound)?;
+            let installment =
+                term_loan::next_installment(&loan)?.ok_or(CreditError::TermLoanNotFound)?;
+
+            collect(
+                &env,
+                &None,
+                &borrower,
+                installment.payment,
+                installment.principal,
+            )?;
+            loan.outstanding_principal = installment.remaining_principal;
+            loan.installments_paid += 1;
+            term_loan::save(&env, &loan)?;
+
+            publish_term_loan_payment(
+                &env,
+                symbol_short!("loan_pay"),
+                TermLoanPaymentEvent {
+                    borrower: borrower.clone(),
+                    amount: installment.payment,
+                    principal_paid: installment.principal,
+                    interest_paid: installment.interest,
+                    remaining_principal: loan.outstanding_principal,
+                    installments_paid: loan.installments_paid,
+                    timestamp: env.ledger().timestamp(),
+                },
+            );
+            Ok(installment)
+        })
+    }
+
+    /// Repay the borrower's term loan in full before its schedule ends (borrower). The
+    /// borrower pays the outstanding principal plus interest on it since the last paid
+    /// installment fell due, however many periods ago. Returns the amount paid.
+    ///
+    /// # Errors
+    /// * `TermLoanNotFound` if the borrower has no loan or it is repaid.
+    /// * `Overflow`, `Reentrancy`.
+    ///
+    /// Emits `(credit, loan_off)` with a `TermLoanPaymentEvent`.
+    pub fn payoff_term_loan(env: Env, borrower: Address) -> Result<i128, CreditError> {
+        with_reentrancy_guard(&env, || {
+            borrower.require_auth();
+            let mut loan = term_loan::load(&env, &borrower)
+                .filter(|loan| loan.outstanding_principal > 0)
+                .ok_or(CreditError::TermLoanNotFound)?;
+            let now = env.ledger().timestamp();
+            let interest = term_loan::payoff_interest(&loan, now)?;
+            let principal = loan.outstanding_principal;
+            let amount = principal
+                .checked_add(interest)
+                .ok_or(CreditError::Overflow)?;
+
+            collect(&env, &None, &borrower, amount, principal)?;
+            loan.outstanding_principal = 0;
+            term_loan::save(&env, &loan)?;
+
+            publish_term_loan_payment(
+                &env,
+                symbol_short!("loan_off"),
+                TermLoanPaymentEvent {
+                    borrower: borrower.clone(),
+                    amount,
+                    principal_paid: principal,
+                    interest_paid: interest,
+                    remaining_principal: 0,
+                    installments_paid: loan.installments_paid,
+                    timestamp: now,
+                },
+            );
+            Ok(amount)
+        })
+    }
+
+    /// Charge the configured late fee to a line whose latest billing statement's minimum
+    /// payment is past due (risk engine only). The fee is added to the line's fee balance
+    /// at most once per statement; nothing is charged while billing is off, before the due
+    /// date, once the minimum is paid, or if no late fee is configured.
+    ///
+    /// # Errors
+    /// * `Unauthorized` if `caller` does not hold `Role::RiskEngine`.
+    /// * `CreditLineNotFound`, `CreditLineClosed`, `Overflow`.
+    ///
+    /// Emits `(credit, late_fee)` with a `FeeChargedEvent` when a fee is charged.
+    pub fn assess_late_fee(
+        env: Env,
+        caller: Address,
+        borrower: Address,
+    ) -> Result<(), CreditError> {
+        require_role(&env, Role::RiskEngine, &caller)?;
+
+        let mut credit_line = load_credit_line(&env, &borrower)?;
+        status::ensure_operation(credit_line.status, Operation::Accrue)?;
+
+        refresh_line(&env, &mut credit_line)?;
+        let fee = billing::charge_late_fee(&env, &mut credit_line)?;
+        save_credit_line(&env, &credit_line)?;
+        if fee == 0 {
+            return Ok(());
+        }
+
+        publish_fee_charged(
+            &env,
+            symbol_short!("late_fee"),
+            FeeChargedEvent {
+                borrower,
+                fee,
+                accrued_fees: credit_line.accrued_fees,
+                timestamp: env.ledger().timestamp(),
             },
         );
+        Ok(())
+    }
+
+    /// Suspend a credit line (admin only). Only Active lines can be suspended; anything else
+    /// fails with `InvalidStatusTransition`.
+    /// Emits a CreditLineSuspended event.
+    pub fn suspend_credit_line(env: Env, borrower: Address) -> Result<(), CreditError> {
+        require_admin_auth(&env)?;
+        let mut credit_line = load_credit_line(&env, &borrower)?;
+        transition_status(
+            &env,
+            &mut credit_line,
+            CreditStatus::Suspended,
+            symbol_short!("suspend"),
+        )
     }
 
     /// Close a credit line. Callable by admin (force-close) or by borrower when utilization is zero.
@@ -389,1370 +1924,5954 @@ impl Credit {
     /// # Arguments
     /// * `closer` - Address that must have authorized this call. Must be either the contract admin
     ///   (can close regardless of utilization) or the borrower (can close only when
-    ///   `utilized_amount` is zero).
+    ///   `utilized_amount` and `accrued_interest` are zero).
     ///
     /// # Errors
-    /// * Panics if credit line does not exist, or if `closer` is not admin/borrower, or if
-    ///   borrower closes while `utilized_amount != 0`.
+    /// * `CreditLineNotFound` if the credit line does not exist.
+    /// * `Unauthorized` if `closer` is neither admin nor borrower.
+    /// * `UtilizationNotZero` if the borrower closes with an outstanding balance.
     ///
-    /// Emits a CreditLineClosed event.
-    pub fn close_credit_line(env: Env, borrower: Address, closer: Address) {
+    /// Closing an already Closed line is a no-op. Emits a CreditLineClosed event.
+    pub fn close_credit_line(
+        env: Env,
+        borrower: Address,
+        closer: Address,
+    ) -> Result<(), CreditError> {
         closer.require_auth();
 
-        let admin: Address = require_admin(&env);
+        let admin: Address = require_admin(&env)?;
 
-        let mut credit_line: CreditLineData = env
-            .storage()
-            .persistent()
-            .get(&borrower)
-            .expect("Credit line not found");
+        let mut credit_line = load_credit_line(&env, &borrower)?;
 
         if credit_line.status == CreditStatus::Closed {
-            return;
+            return Ok(());
         }
 
-        let allowed = closer == admin || (closer == borrower && credit_line.utilized_amount == 0);
+        refresh_line(&env, &mut credit_line)?;
+
+        let allowed = closer == admin
+            || (closer == borrower
+                && credit_line.utilized_amount == 0
+                && credit_line.accrued_interest == 0
+                && credit_line.accrued_fees == 0);
 
         if !allowed {
             if closer == borrower {
-                panic!("cannot close: utilized amount not zero");
+                return Err(CreditError::UtilizationNotZero);
             }
-            panic!("unauthorized");
+            return Err(CreditError::Unauthorized);
         }
 
-        credit_line.status = CreditStatus::Closed;
-        env.storage().persistent().set(&borrower, &credit_line);
+        transition_status(
+            &env,
+            &mut credit_line,
+            CreditStatus::Closed,
+            symbol_short!("closed"),
+        )
+    }
+
+    /// Move a Suspended credit line back to Active (admin only), keeping its balances and
+    /// history. Optionally applies new risk parameters at the same time.
+    ///
+    /// # Errors
+    /// * `CreditLineNotFound` if no credit line exists for the borrower.
+    /// * `InvalidStatusTransition` unless the line is Suspended. Defaulted lines can only be
+    ///   closed, and Closed lines are replaced through `open_credit_line`.
+    /// * `InvalidCreditLimit`, `CreditLimitBelowUtilized`, `InvalidInterestRate`,
+    ///   `InvalidRiskScore` if `risk_parameters` violate bounds.
+    ///
+    /// Interest is accrued at the old rate before new parameters apply.
+    /// Emits a `(credit, reinstate)` CreditLineEvent.
+    pub fn reinstate_credit_line(
+        env: Env,
+        borrower: Address,
+        risk_parameters: Option<RiskParameters>,
+    ) -> Result<(), CreditError> {
+        require_admin_auth(&env)?;
+        let mut credit_line = load_credit_line(&env, &borrower)?;
+        status::ensure_transition(credit_line.status, CreditStatus::Active)?;
+
+        refresh_line(&env, &mut credit_line)?;
+        if let Some(params) = risk_parameters {
+            validate_risk_parameters(&env, &credit_line, &params)?;
+            publish_tier_change(
+                &env,
+                &borrower,
+                Some(credit_line.risk_score),
+                params.risk_score,
+            );
+            credit_line.credit_limit = params.credit_limit;
+            credit_line.interest_rate_bps = params.interest_rate_bps;
+            credit_line.risk_score = params.risk_score;
+            rate_model::apply(&env, &mut credit_line)?;
+        }
 
-        publish_credit_line_event(
+        transition_status(
             &env,
-            (symbol_short!("credit"), symbol_short!("closed")),
-            CreditLineEvent {
-                event_type: symbol_short!("closed"),
-                borrower: borrower.clone(),
-                status: CreditStatus::Closed,
-                credit_limit: credit_line.credit_limit,
-                interest_rate_bps: credit_line.interest_rate_bps,
-                risk_score: credit_line.risk_score,
-            },
-        );
+            &mut credit_line,
+            CreditStatus::Active,
+            symbol_short!("reinstate"),
+        )
     }
 
-    /// Mark a credit line as defaulted (admin only).
+    /// Mark a credit line as defaulted (admin only). Active and Suspended lines can be
+    /// defaulted; anything else fails with `InvalidStatusTransition`.
     /// Emits a CreditLineDefaulted event.
-    pub fn default_credit_line(env: Env, borrower: Address) {
-        require_admin_auth(&env);
-        let mut credit_line: CreditLineData = env
-            .storage()
-            .persistent()
-            .get(&borrower)
-            .expect("Credit line not found");
+    pub fn default_credit_line(env: Env, borrower: Address) -> Result<(), CreditError> {
+        require_admin_auth(&env)?;
+        let mut credit_line = load_credit_line(&env, &borrower)?;
+        refresh_line(&env, &mut credit_line)?;
+        transition_status(
+            &env,
+            &mut credit_line,
+            CreditStatus::Defaulted,
+            symbol_short!("default"),
+        )
+    }
 
-        credit_line.status = CreditStatus::Defaulted;
-        env.storage().persistent().set(&borrower, &credit_line);
+    /// Write off everything a Defaulted or Closed line owes (admin only) once it will not be
+    /// repaid, e.g. after its collateral has been liquidated. A Defaulted line accrues
+    /// interest first. The principal written off is reported to the lending pool as a loss,
+    /// and the line can be closed and reopened afterwards. Returns the total written off;
+    /// a line that owes nothing is left unchanged.
+    ///
+    /// # Errors
+    /// * `CreditLineNotFound` if the borrower has no line.
+    /// * `ActiveCreditLineExists` or `CreditLineSuspended` unless the line is Defaulted or
+    ///   Closed.
+    ///
+    /// Emits `(credit, write_off)` with a `WriteOffEvent`.
+    pub fn write_off_credit_line(env: Env, borrower: Address) -> Result<i128, CreditError> {
+        require_admin_auth(&env)?;
+        let mut credit_line = load_credit_line(&env, &borrower)?;
+        status::ensure_operation(credit_line.status, Operation::WriteOff)?;
+        if status::allows(credit_line.status, Operation::Accrue) {
+            refresh_line(&env, &mut credit_line)?;
+        }
+        let (principal, interest, fees) = (
+            credit_line.utilized_amount,
+            credit_line.accrued_interest,
+            credit_line.accrued_fees,
+        );
+        let total = principal
+            .checked_add(interest)
+            .and_then(|v| v.checked_add(fees))
+            .ok_or(CreditError::Overflow)?;
+        if total == 0 {
+            return Ok(0);
+        }
+
+        credit_line.utilized_amount = 0;
+        credit_line.accrued_interest = 0;
+        credit_line.accrued_fees = 0;
+        billing::record_payment(&env, &borrower, total);
+        delinquency::update(&env, &credit_line);
+        save_credit_line(&env, &credit_line)?;
+        lending_pool::record_loss(&env, &credit_line.asset, principal);
+        publish_write_off(
+            &env,
+            WriteOffEvent {
+                borrower,
+                principal,
+                interest,
+                fees,
+                timestamp: env.ledger().timestamp(),
+            },
+        );
+        Ok(total)
+    }
+
+    /// Accrue interest on a credit line up to the current ledger timestamp, closing any
+    /// billing cycles that have ended.
+    ///
+    /// Permissionless: accrual is deterministic, so anyone (e.g. a keeper) may trigger it.
+    /// Returns the line's total accrued, unpaid interest. Fails with `CreditLineClosed` for
+    /// Closed lines.
+    pub fn accrue_interest(env: Env, borrower: Address) -> Result<i128, CreditError> {
+        let mut credit_line = load_credit_line(&env, &borrower)?;
+        status::ensure_operation(credit_line.status, Operation::Accrue)?;
+
+        refresh_line(&env, &mut credit_line)?;
+        save_credit_line(&env, &credit_line)?;
+        Ok(credit_line.accrued_interest)
+    }
+
+    /// Total amount owed by a borrower: principal, fees, accrued interest and interest
+    /// pending since the last accrual, up to the current ledger (view function).
+    pub fn get_outstanding(env: Env, borrower: Address) -> Result<i128, CreditError> {
+        let credit_line = load_credit_line(&env, &borrower)?;
+
+        let pending = interest::pending_interest(&credit_line, env.ledger().timestamp())?;
+        credit_line
+            .utilized_amount
+            .checked_add(credit_line.accrued_fees)
+            .and_then(|v| v.checked_add(credit_line.accrued_interest))
+            .and_then(|v| v.checked_add(pending))
+            .ok_or(CreditError::Overflow)
+    }
+
+    /// Billing state of a borrower's line as of the current ledger: the latest statement's
+    /// balance, the minimum payment still due and its due date (view function). Cycles that
+    /// have ended are reported closed even if the line has not been touched since.
+    ///
+    /// Returns `None` while billing is disabled or for Closed lines. Fails with
+    /// `CreditLineNotFound` if the borrower has no line.
+    pub fn get_statement(env: Env, borrower: Address) -> Result<Option<Statement>, CreditError> {
+        let credit_line = load_credit_line(&env, &borrower)?;
+        Ok(billing::current(&env, &credit_line)?.map(|(statement, _)| statement))
+    }
+
+    /// The rate a borrower's line would take if touched now, and how it is computed: the
+    /// utilization of its liquidity source and the base, utilization and risk parts of the
+    /// rate model (view function). Without a model, the line's static rate is reported as
+    /// the base rate. Fails with `CreditLineNotFound` if the borrower has no line.
+    pub fn get_effective_rate(env: Env, borrower: Address) -> Result<RateBreakdown, CreditError> {
+        let credit_line = load_credit_line(&env, &borrower)?;
+        rate_model::breakdown(&env, &credit_line)
+    }
+
+    /// Position of the risk tier covering a borrower's risk score, if any (view function).
+    /// Fails with `CreditLineNotFound` if the borrower has no line.
+    pub fn get_risk_tier(env: Env, borrower: Address) -> Result<Option<u32>, CreditError> {
+        let credit_line = load_credit_line(&env, &borrower)?;
+        Ok(risk_tiers::tier_for(&env, credit_line.risk_score).map(|(index, _)| index))
+    }
+
+    /// Term loan of a borrower, including repaid loans until replaced (view function).
+    pub fn get_term_loan(env: Env, borrower: Address) -> Option<TermLoan> {
+        term_loan::load(&env, &borrower)
+    }
+
+    /// Amount of `token` the borrower has deposited as collateral (view function).
+    pub fn get_collateral(env: Env, borrower: Address, token: Address) -> i128 {
+        collateral::balance(&env, &borrower, &token)
+    }
+
+    /// Value and borrowing power of a borrower's collateral against the debt on their line,
+    /// including interest not yet accrued (view function). Debt is 0 without a line.
+    pub fn get_collateral_position(
+        env: Env,
+        borrower: Address,
+    ) -> Result<CollateralPosition, CreditError> {
+        let debt = match load_credit_line(&env, &borrower) {
+            Ok(credit_line) => collateral::debt(&credit_line, env.ledger().timestamp())?,
+            Err(_) => 0,
+        };
+        collateral::position(&env, &borrower, debt)
+    }
+
+    /// Full amortization schedule of a borrower's term loan from origination, including
+    /// installments already paid (view function). Fails with `TermLoanNotFound` if the
+    /// borrower has never had a term loan.
+    pub fn get_amortization_schedule(
+        env: Env,
+        borrower: Address,
+    ) -> Result<Vec<Installment>, CreditError> {
+        let loan = term_loan::load(&env, &borrower).ok_or(CreditError::TermLoanNotFound)?;
+        term_loan::schedule(&env, &loan)
+    }
+
+    /// Delinquency of a borrower's line as of the current ledger: its bucket, days past due
+    /// and the due date it has been past due since (view function).
+    ///
+    /// Returns `None` while billing is disabled or for Closed lines. Fails with
+    /// `CreditLineNotFound` if the borrower has no line.
+    pub fn get_delinquency(
+        env: Env,
+        borrower: Address,
+    ) -> Result<Option<Delinquency>, CreditError> {
+        let credit_line = load_credit_line(&env, &borrower)?;
+        delinquency::current(&env, &credit_line)
+    }
+
+    /// Bring a line's billing and delinquency up to date, defaulting it if it has been past
+    /// due for at least the default grace period.
+    ///
+    /// Permissionless, so keepers can move lines through the delinquency buckets on time.
+    /// Active and Suspended lines past the grace period move to Defaulted with a
+    /// `(credit, default)` CreditLineEvent; Defaulted lines are only re-assessed. Returns the
+    /// line's delinquency, or `None` while billing is disabled.
+    ///
+    /// # Errors
+    /// * `CreditLineNotFound`, `CreditLineClosed`, `Overflow`.
+    ///
+    /// Emits `(credit, delinq)` with a `DelinquencyEvent` whenever the bucket changes.
+    pub fn process_delinquency(
+        env: Env,
+        borrower: Address,
+    ) -> Result<Option<Delinquency>, CreditError> {
+        let mut credit_line = load_credit_line(&env, &borrower)?;
+        status::ensure_operation(credit_line.status, Operation::Accrue)?;
+        let delinquency = refresh_line(&env, &mut credit_line)?;
+
+        let grace_period = delinquency::default_grace_period(&env);
+        let overdue = delinquency.as_ref().is_some_and(|d| {
+            d.past_due_since != 0 && env.ledger().timestamp() - d.past_due_since >= grace_period
+        });
+        if grace_period > 0
+            && overdue
+            && status::can_transition(credit_line.status, CreditStatus::Defaulted)
+        {
+            transition_status(
+                &env,
+                &mut credit_line,
+                CreditStatus::Defaulted,
+                symbol_short!("default"),
+            )?;
+        } else {
+            save_credit_line(&env, &credit_line)?;
+        }
+        Ok(delinquency)
+    }
+
+    /// Read-only getter for credit line by borrower
+    ///
+    /// @param borrower The address to query
+    /// @return Option<CreditLineData> Full data or None if no line exists
+    /// Get credit line data for a borrower (view function).
+    pub fn get_credit_line(env: Env, borrower: Address) -> Option<CreditLineData> {
+        load_credit_line(&env, &borrower).ok()
+    }
+
+    /// Protocol-wide totals and per-status line counts, maintained incrementally.
+    pub fn get_portfolio_stats(env: Env) -> PortfolioStats {
+        portfolio::stats(&env)
+    }
+
+    /// Number of borrowers in the registry, including those whose lines are closed.
+    pub fn get_borrower_count(env: Env) -> u32 {
+        registry::borrower_count(&env)
+    }
+
+    /// Number of credit lines currently in `status`.
+    pub fn get_status_count(env: Env, status: CreditStatus) -> u32 {
+        registry::status_count(&env, status)
+    }
+
+    /// Credit lines at registry positions `start..start + limit`, in registration order.
+    /// At most `MAX_PAGE_SIZE` (15) lines are returned; an empty result means the end.
+    pub fn list_credit_lines(env: Env, start: u32, limit: u32) -> Vec<CreditLineData> {
+        load_credit_lines(&env, registry::borrowers(&env, start, limit))
+    }
+
+    /// Credit lines in `status`, starting at position `cursor` of that status's list.
+    /// At most `MAX_PAGE_SIZE` (15) lines are returned; pass `cursor + returned length` to
+    /// continue. Lines that change status between calls may be skipped or repeated.
+    pub fn list_by_status(
+        env: Env,
+        status: CreditStatus,
+        cursor: u32,
+        limit: u32,
+    ) -> Vec<CreditLineData> {
+        load_credit_lines(
+            &env,
+            registry::borrowers_by_status(&env, status, cursor, limit),
+        )
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use events::{DelinquencyEvent, StatementClosedEvent};
+    use oracle::PriceFeed;
+    use soroban_sdk::testutils::Address as _;
+    use soroban_sdk::testutils::Events;
+    use soroban_sdk::testutils::Ledger;
+    use soroban_sdk::testutils::{MockAuth, MockAuthInvoke};
+    use soroban_sdk::token::StellarAssetClient;
+    use soroban_sdk::{vec, IntoVal, TryFromVal};
+    use types::DelinquencyBucket;
+
+    fn setup_test(env: &Env) -> (Address, Address, Address) {
+        env.mock_all_auths();
+
+        let admin = Address::generate(env);
+        let borrower = Address::generate(env);
+
+        let contract_id = env.register(Credit, ());
+        let client = CreditClient::new(env, &contract_id);
+
+        client.init(&admin);
+        client.grant_role(&Role::RiskEngine, &admin);
+        client.open_credit_line(&admin, &borrower, &1000_i128, &300_u32, &70_u32);
+
+        (admin, borrower, contract_id)
+    }
+
+    fn call_contract<F>(env: &Env, contract_id: &Address, f: F)
+    where
+        F: FnOnce(),
+    {
+        env.as_contract(contract_id, f);
+    }
+
+    fn get_credit_data(env: &Env, contract_id: &Address, borrower: &Address) -> CreditLineData {
+        let client = CreditClient::new(env, contract_id);
+        client
+            .get_credit_line(borrower)
+            .expect("Credit line not found")
+    }
+
+    #[test]
+    fn test_init_and_open_credit_line() {
+        let env = Env::default();
+        env.mock_all_auths();
+
+        let admin = Address::generate(&env);
+        let borrower = Address::generate(&env);
+
+        let contract_id = env.register(Credit, ());
+        let client = CreditClient::new(&env, &contract_id);
+
+        client.init(&admin);
+        client.grant_role(&Role::RiskEngine, &admin);
+        client.open_credit_line(&admin, &borrower, &1000_i128, &300_u32, &70_u32);
+
+        let credit_line = client.get_credit_line(&borrower);
+        assert!(credit_line.is_some());
+        let credit_line = credit_line.unwrap();
+        assert_eq!(credit_line.borrower, borrower);
+        assert_eq!(credit_line.credit_limit, 1000);
+        assert_eq!(credit_line.utilized_amount, 0);
+        assert_eq!(credit_line.interest_rate_bps, 300);
+        assert_eq!(credit_line.risk_score, 70);
+        assert_eq!(credit_line.status, CreditStatus::Active);
+    }
+
+    #[test]
+    fn test_suspend_credit_line() {
+        let env = Env::default();
+        env.mock_all_auths();
+
+        let admin = Address::generate(&env);
+        let borrower = Address::generate(&env);
+
+        let contract_id = env.register(Credit, ());
+        let client = CreditClient::new(&env, &contract_id);
+
+        client.init(&admin);
+        client.grant_role(&Role::RiskEngine, &admin);
+        client.open_credit_line(&admin, &borrower, &1000_i128, &300_u32, &70_u32);
+        client.suspend_credit_line(&borrower);
+
+        let credit_line = client.get_credit_line(&borrower).unwrap();
+        assert_eq!(credit_line.status, CreditStatus::Suspended);
+    }
+
+    #[test]
+    fn test_close_credit_line() {
+        let env = Env::default();
+        env.mock_all_auths();
+
+        let admin = Address::generate(&env);
+        let borrower = Address::generate(&env);
+
+        let contract_id = env.register(Credit, ());
+        let client = CreditClient::new(&env, &contract_id);
+
+        client.init(&admin);
+        client.grant_role(&Role::RiskEngine, &admin);
+        client.open_credit_line(&admin, &borrower, &1000_i128, &300_u32, &70_u32);
+        client.close_credit_line(&borrower, &admin);
+
+        let credit_line = client.get_credit_line(&borrower).unwrap();
+        assert_eq!(credit_line.status, CreditStatus::Closed);
+    }
+
+    #[test]
+    fn test_default_credit_line() {
+        let env = Env::default();
+        env.mock_all_auths();
+
+        let admin = Address::generate(&env);
+        let borrower = Address::generate(&env);
+
+        let contract_id = env.register(Credit, ());
+        let client = CreditClient::new(&env, &contract_id);
+
+        client.init(&admin);
+        client.grant_role(&Role::RiskEngine, &admin);
+        client.open_credit_line(&admin, &borrower, &1000_i128, &300_u32, &70_u32);
+        client.default_credit_line(&borrower);
+
+        let credit_line = client.get_credit_line(&borrower).unwrap();
+        assert_eq!(credit_line.status, CreditStatus::Defaulted);
+    }
+
+    // ========== open_credit_line: duplicate borrower and invalid params (#28) ==========
+
+    /// open_credit_line must revert when the borrower already has an Active credit line.
+    #[test]
+    fn test_open_credit_line_duplicate_active_borrower_reverts() {
+        let env = Env::default();
+        env.mock_all_auths();
+
+        let admin = Address::generate(&env);
+        let borrower = Address::generate(&env);
+
+        let contract_id = env.register(Credit, ());
+        let client = CreditClient::new(&env, &contract_id);
+
+        client.init(&admin);
+        client.grant_role(&Role::RiskEngine, &admin);
+        client.open_credit_line(&admin, &borrower, &1000_i128, &300_u32, &70_u32);
+        // Second open for same borrower while Active must revert.
+        assert_eq!(
+            client.try_open_credit_line(&admin, &borrower, &2000_i128, &400_u32, &60_u32),
+            Err(Ok(CreditError::ActiveCreditLineExists))
+        );
+    }
+
+    /// open_credit_line must revert when credit_limit is zero.
+    #[test]
+    fn test_open_credit_line_zero_limit_reverts() {
+        let env = Env::default();
+        env.mock_all_auths();
+
+        let admin = Address::generate(&env);
+        let borrower = Address::generate(&env);
+
+        let contract_id = env.register(Credit, ());
+        let client = CreditClient::new(&env, &contract_id);
+
+        client.init(&admin);
+        client.grant_role(&Role::RiskEngine, &admin);
+        assert_eq!(
+            client.try_open_credit_line(&admin, &borrower, &0_i128, &300_u32, &70_u32),
+            Err(Ok(CreditError::InvalidCreditLimit))
+        );
+    }
+
+    /// open_credit_line must revert when credit_limit is negative.
+    #[test]
+    fn test_open_credit_line_negative_limit_reverts() {
+        let env = Env::default();
+        env.mock_all_auths();
+
+        let admin = Address::generate(&env);
+        let borrower = Address::generate(&env);
+
+        let contract_id = env.register(Credit, ());
+        let client = CreditClient::new(&env, &contract_id);
+
+        client.init(&admin);
+        client.grant_role(&Role::RiskEngine, &admin);
+        assert_eq!(
+            client.try_open_credit_line(&admin, &borrower, &-1_i128, &300_u32, &70_u32),
+            Err(Ok(CreditError::InvalidCreditLimit))
+        );
+    }
+
+    /// open_credit_line must revert when interest_rate_bps exceeds 10000 (100%).
+    #[test]
+    fn test_open_credit_line_interest_rate_exceeds_max_reverts() {
+        let env = Env::default();
+        env.mock_all_auths();
+
+        let admin = Address::generate(&env);
+        let borrower = Address::generate(&env);
+
+        let contract_id = env.register(Credit, ());
+        let client = CreditClient::new(&env, &contract_id);
+
+        client.init(&admin);
+        client.grant_role(&Role::RiskEngine, &admin);
+        assert_eq!(
+            client.try_open_credit_line(&admin, &borrower, &1000_i128, &10_001_u32, &70_u32),
+            Err(Ok(CreditError::InvalidInterestRate))
+        );
+    }
+
+    /// open_credit_line must revert when risk_score exceeds 100.
+    #[test]
+    fn test_open_credit_line_risk_score_exceeds_max_reverts() {
+        let env = Env::default();
+        env.mock_all_auths();
+
+        let admin = Address::generate(&env);
+        let borrower = Address::generate(&env);
+
+        let contract_id = env.register(Credit, ());
+        let client = CreditClient::new(&env, &contract_id);
+
+        client.init(&admin);
+        client.grant_role(&Role::RiskEngine, &admin);
+        assert_eq!(
+            client.try_open_credit_line(&admin, &borrower, &1000_i128, &300_u32, &101_u32),
+            Err(Ok(CreditError::InvalidRiskScore))
+        );
+    }
+
+    // ========== draw_credit within limit (#29) ==========
+
+    #[test]
+    fn test_draw_credit() {
+        let env = Env::default();
+        let (_admin, borrower, contract_id) = setup_test(&env);
+
+        call_contract(&env, &contract_id, || {
+            Credit::draw_credit(env.clone(), borrower.clone(), 500_i128).unwrap();
+        });
+
+        let credit_data = get_credit_data(&env, &contract_id, &borrower);
+        assert_eq!(credit_data.utilized_amount, 500_i128);
+
+        // Events are emitted - functionality verified through storage changes
+    }
+
+    /// draw_credit within limit: single draw updates utilized_amount correctly.
+    #[test]
+    fn test_draw_credit_single_within_limit_succeeds_and_updates_utilized() {
+        let env = Env::default();
+        env.mock_all_auths();
+
+        let admin = Address::generate(&env);
+        let borrower = Address::generate(&env);
+
+        let contract_id = env.register(Credit, ());
+        let client = CreditClient::new(&env, &contract_id);
+
+        client.init(&admin);
+        client.grant_role(&Role::RiskEngine, &admin);
+        client.open_credit_line(&admin, &borrower, &1000_i128, &300_u32, &70_u32);
+
+        let line_before = client.get_credit_line(&borrower).unwrap();
+        assert_eq!(line_before.utilized_amount, 0);
+
+        client.draw_credit(&borrower, &400_i128);
+
+        let line_after = client.get_credit_line(&borrower).unwrap();
+        assert_eq!(line_after.utilized_amount, 400);
+        assert_eq!(line_after.credit_limit, 1000);
+    }
+
+    /// draw_credit within limit: multiple draws accumulate utilized_amount correctly.
+    #[test]
+    fn test_draw_credit_multiple_draws_within_limit_accumulate_utilized() {
+        let env = Env::default();
+        env.mock_all_auths();
+
+        let admin = Address::generate(&env);
+        let borrower = Address::generate(&env);
+
+        let contract_id = env.register(Credit, ());
+        let client = CreditClient::new(&env, &contract_id);
+
+        client.init(&admin);
+        client.grant_role(&Role::RiskEngine, &admin);
+        client.open_credit_line(&admin, &borrower, &1000_i128, &300_u32, &70_u32);
+
+        client.draw_credit(&borrower, &100_i128);
+        assert_eq!(
+            client.get_credit_line(&borrower).unwrap().utilized_amount,
+            100
+        );
+
+        client.draw_credit(&borrower, &250_i128);
+        assert_eq!(
+            client.get_credit_line(&borrower).unwrap().utilized_amount,
+            350
+        );
+
+        client.draw_credit(&borrower, &150_i128);
+        assert_eq!(
+            client.get_credit_line(&borrower).unwrap().utilized_amount,
+            500
+        );
+    }
+
+    /// draw_credit within limit: drawing exact available limit succeeds and utilized equals limit.
+    #[test]
+    fn test_draw_credit_exact_available_limit_succeeds() {
+        let env = Env::default();
+        env.mock_all_auths();
+
+        let admin = Address::generate(&env);
+        let borrower = Address::generate(&env);
+
+        let contract_id = env.register(Credit, ());
+        let client = CreditClient::new(&env, &contract_id);
+
+        client.init(&admin);
+        client.grant_role(&Role::RiskEngine, &admin);
+        let limit = 5000_i128;
+        client.open_credit_line(&admin, &borrower, &limit, &300_u32, &70_u32);
+
+        client.draw_credit(&borrower, &limit);
+
+        let line = client.get_credit_line(&borrower).unwrap();
+        assert_eq!(line.utilized_amount, limit);
+        assert_eq!(line.credit_limit, limit);
+    }
+
+    #[test]
+    fn test_repay_credit_partial() {
+        let env = Env::default();
+        let (_admin, borrower, contract_id) = setup_test(&env);
+
+        // First draw some credit
+        call_contract(&env, &contract_id, || {
+            Credit::draw_credit(env.clone(), borrower.clone(), 500_i128).unwrap();
+        });
+        assert_eq!(
+            get_credit_data(&env, &contract_id, &borrower).utilized_amount,
+            500_i128
+        );
+
+        // Partial repayment
+        call_contract(&env, &contract_id, || {
+            Credit::repay_credit(env.clone(), borrower.clone(), 200_i128).unwrap();
+        });
+
+        let credit_data = get_credit_data(&env, &contract_id, &borrower);
+        assert_eq!(credit_data.utilized_amount, 300_i128); // 500 - 200
+    }
+
+    #[test]
+    fn test_repay_credit_full() {
+        let env = Env::default();
+        let (_admin, borrower, contract_id) = setup_test(&env);
+
+        // Draw some credit
+        call_contract(&env, &contract_id, || {
+            Credit::draw_credit(env.clone(), borrower.clone(), 500_i128).unwrap();
+        });
+        assert_eq!(
+            get_credit_data(&env, &contract_id, &borrower).utilized_amount,
+            500_i128
+        );
+
+        // Full repayment
+        call_contract(&env, &contract_id, || {
+            Credit::repay_credit(env.clone(), borrower.clone(), 500_i128).unwrap();
+        });
+
+        let credit_data = get_credit_data(&env, &contract_id, &borrower);
+        assert_eq!(credit_data.utilized_amount, 0_i128); // Fully repaid
+    }
+
+    #[test]
+    fn test_repay_credit_overpayment() {
+        let env = Env::default();
+        let (_admin, borrower, contract_id) = setup_test(&env);
+
+        // Draw some credit
+        call_contract(&env, &contract_id, || {
+            Credit::draw_credit(env.clone(), borrower.clone(), 300_i128).unwrap();
+        });
+        assert_eq!(
+            get_credit_data(&env, &contract_id, &borrower).utilized_amount,
+            300_i128
+        );
+
+        // Overpayment (pay more than utilized)
+        call_contract(&env, &contract_id, || {
+            Credit::repay_credit(env.clone(), borrower.clone(), 500_i128).unwrap();
+        });
+
+        let credit_data = get_credit_data(&env, &contract_id, &borrower);
+        assert_eq!(credit_data.utilized_amount, 0_i128); // Should be capped at 0
+    }
+
+    #[test]
+    fn test_repay_credit_zero_utilization() {
+        let env = Env::default();
+        let (_admin, borrower, contract_id) = setup_test(&env);
+
+        // Try to repay when no credit is utilized
+        call_contract(&env, &contract_id, || {
+            Credit::repay_credit(env.clone(), borrower.clone(), 100_i128).unwrap();
+        });
+
+        let credit_data = get_credit_data(&env, &contract_id, &borrower);
+        assert_eq!(credit_data.utilized_amount, 0_i128); // Should remain 0
+    }
+
+    #[test]
+    fn test_repay_credit_suspended_status() {
+        let env = Env::default();
+        let (_admin, borrower, contract_id) = setup_test(&env);
+
+        // Draw some credit
+        call_contract(&env, &contract_id, || {
+            Credit::draw_credit(env.clone(), borrower.clone(), 500_i128).unwrap();
+        });
+
+        // Manually set status to Suspended
+        let mut credit_data = get_credit_data(&env, &contract_id, &borrower);
+        credit_data.status = CreditStatus::Suspended;
+        env.as_contract(&contract_id, || {
+            env.storage()
+                .persistent()
+                .set(&DataKey::CreditLine(borrower.clone()), &credit_data);
+        });
+
+        // Should be able to repay even when suspended
+        call_contract(&env, &contract_id, || {
+            Credit::repay_credit(env.clone(), borrower.clone(), 200_i128).unwrap();
+        });
+
+        let updated_data = get_credit_data(&env, &contract_id, &borrower);
+        assert_eq!(updated_data.utilized_amount, 300_i128);
+        assert_eq!(updated_data.status, CreditStatus::Suspended); // Status should remain Suspended
+    }
+
+    #[test]
+    fn test_repay_credit_invalid_amount_zero() {
+        let env = Env::default();
+        let (_admin, borrower, contract_id) = setup_test(&env);
+
+        let result = env.as_contract(&contract_id, || {
+            Credit::repay_credit(env.clone(), borrower.clone(), 0_i128)
+        });
+        assert_eq!(result, Err(CreditError::InvalidAmount));
+    }
+
+    #[test]
+    fn test_repay_credit_invalid_amount_negative() {
+        let env = Env::default();
+        let (_admin, borrower, contract_id) = setup_test(&env);
+
+        let negative_amount: i128 = -100;
+        let result = env.as_contract(&contract_id, || {
+            Credit::repay_credit(env.clone(), borrower.clone(), negative_amount)
+        });
+        assert_eq!(result, Err(CreditError::InvalidAmount));
+    }
+
+    #[test]
+    fn test_full_lifecycle() {
+        let env = Env::default();
+        env.mock_all_auths();
+
+        let admin = Address::generate(&env);
+        let borrower = Address::generate(&env);
+
+        let contract_id = env.register(Credit, ());
+        let client = CreditClient::new(&env, &contract_id);
+
+        client.init(&admin);
+        client.grant_role(&Role::RiskEngine, &admin);
+
+        client.open_credit_line(&admin, &borrower, &5000_i128, &500_u32, &80_u32);
+        let credit_line = client.get_credit_line(&borrower).unwrap();
+        assert_eq!(credit_line.status, CreditStatus::Active);
+
+        client.suspend_credit_line(&borrower);
+        let credit_line = client.get_credit_line(&borrower).unwrap();
+        assert_eq!(credit_line.status, CreditStatus::Suspended);
+
+        client.close_credit_line(&borrower, &admin);
+        let credit_line = client.get_credit_line(&borrower).unwrap();
+        assert_eq!(credit_line.status, CreditStatus::Closed);
+    }
+
+    #[test]
+    fn test_event_data_integrity() {
+        let env = Env::default();
+        env.mock_all_auths();
+
+        let admin = Address::generate(&env);
+        let borrower = Address::generate(&env);
+
+        let contract_id = env.register(Credit, ());
+        let client = CreditClient::new(&env, &contract_id);
+
+        client.init(&admin);
+        client.grant_role(&Role::RiskEngine, &admin);
+        client.open_credit_line(&admin, &borrower, &2000_i128, &400_u32, &75_u32);
+
+        let credit_line = client.get_credit_line(&borrower).unwrap();
+        assert_eq!(credit_line.borrower, borrower);
+        assert_eq!(credit_line.status, CreditStatus::Active);
+        assert_eq!(credit_line.credit_limit, 2000);
+        assert_eq!(credit_line.interest_rate_bps, 400);
+        assert_eq!(credit_line.risk_score, 75);
+    }
+
+    #[test]
+    fn test_suspend_nonexistent_credit_line() {
+        let env = Env::default();
+        env.mock_all_auths();
+        let admin = Address::generate(&env);
+        let borrower = Address::generate(&env);
+
+        let contract_id = env.register(Credit, ());
+        let client = CreditClient::new(&env, &contract_id);
+
+        client.init(&admin);
+        assert_eq!(
+            client.try_suspend_credit_line(&borrower),
+            Err(Ok(CreditError::CreditLineNotFound))
+        );
+    }
+
+    #[test]
+    fn test_close_nonexistent_credit_line() {
+        let env = Env::default();
+        env.mock_all_auths();
+        let admin = Address::generate(&env);
+        let borrower = Address::generate(&env);
+
+        let contract_id = env.register(Credit, ());
+        let client = CreditClient::new(&env, &contract_id);
+
+        client.init(&admin);
+        assert_eq!(
+            client.try_close_credit_line(&borrower, &admin),
+            Err(Ok(CreditError::CreditLineNotFound))
+        );
+    }
+
+    #[test]
+    fn test_default_nonexistent_credit_line() {
+        let env = Env::default();
+        env.mock_all_auths();
+        let admin = Address::generate(&env);
+        let borrower = Address::generate(&env);
+
+        let contract_id = env.register(Credit, ());
+        let client = CreditClient::new(&env, &contract_id);
+
+        client.init(&admin);
+        assert_eq!(
+            client.try_default_credit_line(&borrower),
+            Err(Ok(CreditError::CreditLineNotFound))
+        );
+    }
+
+    #[test]
+    fn test_multiple_borrowers() {
+        let env = Env::default();
+        env.mock_all_auths();
+
+        let admin = Address::generate(&env);
+        let borrower1 = Address::generate(&env);
+        let borrower2 = Address::generate(&env);
+
+        let contract_id = env.register(Credit, ());
+        let client = CreditClient::new(&env, &contract_id);
+
+        client.init(&admin);
+        client.grant_role(&Role::RiskEngine, &admin);
+        client.open_credit_line(&admin, &borrower1, &1000_i128, &300_u32, &70_u32);
+        client.open_credit_line(&admin, &borrower2, &2000_i128, &400_u32, &80_u32);
+
+        let credit_line1 = client.get_credit_line(&borrower1).unwrap();
+        let credit_line2 = client.get_credit_line(&borrower2).unwrap();
+
+        assert_eq!(credit_line1.credit_limit, 1000);
+        assert_eq!(credit_line2.credit_limit, 2000);
+        assert_eq!(credit_line1.status, CreditStatus::Active);
+        assert_eq!(credit_line2.status, CreditStatus::Active);
+    }
+
+    #[test]
+    fn test_lifecycle_transitions() {
+        let env = Env::default();
+        env.mock_all_auths();
+
+        let admin = Address::generate(&env);
+        let borrower = Address::generate(&env);
+
+        let contract_id = env.register(Credit, ());
+        let client = CreditClient::new(&env, &contract_id);
+
+        client.init(&admin);
+        client.grant_role(&Role::RiskEngine, &admin);
+
+        client.open_credit_line(&admin, &borrower, &1000_i128, &300_u32, &70_u32);
+        assert_eq!(
+            client.get_credit_line(&borrower).unwrap().status,
+            CreditStatus::Active
+        );
+
+        client.default_credit_line(&borrower);
+        assert_eq!(
+            client.get_credit_line(&borrower).unwrap().status,
+            CreditStatus::Defaulted
+        );
+    }
+
+    #[test]
+    fn test_close_credit_line_borrower_when_utilized_zero() {
+        let env = Env::default();
+        env.mock_all_auths();
+
+        let admin = Address::generate(&env);
+        let borrower = Address::generate(&env);
+
+        let contract_id = env.register(Credit, ());
+        let client = CreditClient::new(&env, &contract_id);
+
+        client.init(&admin);
+        client.grant_role(&Role::RiskEngine, &admin);
+        client.open_credit_line(&admin, &borrower, &1000_i128, &300_u32, &70_u32);
+        client.close_credit_line(&borrower, &borrower);
+
+        let credit_line = client.get_credit_line(&borrower).unwrap();
+        assert_eq!(credit_line.status, CreditStatus::Closed);
+        assert_eq!(credit_line.utilized_amount, 0);
+    }
+
+    #[test]
+    fn test_close_credit_line_borrower_rejected_when_utilized_nonzero() {
+        let env = Env::default();
+        env.mock_all_auths();
+
+        let admin = Address::generate(&env);
+        let borrower = Address::generate(&env);
+
+        let contract_id = env.register(Credit, ());
+        let client = CreditClient::new(&env, &contract_id);
+
+        client.init(&admin);
+        client.grant_role(&Role::RiskEngine, &admin);
+        client.open_credit_line(&admin, &borrower, &1000_i128, &300_u32, &70_u32);
+        client.draw_credit(&borrower, &300_i128);
+
+        assert_eq!(
+            client.try_close_credit_line(&borrower, &borrower),
+            Err(Ok(CreditError::UtilizationNotZero))
+        );
+    }
+
+    #[test]
+    fn test_close_credit_line_admin_force_close_with_utilization() {
+        let env = Env::default();
+        env.mock_all_auths();
+
+        let admin = Address::generate(&env);
+        let borrower = Address::generate(&env);
+
+        let contract_id = env.register(Credit, ());
+        let client = CreditClient::new(&env, &contract_id);
+
+        client.init(&admin);
+        client.grant_role(&Role::RiskEngine, &admin);
+        client.open_credit_line(&admin, &borrower, &1000_i128, &300_u32, &70_u32);
+        client.draw_credit(&borrower, &300_i128);
+        assert_eq!(
+            client.get_credit_line(&borrower).unwrap().utilized_amount,
+            300
+        );
+
+        client.close_credit_line(&borrower, &admin);
+
+        let credit_line = client.get_credit_line(&borrower).unwrap();
+        assert_eq!(credit_line.status, CreditStatus::Closed);
+        assert_eq!(credit_line.utilized_amount, 300);
+    }
+
+    #[test]
+    fn test_reopen_rejected_while_force_closed_line_owes() {
+        let env = Env::default();
+        env.mock_all_auths();
+
+        let admin = Address::generate(&env);
+        let borrower = Address::generate(&env);
+
+        let contract_id = env.register(Credit, ());
+        let client = CreditClient::new(&env, &contract_id);
+
+        client.init(&admin);
+        client.grant_role(&Role::RiskEngine, &admin);
+        client.open_credit_line(&admin, &borrower, &1000_i128, &300_u32, &70_u32);
+        client.draw_credit(&borrower, &300_i128);
+        client.close_credit_line(&borrower, &admin);
+
+        let result = client.try_open_credit_line(&admin, &borrower, &1000_i128, &300_u32, &70_u32);
+        assert_eq!(result, Err(Ok(CreditError::UtilizationNotZero)));
+        let credit_line = client.get_credit_line(&borrower).unwrap();
+        assert_eq!(credit_line.status, CreditStatus::Closed);
+        assert_eq!(credit_line.utilized_amount, 300);
+        assert_eq!(client.get_portfolio_stats().total_utilized, 300);
+    }
+
+    #[test]
+    fn test_close_credit_line_idempotent_when_already_closed() {
+        let env = Env::default();
+        env.mock_all_auths();
+
+        let admin = Address::generate(&env);
+        let borrower = Address::generate(&env);
+
+        let contract_id = env.register(Credit, ());
+        let client = CreditClient::new(&env, &contract_id);
+
+        client.init(&admin);
+        client.grant_role(&Role::RiskEngine, &admin);
+        client.open_credit_line(&admin, &borrower, &1000_i128, &300_u32, &70_u32);
+        client.close_credit_line(&borrower, &admin);
+        client.close_credit_line(&borrower, &admin);
+
+        assert_eq!(
+            client.get_credit_line(&borrower).unwrap().status,
+            CreditStatus::Closed
+        );
+    }
+
+    #[test]
+    fn test_draw_credit_rejected_when_closed() {
+        let env = Env::default();
+        env.mock_all_auths();
+
+        let admin = Address::generate(&env);
+        let borrower = Address::generate(&env);
+
+        let contract_id = env.register(Credit, ());
+        let client = CreditClient::new(&env, &contract_id);
+
+        client.init(&admin);
+        client.grant_role(&Role::RiskEngine, &admin);
+        client.open_credit_line(&admin, &borrower, &1000_i128, &300_u32, &70_u32);
+        client.close_credit_line(&borrower, &admin);
+
+        assert_eq!(
+            client.try_draw_credit(&borrower, &100_i128),
+            Err(Ok(CreditError::CreditLineClosed))
+        );
+    }
+
+    #[test]
+    fn test_draw_credit_rejected_when_exceeding_limit() {
+        let env = Env::default();
+        env.mock_all_auths();
+
+        let admin = Address::generate(&env);
+        let borrower = Address::generate(&env);
+
+        let contract_id = env.register(Credit, ());
+        let client = CreditClient::new(&env, &contract_id);
+
+        client.init(&admin);
+        client.grant_role(&Role::RiskEngine, &admin);
+        client.open_credit_line(&admin, &borrower, &100_i128, &300_u32, &70_u32);
+        assert_eq!(
+            client.try_draw_credit(&borrower, &101_i128),
+            Err(Ok(CreditError::CreditLimitExceeded))
+        );
+    }
+
+    #[test]
+    fn test_repay_credit_rejected_when_closed() {
+        let env = Env::default();
+        env.mock_all_auths();
+
+        let admin = Address::generate(&env);
+        let borrower = Address::generate(&env);
+
+        let contract_id = env.register(Credit, ());
+        let client = CreditClient::new(&env, &contract_id);
+
+        client.init(&admin);
+        client.grant_role(&Role::RiskEngine, &admin);
+        client.open_credit_line(&admin, &borrower, &1000_i128, &300_u32, &70_u32);
+        client.close_credit_line(&borrower, &admin);
+
+        assert_eq!(
+            client.try_repay_credit(&borrower, &100_i128),
+            Err(Ok(CreditError::CreditLineClosed))
+        );
+    }
+
+    #[test]
+    fn test_close_credit_line_unauthorized_closer() {
+        let env = Env::default();
+        env.mock_all_auths();
+
+        let admin = Address::generate(&env);
+        let borrower = Address::generate(&env);
+        let other = Address::generate(&env);
+
+        let contract_id = env.register(Credit, ());
+        let client = CreditClient::new(&env, &contract_id);
+
+        client.init(&admin);
+        client.grant_role(&Role::RiskEngine, &admin);
+        client.open_credit_line(&admin, &borrower, &1000_i128, &300_u32, &70_u32);
+        assert_eq!(
+            client.try_close_credit_line(&borrower, &other),
+            Err(Ok(CreditError::Unauthorized))
+        );
+    }
+
+    #[test]
+    fn test_draw_credit_updates_utilized() {
+        let env = Env::default();
+        env.mock_all_auths();
+
+        let admin = Address::generate(&env);
+        let borrower = Address::generate(&env);
+
+        let contract_id = env.register(Credit, ());
+        let client = CreditClient::new(&env, &contract_id);
+
+        client.init(&admin);
+        client.grant_role(&Role::RiskEngine, &admin);
+        client.open_credit_line(&admin, &borrower, &1000_i128, &300_u32, &70_u32);
+
+        client.draw_credit(&borrower, &200_i128);
+        assert_eq!(
+            client.get_credit_line(&borrower).unwrap().utilized_amount,
+            200
+        );
+
+        client.draw_credit(&borrower, &300_i128);
+        assert_eq!(
+            client.get_credit_line(&borrower).unwrap().utilized_amount,
+            500
+        );
+    }
+
+    // --- draw_credit: zero and negative amount guards ---
+
+    #[test]
+    fn test_draw_credit_rejected_when_amount_is_zero() {
+        let env = Env::default();
+        env.mock_all_auths();
+
+        let admin = Address::generate(&env);
+        let borrower = Address::generate(&env);
+
+        let contract_id = env.register(Credit, ());
+        let client = CreditClient::new(&env, &contract_id);
+
+        client.init(&admin);
+        client.grant_role(&Role::RiskEngine, &admin);
+        client.open_credit_line(&admin, &borrower, &1000_i128, &300_u32, &70_u32);
+
+        // Should panic: zero is not a positive amount
+        assert_eq!(
+            client.try_draw_credit(&borrower, &0_i128),
+            Err(Ok(CreditError::InvalidAmount))
+        );
+    }
+
+    #[test]
+    fn test_draw_credit_rejected_when_amount_is_negative() {
+        let env = Env::default();
+        env.mock_all_auths();
+
+        let admin = Address::generate(&env);
+        let borrower = Address::generate(&env);
+
+        let contract_id = env.register(Credit, ());
+        let client = CreditClient::new(&env, &contract_id);
+
+        client.init(&admin);
+        client.grant_role(&Role::RiskEngine, &admin);
+        client.open_credit_line(&admin, &borrower, &1000_i128, &300_u32, &70_u32);
+
+        // i128 allows negatives — the guard `amount <= 0` must catch this
+        assert_eq!(
+            client.try_draw_credit(&borrower, &-1_i128),
+            Err(Ok(CreditError::InvalidAmount))
+        );
+    }
+
+    // --- repay_credit: zero and negative amount guards ---
+
+    #[test]
+    fn test_repay_credit_rejects_non_positive_amount() {
+        let env = Env::default();
+        env.mock_all_auths();
+
+        let admin = Address::generate(&env);
+        let borrower = Address::generate(&env);
+
+        let contract_id = env.register(Credit, ());
+        let client = CreditClient::new(&env, &contract_id);
+
+        client.init(&admin);
+        client.grant_role(&Role::RiskEngine, &admin);
+        client.open_credit_line(&admin, &borrower, &1000_i128, &300_u32, &70_u32);
+
+        // Should panic: repaying zero is meaningless and must be rejected
+        assert_eq!(
+            client.try_repay_credit(&borrower, &0_i128),
+            Err(Ok(CreditError::InvalidAmount))
+        );
+    }
+
+    #[test]
+    fn test_repay_credit_rejected_when_amount_is_negative() {
+        let env = Env::default();
+        env.mock_all_auths();
+
+        let admin = Address::generate(&env);
+        let borrower = Address::generate(&env);
+
+        let contract_id = env.register(Credit, ());
+        let client = CreditClient::new(&env, &contract_id);
+
+        client.init(&admin);
+        client.grant_role(&Role::RiskEngine, &admin);
+        client.open_credit_line(&admin, &borrower, &1000_i128, &300_u32, &70_u32);
+
+        // Negative repayment would effectively be a draw — must be rejected
+        assert_eq!(
+            client.try_repay_credit(&borrower, &-500_i128),
+            Err(Ok(CreditError::InvalidAmount))
+        );
+    }
+
+    // --- update_risk_parameters ---
+
+    #[test]
+    fn test_update_risk_parameters_success() {
+        let env = Env::default();
+        env.mock_all_auths();
+
+        let admin = Address::generate(&env);
+        let borrower = Address::generate(&env);
+
+        let contract_id = env.register(Credit, ());
+        let client = CreditClient::new(&env, &contract_id);
+
+        client.init(&admin);
+        client.grant_role(&Role::RiskEngine, &admin);
+        client.open_credit_line(&admin, &borrower, &1000_i128, &300_u32, &70_u32);
+
+        client.update_risk_parameters(&admin, &borrower, &2000_i128, &400_u32, &85_u32);
+
+        let credit_line = client.get_credit_line(&borrower).unwrap();
+        assert_eq!(credit_line.credit_limit, 2000);
+        assert_eq!(credit_line.interest_rate_bps, 400);
+        assert_eq!(credit_line.risk_score, 85);
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_update_risk_parameters_unauthorized_caller() {
+        let env = Env::default();
+        env.mock_all_auths();
+        let admin = Address::generate(&env);
+        let borrower = Address::generate(&env);
+
+        let contract_id = env.register(Credit, ());
+        let client = CreditClient::new(&env, &contract_id);
+
+        client.init(&admin);
+        client.grant_role(&Role::RiskEngine, &admin);
+        client.open_credit_line(&admin, &borrower, &1000_i128, &300_u32, &70_u32);
+        // Drop mocked auths: no auth means caller.require_auth() will fail.
+        env.set_auths(&[]);
+        client.update_risk_parameters(&admin, &borrower, &2000_i128, &400_u32, &85_u32);
+    }
+
+    #[test]
+    fn test_update_risk_parameters_nonexistent_line() {
+        let env = Env::default();
+        env.mock_all_auths();
+
+        let admin = Address::generate(&env);
+        let borrower = Address::generate(&env);
+
+        let contract_id = env.register(Credit, ());
+        let client = CreditClient::new(&env, &contract_id);
+
+        client.init(&admin);
+        client.grant_role(&Role::RiskEngine, &admin);
+        assert_eq!(
+            client.try_update_risk_parameters(&admin, &borrower, &1000_i128, &300_u32, &70_u32),
+            Err(Ok(CreditError::CreditLineNotFound))
+        );
+    }
+
+    #[test]
+    fn test_update_risk_parameters_credit_limit_below_utilized() {
+        let env = Env::default();
+        env.mock_all_auths();
+
+        let admin = Address::generate(&env);
+        let borrower = Address::generate(&env);
+
+        let contract_id = env.register(Credit, ());
+        let client = CreditClient::new(&env, &contract_id);
+
+        client.init(&admin);
+        client.grant_role(&Role::RiskEngine, &admin);
+        client.open_credit_line(&admin, &borrower, &1000_i128, &300_u32, &70_u32);
+        client.draw_credit(&borrower, &500_i128);
+
+        assert_eq!(
+            client.try_update_risk_parameters(&admin, &borrower, &300_i128, &300_u32, &70_u32),
+            Err(Ok(CreditError::CreditLimitBelowUtilized))
+        );
+    }
+
+    #[test]
+    fn test_update_risk_parameters_negative_credit_limit() {
+        let env = Env::default();
+        env.mock_all_auths();
+
+        let admin = Address::generate(&env);
+        let borrower = Address::generate(&env);
+
+        let contract_id = env.register(Credit, ());
+        let client = CreditClient::new(&env, &contract_id);
+
+        client.init(&admin);
+        client.grant_role(&Role::RiskEngine, &admin);
+        client.open_credit_line(&admin, &borrower, &1000_i128, &300_u32, &70_u32);
+        assert_eq!(
+            client.try_update_risk_parameters(&admin, &borrower, &(-1_i128), &300_u32, &70_u32),
+            Err(Ok(CreditError::InvalidCreditLimit))
+        );
+    }
+
+    #[test]
+    fn test_update_risk_parameters_interest_rate_exceeds_max() {
+        let env = Env::default();
+        env.mock_all_auths();
+
+        let admin = Address::generate(&env);
+        let borrower = Address::generate(&env);
+
+        let contract_id = env.register(Credit, ());
+        let client = CreditClient::new(&env, &contract_id);
+
+        client.init(&admin);
+        client.grant_role(&Role::RiskEngine, &admin);
+        client.open_credit_line(&admin, &borrower, &1000_i128, &300_u32, &70_u32);
+        assert_eq!(
+            client.try_update_risk_parameters(&admin, &borrower, &1000_i128, &10001_u32, &70_u32),
+            Err(Ok(CreditError::InvalidInterestRate))
+        );
+    }
+
+    #[test]
+    fn test_update_risk_parameters_risk_score_exceeds_max() {
+        let env = Env::default();
+        env.mock_all_auths();
+
+        let admin = Address::generate(&env);
+        let borrower = Address::generate(&env);
+
+        let contract_id = env.register(Credit, ());
+        let client = CreditClient::new(&env, &contract_id);
+
+        client.init(&admin);
+        client.grant_role(&Role::RiskEngine, &admin);
+        client.open_credit_line(&admin, &borrower, &1000_i128, &300_u32, &70_u32);
+        assert_eq!(
+            client.try_update_risk_parameters(&admin, &borrower, &1000_i128, &300_u32, &101_u32),
+            Err(Ok(CreditError::InvalidRiskScore))
+        );
+    }
+
+    #[test]
+    fn test_update_risk_parameters_at_boundaries() {
+        let env = Env::default();
+        env.mock_all_auths();
+
+        let admin = Address::generate(&env);
+        let borrower = Address::generate(&env);
+
+        let contract_id = env.register(Credit, ());
+        let client = CreditClient::new(&env, &contract_id);
+
+        client.init(&admin);
+        client.grant_role(&Role::RiskEngine, &admin);
+        client.open_credit_line(&admin, &borrower, &1000_i128, &300_u32, &70_u32);
+        client.update_risk_parameters(&admin, &borrower, &1000_i128, &10000_u32, &100_u32);
+
+        let credit_line = client.get_credit_line(&borrower).unwrap();
+        assert_eq!(credit_line.interest_rate_bps, 10000);
+        assert_eq!(credit_line.risk_score, 100);
+    }
+
+    // --- repay_credit: happy path and event emission ---
+
+    #[test]
+    fn test_repay_credit_reduces_utilized_and_emits_event() {
+        let env = Env::default();
+        env.mock_all_auths();
+
+        let admin = Address::generate(&env);
+        let borrower = Address::generate(&env);
+
+        let contract_id = env.register(Credit, ());
+        let client = CreditClient::new(&env, &contract_id);
+
+        client.init(&admin);
+        client.grant_role(&Role::RiskEngine, &admin);
+        client.open_credit_line(&admin, &borrower, &1000_i128, &300_u32, &70_u32);
+        client.draw_credit(&borrower, &500_i128);
+
+        let _ = env.events().all();
+        client.repay_credit(&borrower, &200_i128);
+        let events_after = env.events().all().len();
+
+        let credit_line = client.get_credit_line(&borrower).unwrap();
+        assert_eq!(credit_line.utilized_amount, 300);
+        assert_eq!(
+            events_after, 1,
+            "repay_credit must emit exactly one RepaymentEvent"
+        );
+    }
+
+    #[test]
+    fn test_repay_credit_saturates_at_zero() {
+        let env = Env::default();
+        env.mock_all_auths();
+
+        let admin = Address::generate(&env);
+        let borrower = Address::generate(&env);
+
+        let contract_id = env.register(Credit, ());
+        let client = CreditClient::new(&env, &contract_id);
+
+        client.init(&admin);
+        client.grant_role(&Role::RiskEngine, &admin);
+        client.open_credit_line(&admin, &borrower, &1000_i128, &300_u32, &70_u32);
+        client.draw_credit(&borrower, &100_i128);
+        client.repay_credit(&borrower, &500_i128);
+
+        let credit_line = client.get_credit_line(&borrower).unwrap();
+        assert_eq!(credit_line.utilized_amount, 0);
+    }
+
+    #[test]
+    fn test_repay_credit_nonexistent_line() {
+        let env = Env::default();
+        env.mock_all_auths();
+
+        let admin = Address::generate(&env);
+        let borrower = Address::generate(&env);
+
+        let contract_id = env.register(Credit, ());
+        let client = CreditClient::new(&env, &contract_id);
+
+        client.init(&admin);
+        assert_eq!(
+            client.try_repay_credit(&borrower, &100_i128),
+            Err(Ok(CreditError::CreditLineNotFound))
+        );
+    }
+
+    // --- suspend/default: unauthorized caller ---
+
+    #[test]
+    #[should_panic]
+    fn test_suspend_credit_line_unauthorized() {
+        let env = Env::default();
+        env.mock_all_auths();
+        let admin = Address::generate(&env);
+        let borrower = Address::generate(&env);
+
+        let contract_id = env.register(Credit, ());
+        let client = CreditClient::new(&env, &contract_id);
+
+        client.init(&admin);
+        client.grant_role(&Role::RiskEngine, &admin);
+        client.open_credit_line(&admin, &borrower, &1000_i128, &300_u32, &70_u32);
+        env.set_auths(&[]);
+        client.suspend_credit_line(&borrower);
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_default_credit_line_unauthorized() {
+        let env = Env::default();
+        env.mock_all_auths();
+        let admin = Address::generate(&env);
+        let borrower = Address::generate(&env);
+
+        let contract_id = env.register(Credit, ());
+        let client = CreditClient::new(&env, &contract_id);
+
+        client.init(&admin);
+        client.grant_role(&Role::RiskEngine, &admin);
+        client.open_credit_line(&admin, &borrower, &1000_i128, &300_u32, &70_u32);
+        env.set_auths(&[]);
+        client.default_credit_line(&borrower);
+    }
+
+    // --- Reentrancy guard: cleared correctly after draw and repay ---
+    //
+    // We cannot simulate a token callback in unit tests without a mock contract.
+    // These tests verify the guard is cleared on the happy path so that sequential
+    // calls succeed, proving no guard leak occurs on successful execution.
+
+    #[test]
+    fn test_reentrancy_guard_cleared_after_draw() {
+        let env = Env::default();
+        env.mock_all_auths();
+
+        let admin = Address::generate(&env);
+        let borrower = Address::generate(&env);
+
+        let contract_id = env.register(Credit, ());
+        let client = CreditClient::new(&env, &contract_id);
+
+        client.init(&admin);
+        client.grant_role(&Role::RiskEngine, &admin);
+        client.open_credit_line(&admin, &borrower, &1000_i128, &300_u32, &70_u32);
+        client.draw_credit(&borrower, &100_i128);
+        client.draw_credit(&borrower, &100_i128);
+        assert_eq!(
+            client.get_credit_line(&borrower).unwrap().utilized_amount,
+            200
+        );
+    }
+
+    #[test]
+    fn test_reentrancy_guard_cleared_after_repay() {
+        let env = Env::default();
+        env.mock_all_auths();
+
+        let admin = Address::generate(&env);
+        let borrower = Address::generate(&env);
+
+        let contract_id = env.register(Credit, ());
+        let client = CreditClient::new(&env, &contract_id);
+
+        client.init(&admin);
+        client.grant_role(&Role::RiskEngine, &admin);
+        client.open_credit_line(&admin, &borrower, &1000_i128, &300_u32, &70_u32);
+        client.draw_credit(&borrower, &200_i128);
+        client.repay_credit(&borrower, &50_i128);
+        client.repay_credit(&borrower, &50_i128);
+        assert_eq!(
+            client.get_credit_line(&borrower).unwrap().utilized_amount,
+            100
+        );
+    }
+
+    #[test]
+    fn test_draw_credit_with_sufficient_liquidity() {
+        let env = Env::default();
+        env.mock_all_auths();
+
+        let admin = Address::generate(&env);
+        let borrower = Address::generate(&env);
+        let token_admin = Address::generate(&env);
+
+        let contract_id = env.register(Credit, ());
+        let client = CreditClient::new(&env, &contract_id);
+
+        client.init(&admin);
+        client.grant_role(&Role::RiskEngine, &admin);
+        client.open_credit_line(&admin, &borrower, &1_000_i128, &300_u32, &70_u32);
+
+        let token = env.register_stellar_asset_contract_v2(token_admin);
+        let token_admin_client = StellarAssetClient::new(&env, &token.address());
+        let token_client = token::Client::new(&env, &token.address());
+
+        client.set_liquidity_token(&token.address());
+
+        token_admin_client.mint(&contract_id, &500_i128);
+        client.draw_credit(&borrower, &200_i128);
+
+        assert_eq!(token_client.balance(&contract_id), 300_i128);
+        assert_eq!(token_client.balance(&borrower), 200_i128);
+        assert_eq!(
+            client.get_credit_line(&borrower).unwrap().utilized_amount,
+            200_i128
+        );
+    }
+
+    #[test]
+    fn test_set_liquidity_source_updates_instance_storage() {
+        let env = Env::default();
+        env.mock_all_auths();
+
+        let admin = Address::generate(&env);
+        let reserve = Address::generate(&env);
+
+        let contract_id = env.register(Credit, ());
+        let client = CreditClient::new(&env, &contract_id);
+
+        client.init(&admin);
+        client.set_liquidity_source(&reserve);
+
+        let stored: Address = env
+            .as_contract(&contract_id, || {
+                env.storage().instance().get(&DataKey::LiquiditySource)
+            })
+            .unwrap();
+        assert_eq!(stored, reserve);
+    }
+
+    #[test]
+    fn test_draw_credit_uses_configured_external_liquidity_source() {
+        let env = Env::default();
+        env.mock_all_auths();
+
+        let admin = Address::generate(&env);
+        let borrower = Address::generate(&env);
+        let token_admin = Address::generate(&env);
+
+        let contract_id = env.register(Credit, ());
+        let client = CreditClient::new(&env, &contract_id);
+
+        client.init(&admin);
+        client.grant_role(&Role::RiskEngine, &admin);
+        client.open_credit_line(&admin, &borrower, &1_000_i128, &300_u32, &70_u32);
+
+        let token = env.register_stellar_asset_contract_v2(token_admin);
+        let token_admin_client = StellarAssetClient::new(&env, &token.address());
+        let token_client = token::Client::new(&env, &token.address());
+        let reserve = contract_id.clone();
+
+        client.set_liquidity_token(&token.address());
+        client.set_liquidity_source(&reserve);
+
+        token_admin_client.mint(&reserve, &500_i128);
+        client.draw_credit(&borrower, &120_i128);
+
+        assert_eq!(token_client.balance(&reserve), 380_i128);
+        assert_eq!(token_client.balance(&borrower), 120_i128);
+        assert_eq!(token_client.balance(&contract_id), 380_i128);
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_set_liquidity_token_requires_admin_auth() {
+        let env = Env::default();
+        let admin = Address::generate(&env);
+        let token_admin = Address::generate(&env);
+
+        let contract_id = env.register(Credit, ());
+        let client = CreditClient::new(&env, &contract_id);
+
+        client.init(&admin);
+
+        let token = env.register_stellar_asset_contract_v2(token_admin);
+        client.set_liquidity_token(&token.address());
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_set_liquidity_source_requires_admin_auth() {
+        let env = Env::default();
+        let admin = Address::generate(&env);
+        let reserve = Address::generate(&env);
+
+        let contract_id = env.register(Credit, ());
+        let client = CreditClient::new(&env, &contract_id);
+
+        client.init(&admin);
+        client.set_liquidity_source(&reserve);
+    }
+
+    #[test]
+    fn test_draw_credit_with_insufficient_liquidity() {
+        let env = Env::default();
+        env.mock_all_auths();
+
+        let admin = Address::generate(&env);
+        let borrower = Address::generate(&env);
+        let token_admin = Address::generate(&env);
+
+        let contract_id = env.register(Credit, ());
+        let client = CreditClient::new(&env, &contract_id);
+
+        client.init(&admin);
+        client.grant_role(&Role::RiskEngine, &admin);
+        client.open_credit_line(&admin, &borrower, &1_000_i128, &300_u32, &70_u32);
+
+        let token = env.register_stellar_asset_contract_v2(token_admin);
+        let token_admin_client = StellarAssetClient::new(&env, &token.address());
+
+        client.set_liquidity_token(&token.address());
+
+        token_admin_client.mint(&contract_id, &50_i128);
+        assert_eq!(
+            client.try_draw_credit(&borrower, &100_i128),
+            Err(Ok(CreditError::InsufficientLiquidity))
+        );
+    }
+
+    // --- interest accrual ---
+
+    fn advance_ledger(env: &Env, seconds: u64) {
+        env.ledger().with_mut(|li| li.timestamp += seconds);
+    }
+
+    fn setup_interest_line(env: &Env, rate_bps: u32) -> (CreditClient<'_>, Address) {
+        env.mock_all_auths();
+        let admin = Address::generate(env);
+        let borrower = Address::generate(env);
+        let contract_id = env.register(Credit, ());
+        let client = CreditClient::new(env, &contract_id);
+        client.init(&admin);
+        client.grant_role(&Role::RiskEngine, &admin);
+        client.open_credit_line(&admin, &borrower, &10_000_i128, &rate_bps, &70_u32);
+        (client, borrower)
+    }
+
+    #[test]
+    fn test_interest_accrues_over_time() {
+        let env = Env::default();
+        let (client, borrower) = setup_interest_line(&env, 1_000);
+        client.draw_credit(&borrower, &1_000_i128);
+
+        advance_ledger(&env, interest::SECONDS_PER_YEAR);
+        assert_eq!(client.get_outstanding(&borrower), 1_100);
+        // get_outstanding is a view: nothing is written until accrual.
+        assert_eq!(
+            client.get_credit_line(&borrower).unwrap().accrued_interest,
+            0
+        );
+
+        assert_eq!(client.accrue_interest(&borrower), 100);
+        let line = client.get_credit_line(&borrower).unwrap();
+        assert_eq!(line.utilized_amount, 1_000);
+        assert_eq!(line.accrued_interest, 100);
+        assert_eq!(line.last_accrual_ts, interest::SECONDS_PER_YEAR);
+        assert_eq!(client.get_outstanding(&borrower), 1_100);
+    }
+
+    #[test]
+    fn test_draw_accrues_interest_before_increasing_principal() {
+        let env = Env::default();
+        let (client, borrower) = setup_interest_line(&env, 1_000);
+        client.draw_credit(&borrower, &1_000_i128);
+
+        advance_ledger(&env, interest::SECONDS_PER_YEAR / 2);
+        client.draw_credit(&borrower, &1_000_i128);
+
+        let line = client.get_credit_line(&borrower).unwrap();
+        assert_eq!(line.accrued_interest, 50);
+        assert_eq!(line.utilized_amount, 2_000);
+
+        advance_ledger(&env, interest::SECONDS_PER_YEAR / 2);
+        assert_eq!(client.accrue_interest(&borrower), 150);
+    }
+
+    #[test]
+    fn test_repay_applies_to_interest_before_principal() {
+        let env = Env::default();
+        let (client, borrower) = setup_interest_line(&env, 1_000);
+        client.draw_credit(&borrower, &1_000_i128);
+
+        advance_ledger(&env, interest::SECONDS_PER_YEAR);
+        client.repay_credit(&borrower, &150_i128);
+
+        let line = client.get_credit_line(&borrower).unwrap();
+        assert_eq!(line.accrued_interest, 0);
+        assert_eq!(line.utilized_amount, 950);
+    }
+
+    #[test]
+    fn test_update_risk_parameters_accrues_at_old_rate() {
+        let env = Env::default();
+        let (client, borrower) = setup_interest_line(&env, 1_000);
+        let admin = client.get_admin();
+        client.draw_credit(&borrower, &1_000_i128);
+
+        advance_ledger(&env, interest::SECONDS_PER_YEAR);
+        client.update_risk_parameters(&admin, &borrower, &10_000_i128, &2_000_u32, &70_u32);
+        assert_eq!(
+            client.get_credit_line(&borrower).unwrap().accrued_interest,
+            100
+        );
+
+        advance_ledger(&env, interest::SECONDS_PER_YEAR);
+        assert_eq!(client.accrue_interest(&borrower), 300);
+    }
+
+    #[test]
+    fn test_accrue_interest_rounding_does_not_reset_clock() {
+        let env = Env::default();
+        let (client, borrower) = setup_interest_line(&env, 300);
+        client.draw_credit(&borrower, &1_000_i128);
+
+        // One second of interest on 1000 at 3% rounds down to zero.
+        advance_ledger(&env, 1);
+        assert_eq!(client.accrue_interest(&borrower), 0);
+        assert_eq!(
+            client.get_credit_line(&borrower).unwrap().last_accrual_ts,
+            0
+        );
+
+        advance_ledger(&env, interest::SECONDS_PER_YEAR - 1);
+        assert_eq!(client.accrue_interest(&borrower), 30);
+    }
+
+    #[test]
+    fn test_close_credit_line_borrower_after_repaying_principal_and_interest() {
+        let env = Env::default();
+        let (client, borrower) = setup_interest_line(&env, 1_000);
+        client.draw_credit(&borrower, &1_000_i128);
+
+        advance_ledger(&env, interest::SECONDS_PER_YEAR);
+        client.repay_credit(&borrower, &1_000_i128);
+        assert_eq!(client.get_outstanding(&borrower), 100);
+        client.repay_credit(&borrower, &100_i128);
+        assert_eq!(client.get_outstanding(&borrower), 0);
+
+        client.close_credit_line(&borrower, &borrower);
+        assert_eq!(
+            client.get_credit_line(&borrower).unwrap().status,
+            CreditStatus::Closed
+        );
+    }
+
+    #[test]
+    fn test_get_outstanding_nonexistent_line() {
+        let env = Env::default();
+        let (client, _borrower) = setup_interest_line(&env, 1_000);
+        assert_eq!(
+            client.try_get_outstanding(&Address::generate(&env)),
+            Err(Ok(CreditError::CreditLineNotFound))
+        );
+    }
+
+    // --- repay_credit: token pull and waterfall ---
+
+    fn setup_token_line(env: &Env, reserve_funding: i128) -> (CreditClient<'_>, Address, Address) {
+        env.mock_all_auths();
+        let admin = Address::generate(env);
+        let borrower = Address::generate(env);
+        let token_admin = Address::generate(env);
+        let contract_id = env.register(Credit, ());
+        let client = CreditClient::new(env, &contract_id);
+        client.init(&admin);
+        client.grant_role(&Role::RiskEngine, &admin);
+        client.open_credit_line(&admin, &borrower, &1_000_i128, &1_000_u32, &70_u32);
+
+        let token = env.register_stellar_asset_contract_v2(token_admin);
+        client.set_liquidity_token(&token.address());
+        StellarAssetClient::new(env, &token.address()).mint(&contract_id, &reserve_funding);
+        (client, borrower, token.address())
+    }
+
+    #[test]
+    fn test_repay_credit_transfers_tokens_to_liquidity_source() {
+        let env = Env::default();
+        let (client, borrower, token_address) = setup_token_line(&env, 1_000);
+        let token_client = token::Client::new(&env, &token_address);
+
+        client.draw_credit(&borrower, &400_i128);
+        client.repay_credit(&borrower, &150_i128);
+
+        assert_eq!(token_client.balance(&borrower), 250);
+        assert_eq!(token_client.balance(&client.address), 750);
+        assert_eq!(
+            client.get_credit_line(&borrower).unwrap().utilized_amount,
+            250
+        );
+    }
+
+    #[test]
+    fn test_repay_credit_overpayment_pulls_only_amount_owed() {
+        let env = Env::default();
+        let (client, borrower, token_address) = setup_token_line(&env, 1_000);
+        let token_client = token::Client::new(&env, &token_address);
+        StellarAssetClient::new(&env, &token_address).mint(&borrower, &500_i128);
+
+        client.draw_credit(&borrower, &300_i128);
+        client.repay_credit(&borrower, &800_i128);
+
+        assert_eq!(token_client.balance(&borrower), 500);
+        assert_eq!(token_client.balance(&client.address), 1_000);
+        assert_eq!(
+            client.get_credit_line(&borrower).unwrap().utilized_amount,
+            0
+        );
+    }
+
+    #[test]
+    fn test_repay_credit_pulls_interest_to_liquidity_source() {
+        let env = Env::default();
+        let (client, borrower, token_address) = setup_token_line(&env, 1_000);
+        let token_client = token::Client::new(&env, &token_address);
+        StellarAssetClient::new(&env, &token_address).mint(&borrower, &100_i128);
+
+        client.draw_credit(&borrower, &1_000_i128);
+        advance_ledger(&env, interest::SECONDS_PER_YEAR);
+        client.repay_credit(&borrower, &1_100_i128);
+
+        assert_eq!(token_client.balance(&borrower), 0);
+        assert_eq!(token_client.balance(&client.address), 1_100);
+        assert_eq!(client.get_outstanding(&borrower), 0);
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_repay_credit_reverts_when_borrower_lacks_tokens() {
+        let env = Env::default();
+        let (client, borrower, token_address) = setup_token_line(&env, 1_000);
+
+        client.draw_credit(&borrower, &400_i128);
+        token::Client::new(&env, &token_address).burn(&borrower, &400_i128);
+        client.repay_credit(&borrower, &100_i128);
+    }
+
+    #[test]
+    fn test_repay_credit_waterfall_fees_then_interest_then_principal() {
+        let env = Env::default();
+        let (client, borrower) = setup_interest_line(&env, 1_000);
+        client.draw_credit(&borrower, &1_000_i128);
+
+        let mut line = client.get_credit_line(&borrower).unwrap();
+        line.accrued_fees = 20;
+        env.as_contract(&client.address, || {
+            env.storage()
+                .persistent()
+                .set(&DataKey::CreditLine(borrower.clone()), &line);
+        });
+
+        advance_ledger(&env, interest::SECONDS_PER_YEAR);
+        client.repay_credit(&borrower, &150_i128);
+
+        let event = env.events().all().last().unwrap();
+        let payload = RepaymentEvent::try_from_val(&env, &event.2).unwrap();
+        assert_eq!(payload.amount, 150);
+        assert_eq!(payload.fees_paid, 20);
+        assert_eq!(payload.interest_paid, 100);
+        assert_eq!(payload.principal_paid, 30);
+        assert_eq!(payload.new_utilized_amount, 970);
+
+        let line = client.get_credit_line(&borrower).unwrap();
+        assert_eq!(line.accrued_fees, 0);
+        assert_eq!(line.accrued_interest, 0);
+        assert_eq!(line.utilized_amount, 970);
+    }
+
+    #[test]
+    fn test_repay_credit_event_reports_applied_amount_on_overpayment() {
+        let env = Env::default();
+        let (client, borrower) = setup_interest_line(&env, 1_000);
+        client.draw_credit(&borrower, &100_i128);
+        client.repay_credit(&borrower, &500_i128);
+
+        let event = env.events().all().last().unwrap();
+        let payload = RepaymentEvent::try_from_val(&env, &event.2).unwrap();
+        assert_eq!(payload.amount, 100);
+        assert_eq!(payload.principal_paid, 100);
+        assert_eq!(payload.new_utilized_amount, 0);
+    }
+
+    // --- typed errors: overflow, reentrancy and stable codes ---
+
+    #[test]
+    fn test_draw_credit_near_i128_max_succeeds_without_overflow() {
+        let env = Env::default();
+        env.mock_all_auths();
+        let admin = Address::generate(&env);
+        let borrower = Address::generate(&env);
+        let client = CreditClient::new(&env, &env.register(Credit, ()));
+        client.init(&admin);
+        client.grant_role(&Role::RiskEngine, &admin);
+        client.open_credit_line(&admin, &borrower, &i128::MAX, &300_u32, &70_u32);
+
+        client.draw_credit(&borrower, &(i128::MAX - 1));
+        client.draw_credit(&borrower, &1_i128);
+        assert_eq!(
+            client.get_credit_line(&borrower).unwrap().utilized_amount,
+            i128::MAX
+        );
+    }
+
+    #[test]
+    fn test_draw_credit_overflow_reverts_with_defined_error() {
+        let env = Env::default();
+        env.mock_all_auths();
+        let admin = Address::generate(&env);
+        let borrower = Address::generate(&env);
+        let client = CreditClient::new(&env, &env.register(Credit, ()));
+        client.init(&admin);
+        client.grant_role(&Role::RiskEngine, &admin);
+        client.open_credit_line(&admin, &borrower, &i128::MAX, &300_u32, &70_u32);
+        client.draw_credit(&borrower, &i128::MAX);
+
+        assert_eq!(
+            client.try_draw_credit(&borrower, &1_i128),
+            Err(Ok(CreditError::Overflow))
+        );
+    }
+
+    #[test]
+    fn test_draw_credit_large_values_exceed_limit_reverts_with_defined_error() {
+        let env = Env::default();
+        env.mock_all_auths();
+        let admin = Address::generate(&env);
+        let borrower = Address::generate(&env);
+        let client = CreditClient::new(&env, &env.register(Credit, ()));
+        client.init(&admin);
+        client.grant_role(&Role::RiskEngine, &admin);
+        client.open_credit_line(&admin, &borrower, &(i128::MAX - 10), &300_u32, &70_u32);
+
+        assert_eq!(
+            client.try_draw_credit(&borrower, &(i128::MAX - 9)),
+            Err(Ok(CreditError::CreditLimitExceeded))
+        );
+    }
+
+    #[test]
+    fn test_draw_credit_rejected_when_reentrancy_guard_held() {
+        let env = Env::default();
+        let (_admin, borrower, contract_id) = setup_test(&env);
+
+        let result = env.as_contract(&contract_id, || {
+            set_reentrancy_guard(&env).unwrap();
+            Credit::draw_credit(env.clone(), borrower.clone(), 100_i128)
+        });
+        assert_eq!(result, Err(CreditError::Reentrancy));
+    }
+
+    #[test]
+    fn test_reentrancy_guard_cleared_after_failed_call() {
+        let env = Env::default();
+        let (_admin, borrower, contract_id) = setup_test(&env);
+
+        let result = env.as_contract(&contract_id, || {
+            Credit::draw_credit(env.clone(), borrower.clone(), 5_000_i128)
+        });
+        assert_eq!(result, Err(CreditError::CreditLimitExceeded));
+        env.as_contract(&contract_id, || {
+            Credit::draw_credit(env.clone(), borrower.clone(), 100_i128).unwrap();
+        });
+    }
+
+    #[test]
+    fn test_admin_entrypoints_require_init() {
+        let env = Env::default();
+        env.mock_all_auths();
+        let client = CreditClient::new(&env, &env.register(Credit, ()));
+
+        assert_eq!(
+            client.try_set_liquidity_source(&Address::generate(&env)),
+            Err(Ok(CreditError::NotInitialized))
+        );
+    }
+
+    #[test]
+    fn test_credit_error_codes_are_stable() {
+        assert_eq!(CreditError::CreditLineNotFound as u32, 1);
+        assert_eq!(CreditError::Unauthorized as u32, 5);
+        assert_eq!(CreditError::CreditLimitExceeded as u32, 8);
+        assert_eq!(CreditError::InsufficientLiquidity as u32, 9);
+        assert_eq!(CreditError::Overflow as u32, 10);
+        assert_eq!(CreditError::CreditLineClosed as u32, 12);
+        assert_eq!(
+            soroban_sdk::Error::from(CreditError::CreditLineClosed),
+            soroban_sdk::Error::from_contract_error(12)
+        );
+    }
+
+    // --- init guard and two-step admin transfer ---
+
+    #[test]
+    fn test_init_twice() {
+        let env = Env::default();
+        let (admin, _borrower, contract_id) = setup_test(&env);
+        let client = CreditClient::new(&env, &contract_id);
+        let attacker = Address::generate(&env);
+
+        assert_eq!(
+            client.try_init(&attacker),
+            Err(Ok(CreditError::AlreadyInitialized))
+        );
+        assert_eq!(client.get_admin(), admin);
+    }
+
+    #[test]
+    fn test_admin_transfer_two_step() {
+        let env = Env::default();
+        let (admin, _borrower, contract_id) = setup_test(&env);
+        let client = CreditClient::new(&env, &contract_id);
+        let new_admin = Address::generate(&env);
+
+        client.propose_admin(&new_admin);
+        assert_eq!(client.get_pending_admin(), Some(new_admin.clone()));
+        // Proposal alone does not hand over control.
+        assert_eq!(client.get_admin(), admin);
+
+        client.accept_admin();
+        let event = env.events().all().last().unwrap();
+        let payload = AdminTransferredEvent::try_from_val(&env, &event.2).unwrap();
+        assert_eq!(payload.previous_admin, admin);
+        assert_eq!(payload.new_admin, new_admin);
+
+        assert_eq!(client.get_admin(), new_admin);
+        assert_eq!(client.get_pending_admin(), None);
+    }
+
+    #[test]
+    fn test_propose_admin_emits_event_and_can_be_replaced() {
+        let env = Env::default();
+        let (admin, _borrower, contract_id) = setup_test(&env);
+        let client = CreditClient::new(&env, &contract_id);
+        let first = Address::generate(&env);
+        let second = Address::generate(&env);
+
+        client.propose_admin(&first);
+        let event = env.events().all().last().unwrap();
+        let payload = AdminProposedEvent::try_from_val(&env, &event.2).unwrap();
+        assert_eq!(payload.admin, admin);
+        assert_eq!(payload.pending_admin, first);
+
+        client.propose_admin(&second);
+        assert_eq!(client.get_pending_admin(), Some(second));
+    }
+
+    #[test]
+    fn test_accept_admin_without_proposal() {
+        let env = Env::default();
+        let (_admin, _borrower, contract_id) = setup_test(&env);
+        let client = CreditClient::new(&env, &contract_id);
+
+        assert_eq!(
+            client.try_accept_admin(),
+            Err(Ok(CreditError::NoPendingAdmin))
+        );
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_propose_admin_requires_admin_auth() {
+        let env = Env::default();
+        let admin = Address::generate(&env);
+        let client = CreditClient::new(&env, &env.register(Credit, ()));
+        client.init(&admin);
+        client.propose_admin(&Address::generate(&env));
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_accept_admin_requires_pending_admin_auth() {
+        let env = Env::default();
+        let admin = Address::generate(&env);
+        let new_admin = Address::generate(&env);
+        let contract_id = env.register(Credit, ());
+        let client = CreditClient::new(&env, &contract_id);
+        client.init(&admin);
+
+        env.mock_auths(&[MockAuth {
+            address: &admin,
+            invoke: &MockAuthInvoke {
+                contract: &contract_id,
+                fn_name: "propose_admin",
+                args: (new_admin.clone(),).into_val(&env),
+                sub_invokes: &[],
+            },
+        }]);
+        client.propose_admin(&new_admin);
+
+        // Only the current admin signs; the pending admin's auth is missing.
+        env.mock_auths(&[MockAuth {
+            address: &admin,
+            invoke: &MockAuthInvoke {
+                contract: &contract_id,
+                fn_name: "accept_admin",
+                args: ().into_val(&env),
+                sub_invokes: &[],
+            },
+        }]);
+        client.accept_admin();
+    }
+
+    // --- roles: risk engine ---
+
+    #[test]
+    fn test_grant_and_revoke_role() {
+        let env = Env::default();
+        env.mock_all_auths();
+        let admin = Address::generate(&env);
+        let engine = Address::generate(&env);
+        let client = CreditClient::new(&env, &env.register(Credit, ()));
+        client.init(&admin);
+
+        assert!(!client.has_role(&Role::RiskEngine, &engine));
+        client.grant_role(&Role::RiskEngine, &engine);
+        let event = env.events().all().last().unwrap();
+        assert_eq!(
+            event.1,
+            (symbol_short!("credit"), symbol_short!("role_grnt")).into_val(&env)
+        );
+        let payload = RoleChangedEvent::try_from_val(&env, &event.2).unwrap();
+        assert_eq!(payload.role, Role::RiskEngine);
+        assert_eq!(payload.account, engine);
+        assert_eq!(payload.admin, admin);
+        assert!(client.has_role(&Role::RiskEngine, &engine));
+
+        client.revoke_role(&Role::RiskEngine, &engine);
+        let event = env.events().all().last().unwrap();
+        assert_eq!(
+            event.1,
+            (symbol_short!("credit"), symbol_short!("role_rvk")).into_val(&env)
+        );
+        assert!(!client.has_role(&Role::RiskEngine, &engine));
+    }
+
+    #[test]
+    fn test_grant_role_is_idempotent() {
+        let env = Env::default();
+        env.mock_all_auths();
+        let admin = Address::generate(&env);
+        let engine = Address::generate(&env);
+        let client = CreditClient::new(&env, &env.register(Credit, ()));
+        client.init(&admin);
+
+        client.grant_role(&Role::RiskEngine, &engine);
+        client.grant_role(&Role::RiskEngine, &engine);
+        assert_eq!(env.events().all().len(), 0);
+        client.revoke_role(&Role::RiskEngine, &engine);
+        client.revoke_role(&Role::RiskEngine, &engine);
+        assert_eq!(env.events().all().len(), 0);
+    }
+
+    #[test]
+    fn test_open_credit_line_by_risk_engine() {
+        let env = Env::default();
+        env.mock_all_auths();
+        let admin = Address::generate(&env);
+        let engine = Address::generate(&env);
+        let borrower = Address::generate(&env);
+        let client = CreditClient::new(&env, &env.register(Credit, ()));
+        client.init(&admin);
+        client.grant_role(&Role::RiskEngine, &engine);
+
+        client.open_credit_line(&engine, &borrower, &1_000_i128, &300_u32, &70_u32);
+        client.update_risk_parameters(&engine, &borrower, &2_000_i128, &300_u32, &70_u32);
+        assert_eq!(
+            client.get_credit_line(&borrower).unwrap().credit_limit,
+            2_000
+        );
+    }
+
+    #[test]
+    fn test_open_credit_line_rejected_without_risk_engine_role() {
+        let env = Env::default();
+        env.mock_all_auths();
+        let admin = Address::generate(&env);
+        let borrower = Address::generate(&env);
+        let client = CreditClient::new(&env, &env.register(Credit, ()));
+        client.init(&admin);
+
+        // Neither the borrower nor the admin may open lines without the role.
+        assert_eq!(
+            client.try_open_credit_line(&borrower, &borrower, &1_000_i128, &300_u32, &70_u32),
+            Err(Ok(CreditError::Unauthorized))
+        );
+        assert_eq!(
+            client.try_open_credit_line(&admin, &borrower, &1_000_i128, &300_u32, &70_u32),
+            Err(Ok(CreditError::Unauthorized))
+        );
+    }
+
+    #[test]
+    fn test_revoked_risk_engine_cannot_update_risk_parameters() {
+        let env = Env::default();
+        env.mock_all_auths();
+        let admin = Address::generate(&env);
+        let engine = Address::generate(&env);
+        let borrower = Address::generate(&env);
+        let client = CreditClient::new(&env, &env.register(Credit, ()));
+        client.init(&admin);
+        client.grant_role(&Role::RiskEngine, &engine);
+        client.open_credit_line(&engine, &borrower, &1_000_i128, &300_u32, &70_u32);
+
+        client.revoke_role(&Role::RiskEngine, &engine);
+        assert_eq!(
+            client.try_update_risk_parameters(&engine, &borrower, &2_000_i128, &300_u32, &70_u32),
+            Err(Ok(CreditError::Unauthorized))
+        );
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_open_credit_line_requires_caller_auth() {
+        let env = Env::default();
+        env.mock_all_auths();
+        let admin = Address::generate(&env);
+        let engine = Address::generate(&env);
+        let borrower = Address::generate(&env);
+        let client = CreditClient::new(&env, &env.register(Credit, ()));
+        client.init(&admin);
+        client.grant_role(&Role::RiskEngine, &engine);
+
+        env.set_auths(&[]);
+        client.open_credit_line(&engine, &borrower, &1_000_i128, &300_u32, &70_u32);
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_grant_role_requires_admin_auth() {
+        let env = Env::default();
+        let admin = Address::generate(&env);
+        let client = CreditClient::new(&env, &env.register(Credit, ()));
+        client.init(&admin);
+        client.grant_role(&Role::RiskEngine, &Address::generate(&env));
+    }
+
+    // --- status state machine ---
+
+    #[test]
+    fn test_draw_credit_rejected_when_suspended() {
+        let env = Env::default();
+        let (client, borrower) = setup_interest_line(&env, 0);
+        client.suspend_credit_line(&borrower);
+        assert_eq!(
+            client.try_draw_credit(&borrower, &100_i128),
+            Err(Ok(CreditError::CreditLineSuspended))
+        );
+    }
+
+    #[test]
+    fn test_draw_credit_rejected_when_defaulted() {
+        let env = Env::default();
+        let (client, borrower) = setup_interest_line(&env, 0);
+        client.default_credit_line(&borrower);
+        assert_eq!(
+            client.try_draw_credit(&borrower, &100_i128),
+            Err(Ok(CreditError::CreditLineDefaulted))
+        );
+    }
+
+    #[test]
+    fn test_repay_allowed_when_suspended_or_defaulted() {
+        let env = Env::default();
+        let (client, borrower) = setup_interest_line(&env, 0);
+        client.draw_credit(&borrower, &500_i128);
+
+        client.suspend_credit_line(&borrower);
+        client.repay_credit(&borrower, &100_i128);
+        client.default_credit_line(&borrower);
+        client.repay_credit(&borrower, &100_i128);
+
+        assert_eq!(
+            client.get_credit_line(&borrower).unwrap().utilized_amount,
+            300
+        );
+    }
+
+    #[test]
+    fn test_update_risk_parameters_rejected_when_defaulted() {
+        let env = Env::default();
+        let (client, borrower) = setup_interest_line(&env, 0);
+        let admin = client.get_admin();
+        client.default_credit_line(&borrower);
+        assert_eq!(
+            client.try_update_risk_parameters(&admin, &borrower, &5_000_i128, &300_u32, &70_u32),
+            Err(Ok(CreditError::CreditLineDefaulted))
+        );
+    }
+
+    #[test]
+    fn test_illegal_status_transitions_rejected() {
+        let env = Env::default();
+        let (client, borrower) = setup_interest_line(&env, 0);
+
+        client.suspend_credit_line(&borrower);
+        assert_eq!(
+            client.try_suspend_credit_line(&borrower),
+            Err(Ok(CreditError::InvalidStatusTransition))
+        );
+
+        client.default_credit_line(&borrower);
+        assert_eq!(
+            client.try_suspend_credit_line(&borrower),
+            Err(Ok(CreditError::InvalidStatusTransition))
+        );
+
+        let admin = client.get_admin();
+        client.close_credit_line(&borrower, &admin);
+        assert_eq!(
+            client.try_suspend_credit_line(&borrower),
+            Err(Ok(CreditError::InvalidStatusTransition))
+        );
+        assert_eq!(
+            client.try_default_credit_line(&borrower),
+            Err(Ok(CreditError::InvalidStatusTransition))
+        );
+        assert_eq!(
+            client.try_accrue_interest(&borrower),
+            Err(Ok(CreditError::CreditLineClosed))
+        );
+    }
+
+    #[test]
+    fn test_open_credit_line_rejected_over_suspended_or_defaulted_line() {
+        let env = Env::default();
+        let (client, borrower) = setup_interest_line(&env, 0);
+        let admin = client.get_admin();
+
+        client.suspend_credit_line(&borrower);
+        assert_eq!(
+            client.try_open_credit_line(&admin, &borrower, &1_000_i128, &300_u32, &70_u32),
+            Err(Ok(CreditError::CreditLineSuspended))
+        );
+
+        client.default_credit_line(&borrower);
+        assert_eq!(
+            client.try_open_credit_line(&admin, &borrower, &1_000_i128, &300_u32, &70_u32),
+            Err(Ok(CreditError::CreditLineDefaulted))
+        );
+
+        client.close_credit_line(&borrower, &admin);
+        client.open_credit_line(&admin, &borrower, &1_000_i128, &300_u32, &70_u32);
+        assert_eq!(
+            client.get_credit_line(&borrower).unwrap().status,
+            CreditStatus::Active
+        );
+    }
+
+    #[test]
+    fn test_status_tables() {
+        use status::{allows, can_transition};
+
+        assert!(!can_transition(
+            CreditStatus::Defaulted,
+            CreditStatus::Active
+        ));
+        assert!(!can_transition(
+            CreditStatus::Closed,
+            CreditStatus::Suspended
+        ));
+        assert!(!can_transition(CreditStatus::Closed, CreditStatus::Active));
+        assert!(can_transition(
+            CreditStatus::Suspended,
+            CreditStatus::Defaulted
+        ));
+
+        assert!(allows(CreditStatus::Active, Operation::Draw));
+        assert!(!allows(CreditStatus::Suspended, Operation::Draw));
+        assert!(!allows(CreditStatus::Defaulted, Operation::Draw));
+        assert!(!allows(CreditStatus::Closed, Operation::Repay));
+        assert!(allows(CreditStatus::Closed, Operation::Reopen));
+        assert!(!allows(CreditStatus::Active, Operation::Reopen));
+        assert!(allows(CreditStatus::Closed, Operation::WithdrawCollateral));
+        assert!(!allows(
+            CreditStatus::Defaulted,
+            Operation::WithdrawCollateral
+        ));
+        assert!(allows(CreditStatus::Defaulted, Operation::WriteOff));
+        assert!(!allows(CreditStatus::Suspended, Operation::WriteOff));
+    }
+
+    // --- reinstate ---
+
+    #[test]
+    fn test_reinstate_credit_line_restores_active_and_keeps_balances() {
+        let env = Env::default();
+        let (client, borrower) = setup_interest_line(&env, 1_000);
+        client.draw_credit(&borrower, &1_000_i128);
+        client.suspend_credit_line(&borrower);
+
+        advance_ledger(&env, interest::SECONDS_PER_YEAR);
+        client.reinstate_credit_line(&borrower, &None);
+
+        let event = env.events().all().last().unwrap();
+        let payload = CreditLineEvent::try_from_val(&env, &event.2).unwrap();
+        assert_eq!(payload.event_type, symbol_short!("reinstate"));
+        assert_eq!(payload.status, CreditStatus::Active);
+
+        let line = client.get_credit_line(&borrower).unwrap();
+        assert_eq!(line.status, CreditStatus::Active);
+        assert_eq!(line.utilized_amount, 1_000);
+        assert_eq!(line.accrued_interest, 100);
+        assert_eq!(line.credit_limit, 10_000);
+
+        client.draw_credit(&borrower, &500_i128);
+    }
+
+    #[test]
+    fn test_reinstate_credit_line_applies_new_risk_parameters() {
+        let env = Env::default();
+        let (client, borrower) = setup_interest_line(&env, 1_000);
+        client.draw_credit(&borrower, &1_000_i128);
+        client.suspend_credit_line(&borrower);
+
+        let params = RiskParameters {
+            credit_limit: 2_000,
+            interest_rate_bps: 2_000,
+            risk_score: 90,
+        };
+        client.reinstate_credit_line(&borrower, &Some(params));
+
+        let line = client.get_credit_line(&borrower).unwrap();
+        assert_eq!(line.credit_limit, 2_000);
+        assert_eq!(line.interest_rate_bps, 2_000);
+        assert_eq!(line.risk_score, 90);
+    }
+
+    #[test]
+    fn test_reinstate_credit_line_rejects_invalid_risk_parameters() {
+        let env = Env::default();
+        let (client, borrower) = setup_interest_line(&env, 0);
+        client.draw_credit(&borrower, &1_000_i128);
+        client.suspend_credit_line(&borrower);
+
+        let params = RiskParameters {
+            credit_limit: 500,
+            interest_rate_bps: 300,
+            risk_score: 70,
+        };
+        assert_eq!(
+            client.try_reinstate_credit_line(&borrower, &Some(params)),
+            Err(Ok(CreditError::CreditLimitBelowUtilized))
+        );
+        assert_eq!(
+            client.get_credit_line(&borrower).unwrap().status,
+            CreditStatus::Suspended
+        );
+    }
+
+    #[test]
+    fn test_reinstate_credit_line_rejects_non_suspended_lines() {
+        let env = Env::default();
+        let (client, borrower) = setup_interest_line(&env, 0);
+        assert_eq!(
+            client.try_reinstate_credit_line(&borrower, &None),
+            Err(Ok(CreditError::InvalidStatusTransition))
+        );
+
+        client.default_credit_line(&borrower);
+        assert_eq!(
+            client.try_reinstate_credit_line(&borrower, &None),
+            Err(Ok(CreditError::InvalidStatusTransition))
+        );
+
+        client.close_credit_line(&borrower, &client.get_admin());
+        assert_eq!(
+            client.try_reinstate_credit_line(&borrower, &None),
+            Err(Ok(CreditError::InvalidStatusTransition))
+        );
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_reinstate_credit_line_requires_admin_auth() {
+        let env = Env::default();
+        let (client, borrower) = setup_interest_line(&env, 0);
+        client.suspend_credit_line(&borrower);
+
+        env.set_auths(&[]);
+        client.reinstate_credit_line(&borrower, &None);
+    }
+
+    // --- emergency pause ---
+
+    #[test]
+    fn test_pause_blocks_open_and_draw() {
+        let env = Env::default();
+        let (client, borrower) = setup_interest_line(&env, 0);
+        let admin = client.get_admin();
+
+        client.pause(&admin);
+        assert!(client.is_paused());
+        assert_eq!(
+            client.try_draw_credit(&borrower, &100_i128),
+            Err(Ok(CreditError::ContractPaused))
+        );
+        assert_eq!(
+            client.try_open_credit_line(
+                &admin,
+                &Address::generate(&env),
+                &1_000_i128,
+                &300_u32,
+                &70_u32
+            ),
+            Err(Ok(CreditError::ContractPaused))
+        );
+
+        client.unpause(&admin);
+        assert!(!client.is_paused());
+        client.draw_credit(&borrower, &100_i128);
+    }
+
+    #[test]
+    fn test_pause_allows_repay_and_close() {
+        let env = Env::default();
+        let (client, borrower) = setup_interest_line(&env, 0);
+        client.draw_credit(&borrower, &100_i128);
+        client.pause(&client.get_admin());
+
+        client.repay_credit(&borrower, &100_i128);
+        client.close_credit_line(&borrower, &borrower);
+        assert_eq!(
+            client.get_credit_line(&borrower).unwrap().status,
+            CreditStatus::Closed
+        );
+    }
+
+    #[test]
+    fn test_guardian_can_pause_and_unpause_and_events_emitted() {
+        let env = Env::default();
+        let (client, _borrower) = setup_interest_line(&env, 0);
+        let guardian = Address::generate(&env);
+        client.grant_role(&Role::Guardian, &guardian);
+
+        client.pause(&guardian);
+        let event = env.events().all().last().unwrap();
+        assert_eq!(
+            event.1,
+            (symbol_short!("credit"), symbol_short!("paused")).into_val(&env)
+        );
+        let payload = PauseChangedEvent::try_from_val(&env, &event.2).unwrap();
+        assert!(payload.paused);
+        assert_eq!(payload.caller, guardian);
+
+        client.unpause(&guardian);
+        let event = env.events().all().last().unwrap();
+        assert_eq!(
+            event.1,
+            (symbol_short!("credit"), symbol_short!("unpaused")).into_val(&env)
+        );
+        assert!(!client.is_paused());
+    }
+
+    #[test]
+    fn test_pause_is_idempotent() {
+        let env = Env::default();
+        let (client, _borrower) = setup_interest_line(&env, 0);
+        let admin = client.get_admin();
+        client.pause(&admin);
+        client.pause(&admin);
+        assert_eq!(env.events().all().len(), 0);
+        assert!(client.is_paused());
+    }
+
+    #[test]
+    fn test_pause_rejects_non_guardian() {
+        let env = Env::default();
+        let (client, _borrower) = setup_interest_line(&env, 0);
+        let outsider = Address::generate(&env);
+        assert_eq!(
+            client.try_pause(&outsider),
+            Err(Ok(CreditError::Unauthorized))
+        );
+
+        let engine = Address::generate(&env);
+        client.grant_role(&Role::RiskEngine, &engine);
+        assert_eq!(
+            client.try_pause(&engine),
+            Err(Ok(CreditError::Unauthorized))
+        );
+        assert!(!client.is_paused());
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_pause_requires_caller_auth() {
+        let env = Env::default();
+        let (client, _borrower) = setup_interest_line(&env, 0);
+        let admin = client.get_admin();
+        env.set_auths(&[]);
+        client.pause(&admin);
+    }
+
+    // --- upgrade and migration ---
+
+    fn store_legacy_line(env: &Env, contract_id: &Address, borrower: &Address, utilized: i128) {
+        let legacy = migration::CreditLineDataV1 {
+            borrower: borrower.clone(),
+            credit_limit: 1_000,
+            utilized_amount: utilized,
+            interest_rate_bps: 1_000,
+            risk_score: 40,
+            status: CreditStatus::Active,
+        };
+        env.as_contract(contract_id, || {
+            env.storage().persistent().set(borrower, &legacy);
+        });
+    }
+
+    fn is_current_layout(env: &Env, contract_id: &Address, borrower: &Address) -> bool {
+        env.as_contract(contract_id, || {
+            let stored = migration::read_credit_line(env, borrower).unwrap();
+            !stored.legacy_key && !stored.legacy_layout
+        })
+    }
+
+    #[test]
+    fn test_schema_version_set_on_init() {
+        let env = Env::default();
+        let (client, _borrower) = setup_interest_line(&env, 0);
+        assert_eq!(client.get_schema_version(), migration::SCHEMA_VERSION);
+    }
+
+    #[test]
+    fn test_schema_version_defaults_to_legacy_for_untracked_deployments() {
+        let env = Env::default();
+        let contract_id = env.register(Credit, ());
+        let client = CreditClient::new(&env, &contract_id);
+        assert_eq!(
+            client.get_schema_version(),
+            migration::LEGACY_SCHEMA_VERSION
+        );
+    }
+
+    #[test]
+    fn test_legacy_credit_line_is_readable_and_usable_before_migration() {
+        let env = Env::default();
+        let (client, _borrower) = setup_interest_line(&env, 0);
+        let contract_id = client.address.clone();
+        let legacy_borrower = Address::generate(&env);
+        env.ledger().with_mut(|li| li.timestamp = 1_000);
+        store_legacy_line(&env, &contract_id, &legacy_borrower, 200);
+
+        let line = client.get_credit_line(&legacy_borrower).unwrap();
+        assert_eq!(line.utilized_amount, 200);
+        assert_eq!(line.risk_score, 40);
+        assert_eq!(line.accrued_interest, 0);
+        assert_eq!(line.accrued_fees, 0);
+        assert_eq!(line.last_accrual_ts, 1_000);
+
+        // Any write rewrites the record in the current layout.
+        client.draw_credit(&legacy_borrower, &100_i128);
+        assert!(is_current_layout(&env, &contract_id, &legacy_borrower));
+        assert_eq!(
+            client
+                .get_credit_line(&legacy_borrower)
+                .unwrap()
+                .utilized_amount,
+            300
+        );
+    }
+
+    #[test]
+    fn test_migrate_rewrites_legacy_lines_in_batches() {
+        let env = Env::default();
+        let (client, borrower) = setup_interest_line(&env, 0);
+        let contract_id = client.address.clone();
+        let first = Address::generate(&env);
+        let second = Address::generate(&env);
+        store_legacy_line(&env, &contract_id, &first, 100);
+        store_legacy_line(&env, &contract_id, &second, 0);
+
+        let missing = Address::generate(&env);
+        let batch = Vec::from_array(&env, [first.clone(), borrower.clone(), missing]);
+        assert_eq!(client.migrate(&batch), 1);
+        assert!(is_current_layout(&env, &contract_id, &first));
+        assert!(!is_current_layout(&env, &contract_id, &second));
+
+        // Re-running an overlapping batch only touches what is still legacy.
+        let batch = Vec::from_array(&env, [first.clone(), second.clone()]);
+        assert_eq!(client.migrate(&batch), 1);
+        assert!(is_current_layout(&env, &contract_id, &second));
+        assert_eq!(client.get_credit_line(&first).unwrap().utilized_amount, 100);
+
+        client.complete_migration();
+        assert_eq!(client.get_schema_version(), migration::SCHEMA_VERSION);
+    }
+
+    #[test]
+    fn test_migrate_rejects_oversized_batch() {
+        let env = Env::default();
+        let (client, _borrower) = setup_interest_line(&env, 0);
+        let mut batch = Vec::new(&env);
+        for _ in 0..=migration::MAX_MIGRATION_BATCH {
+            batch.push_back(Address::generate(&env));
+        }
+        assert_eq!(
+            client.try_migrate(&batch),
+            Err(Ok(CreditError::MigrationBatchTooLarge))
+        );
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_migrate_requires_admin_auth() {
+        let env = Env::default();
+        let (client, borrower) = setup_interest_line(&env, 0);
+        env.set_auths(&[]);
+        client.migrate(&Vec::from_array(&env, [borrower]));
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_upgrade_requires_admin_auth() {
+        let env = Env::default();
+        let (client, _borrower) = setup_interest_line(&env, 0);
+        env.set_auths(&[]);
+        client.upgrade(&BytesN::from_array(&env, &[0; 32]));
+    }
+
+    // --- storage TTL ---
+
+    fn line_ttl(env: &Env, client: &CreditClient, borrower: &Address) -> u32 {
+        use soroban_sdk::testutils::storage::Persistent as _;
+        env.as_contract(&client.address, || {
+            env.storage()
+                .persistent()
+                .get_ttl(&DataKey::CreditLine(borrower.clone()))
+        })
+    }
+
+    fn instance_ttl(env: &Env, client: &CreditClient) -> u32 {
+        use soroban_sdk::testutils::storage::Instance as _;
+        env.as_contract(&client.address, || env.storage().instance().get_ttl())
+    }
+
+    fn advance_ledgers(env: &Env, ledgers: u32) {
+        env.ledger().with_mut(|li| li.sequence_number += ledgers);
+    }
+
+    #[test]
+    fn test_open_credit_line_extends_ttls_to_configured_targets() {
+        let env = Env::default();
+        let (client, borrower) = setup_interest_line(&env, 0);
+        let config = client.get_ttl_config();
+        assert_eq!(config, ttl::DEFAULT_TTL_CONFIG);
+        assert_eq!(
+            line_ttl(&env, &client, &borrower),
+            config.persistent_extend_to
+        );
+        assert_eq!(instance_ttl(&env, &client), config.instance_extend_to);
+    }
+
+    #[test]
+    fn test_bump_credit_line_keeps_idle_line_alive() {
+        let env = Env::default();
+        let (client, borrower) = setup_interest_line(&env, 0);
+        client.draw_credit(&borrower, &100_i128);
+        let config = client.get_ttl_config();
+
+        // Step past the default persistent target without ever touching the line
+        // except through the keeper entrypoint.
+        let step = config.instance_extend_to - config.instance_threshold + 1;
+        let mut elapsed = 0;
+        while elapsed <= config.persistent_extend_to {
+            advance_ledgers(&env, step);
+            elapsed += step;
+            client.bump_credit_line(&borrower);
+            assert!(line_ttl(&env, &client, &borrower) >= config.persistent_threshold);
+        }
+        assert_eq!(
+            client.get_credit_line(&borrower).unwrap().utilized_amount,
+            100
+        );
+    }
+
+    #[test]
+    fn test_reads_extend_line_ttl_below_threshold() {
+        let env = Env::default();
+        let (client, borrower) = setup_interest_line(&env, 0);
+        let config = client.get_ttl_config();
+
+        advance_ledgers(
+            &env,
+            config.instance_extend_to - config.instance_threshold + 1,
+        );
+        client.get_outstanding(&borrower);
+        assert_eq!(
+            instance_ttl(&env, &client),
+            config.instance_extend_to,
+            "instance is extended once below its threshold"
+        );
+        // The line is still above its threshold, so it is left alone.
+        assert_eq!(
+            line_ttl(&env, &client, &borrower),
+            config.persistent_extend_to
+                - (config.instance_extend_to - config.instance_threshold + 1)
+        );
+    }
+
+    #[test]
+    fn test_set_ttl_config_applies_to_later_writes() {
+        let env = Env::default();
+        let (client, borrower) = setup_interest_line(&env, 0);
+        let config = TtlConfig {
+            instance_threshold: 10 * 17_280,
+            instance_extend_to: 60 * 17_280,
+            persistent_threshold: 200 * 17_280,
+            persistent_extend_to: 365 * 17_280,
+        };
+        client.set_ttl_config(&config);
+        assert_eq!(client.get_ttl_config(), config);
+
+        client.bump_credit_line(&borrower);
+        assert_eq!(
+            line_ttl(&env, &client, &borrower),
+            config.persistent_extend_to
+        );
+    }
+
+    #[test]
+    fn test_set_ttl_config_rejects_invalid_config() {
+        let env = Env::default();
+        let (client, _borrower) = setup_interest_line(&env, 0);
+        let mut config = ttl::DEFAULT_TTL_CONFIG;
+        config.persistent_threshold = config.persistent_extend_to + 1;
+        assert_eq!(
+            client.try_set_ttl_config(&config),
+            Err(Ok(CreditError::InvalidTtlConfig))
+        );
+
+        let mut config = ttl::DEFAULT_TTL_CONFIG;
+        config.instance_extend_to = u32::MAX;
+        assert_eq!(
+            client.try_set_ttl_config(&config),
+            Err(Ok(CreditError::InvalidTtlConfig))
+        );
+    }
+
+    #[test]
+    fn test_bump_credit_line_extends_term_loan() {
+        use soroban_sdk::testutils::storage::Persistent as _;
+        let env = Env::default();
+        let (client, admin, borrower, _token) = setup_term_loans(&env, 0);
+        client.originate_term_loan(&admin, &borrower, &6_000_i128, &600_u32, &6_u32, &MONTH);
+        let config = client.get_ttl_config();
+        let loan_ttl = || {
+            env.as_contract(&client.address, || {
+                env.storage()
+                    .persistent()
+                    .get_ttl(&DataKey::TermLoan(borrower.clone()))
+            })
+        };
+
+        // Step past the persistent target; the borrower has no credit line, only the loan.
+        let step = config.instance_extend_to - config.instance_threshold + 1;
+        let mut elapsed = 0;
+        while elapsed <= config.persistent_extend_to {
+            advance_ledgers(&env, step);
+            elapsed += step;
+            client.bump_credit_line(&borrower);
+            assert!(loan_ttl() >= config.persistent_threshold);
+        }
+        assert_eq!(
+            client
+                .get_term_loan(&borrower)
+                .unwrap()
+                .outstanding_principal,
+            6_000
+        );
+    }
+
+    #[test]
+    fn test_bump_credit_line_nonexistent() {
+        let env = Env::default();
+        let (client, _borrower) = setup_interest_line(&env, 0);
+        assert_eq!(
+            client.try_bump_credit_line(&Address::generate(&env)),
+            Err(Ok(CreditError::CreditLineNotFound))
+        );
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_set_ttl_config_requires_admin_auth() {
+        let env = Env::default();
+        let (client, _borrower) = setup_interest_line(&env, 0);
+        env.set_auths(&[]);
+        client.set_ttl_config(&ttl::DEFAULT_TTL_CONFIG);
+    }
+
+    // --- namespaced storage keys ---
+
+    #[test]
+    fn test_credit_line_stored_under_namespaced_key() {
+        let env = Env::default();
+        let (client, borrower) = setup_interest_line(&env, 0);
+        env.as_contract(&client.address, || {
+            let persistent = env.storage().persistent();
+            assert!(persistent.has(&DataKey::CreditLine(borrower.clone())));
+            assert!(!persistent.has(&borrower));
+            assert!(env.storage().instance().has(&DataKey::Admin));
+        });
+    }
+
+    #[test]
+    fn test_line_under_legacy_address_key_is_read_and_moved_on_write() {
+        let env = Env::default();
+        let (client, _borrower) = setup_interest_line(&env, 0);
+        let legacy_borrower = Address::generate(&env);
+        let line = CreditLineData {
+            borrower: legacy_borrower.clone(),
+            asset: None,
+            credit_limit: 1_000,
+            utilized_amount: 250,
+            interest_rate_bps: 0,
+            risk_score: 55,
+            status: CreditStatus::Active,
+            accrued_interest: 0,
+            accrued_fees: 0,
+            last_accrual_ts: 0,
+        };
+        env.as_contract(&client.address, || {
+            env.storage().persistent().set(&legacy_borrower, &line);
+        });
+
+        let read = client.get_credit_line(&legacy_borrower).unwrap();
+        assert_eq!(read.utilized_amount, 250);
+        assert_eq!(read.risk_score, 55);
+        assert_eq!(client.get_outstanding(&legacy_borrower), 250);
+
+        client.repay_credit(&legacy_borrower, &50_i128);
+        env.as_contract(&client.address, || {
+            let persistent = env.storage().persistent();
+            assert!(!persistent.has(&legacy_borrower));
+            assert!(persistent.has(&DataKey::CreditLine(legacy_borrower.clone())));
+        });
+        assert_eq!(
+            client
+                .get_credit_line(&legacy_borrower)
+                .unwrap()
+                .utilized_amount,
+            200
+        );
+    }
+
+    #[test]
+    fn test_migrate_moves_legacy_keys() {
+        let env = Env::default();
+        let (client, _borrower) = setup_interest_line(&env, 0);
+        let contract_id = client.address.clone();
+        let legacy_borrower = Address::generate(&env);
+        store_legacy_line(&env, &contract_id, &legacy_borrower, 300);
+
+        assert_eq!(
+            client.migrate(&Vec::from_array(&env, [legacy_borrower.clone()])),
+            1
+        );
+        env.as_contract(&contract_id, || {
+            let persistent = env.storage().persistent();
+            assert!(!persistent.has(&legacy_borrower));
+            assert!(persistent.has(&DataKey::CreditLine(legacy_borrower.clone())));
+        });
+        assert_eq!(
+            client
+                .get_credit_line(&legacy_borrower)
+                .unwrap()
+                .utilized_amount,
+            300
+        );
+    }
+
+    #[test]
+    fn test_legacy_admin_key_is_honoured_and_migrated() {
+        let env = Env::default();
+        env.mock_all_auths();
+        let contract_id = env.register(Credit, ());
+        let client = CreditClient::new(&env, &contract_id);
+        let admin = Address::generate(&env);
+        env.as_contract(&contract_id, || {
+            let instance = env.storage().instance();
+            instance.set(&Symbol::new(&env, "admin"), &admin);
+            instance.set(&Symbol::new(&env, "reentrancy"), &false);
+        });
 
-        publish_credit_line_event(
-            &env,
-            (symbol_short!("credit"), symbol_short!("default")),
-            CreditLineEvent {
-                event_type: symbol_short!("default"),
-                borrower: borrower.clone(),
-                status: CreditStatus::Defaulted,
-                credit_limit: credit_line.credit_limit,
-                interest_rate_bps: credit_line.interest_rate_bps,
-                risk_score: credit_line.risk_score,
-            },
+        assert_eq!(client.get_admin(), admin);
+        assert_eq!(
+            client.try_init(&Address::generate(&env)),
+            Err(Ok(CreditError::AlreadyInitialized))
         );
-    }
 
-    /// Read-only getter for credit line by borrower
-    ///
-    /// @param borrower The address to query
-    /// @return Option<CreditLineData> Full data or None if no line exists
-    /// Get credit line data for a borrower (view function).
-    pub fn get_credit_line(env: Env, borrower: Address) -> Option<CreditLineData> {
-        env.storage().persistent().get(&borrower)
+        client.migrate(&Vec::new(&env));
+        env.as_contract(&contract_id, || {
+            let instance = env.storage().instance();
+            assert_eq!(
+                instance.get::<_, Address>(&DataKey::Admin),
+                Some(admin.clone())
+            );
+            assert!(!instance.has(&Symbol::new(&env, "admin")));
+            assert!(!instance.has(&Symbol::new(&env, "reentrancy")));
+        });
+        assert_eq!(client.get_admin(), admin);
     }
-}
 
-#[cfg(test)]
-mod test {
-    use super::*;
-    use soroban_sdk::testutils::Address as _;
-    use soroban_sdk::testutils::Events;
-    use soroban_sdk::token::StellarAssetClient;
+    // --- borrower registry ---
 
-    fn setup_test(env: &Env) -> (Address, Address, Address) {
+    fn setup_registry(env: &Env, borrowers: u32) -> (CreditClient<'_>, Address, Vec<Address>) {
         env.mock_all_auths();
-
         let admin = Address::generate(env);
-        let borrower = Address::generate(env);
-
-        let contract_id = env.register(Credit, ());
-        let client = CreditClient::new(env, &contract_id);
-
+        let client = CreditClient::new(env, &env.register(Credit, ()));
         client.init(&admin);
-        client.open_credit_line(&borrower, &1000_i128, &300_u32, &70_u32);
+        client.grant_role(&Role::RiskEngine, &admin);
+        let mut opened = Vec::new(env);
+        for _ in 0..borrowers {
+            let borrower = Address::generate(env);
+            client.open_credit_line(&admin, &borrower, &1_000_i128, &300_u32, &70_u32);
+            opened.push_back(borrower);
+        }
+        (client, admin, opened)
+    }
 
-        (admin, borrower, contract_id)
+    fn borrowers_of(env: &Env, lines: &Vec<CreditLineData>) -> Vec<Address> {
+        let mut borrowers = Vec::new(env);
+        for line in lines.iter() {
+            borrowers.push_back(line.borrower);
+        }
+        borrowers
     }
 
-    fn call_contract<F>(env: &Env, contract_id: &Address, f: F)
-    where
-        F: FnOnce(),
-    {
-        env.as_contract(contract_id, f);
+    /// Whether `a` and `b` hold the same borrowers, in any order.
+    fn same_borrowers(a: &Vec<Address>, b: &Vec<Address>) -> bool {
+        a.len() == b.len() && a.iter().all(|borrower| b.contains(&borrower))
     }
 
-    fn get_credit_data(env: &Env, contract_id: &Address, borrower: &Address) -> CreditLineData {
-        let client = CreditClient::new(env, contract_id);
-        client
-            .get_credit_line(borrower)
-            .expect("Credit line not found")
+    #[test]
+    fn test_list_credit_lines_paginates_in_registration_order() {
+        let env = Env::default();
+        let (client, _admin, opened) = setup_registry(&env, 5);
+        assert_eq!(client.get_borrower_count(), 5);
+
+        let first = client.list_credit_lines(&0, &2);
+        let second = client.list_credit_lines(&2, &2);
+        let third = client.list_credit_lines(&4, &2);
+        assert_eq!(borrowers_of(&env, &first), opened.slice(0..2));
+        assert_eq!(borrowers_of(&env, &second), opened.slice(2..4));
+        assert_eq!(borrowers_of(&env, &third), opened.slice(4..5));
+        assert_eq!(first.get(0).unwrap().credit_limit, 1_000);
+        assert_eq!(client.list_credit_lines(&5, &2).len(), 0);
+        assert_eq!(client.list_credit_lines(&u32::MAX, &u32::MAX).len(), 0);
     }
 
     #[test]
-    fn test_init_and_open_credit_line() {
+    fn test_list_credit_lines_caps_page_size() {
         let env = Env::default();
-        env.mock_all_auths();
+        let (client, _admin, _opened) = setup_registry(&env, registry::MAX_PAGE_SIZE + 5);
+        assert_eq!(
+            client.list_credit_lines(&0, &u32::MAX).len(),
+            registry::MAX_PAGE_SIZE
+        );
+        // A full page reads an index entry and a line per borrower, within the network's
+        // limit of 40 ledger entries per transaction.
+        let resources = env.cost_estimate().resources();
+        assert!(resources.read_entries + resources.write_entries <= 40);
 
-        let admin = Address::generate(&env);
-        let borrower = Address::generate(&env);
+        assert_eq!(
+            client
+                .list_by_status(&CreditStatus::Active, &0, &u32::MAX)
+                .len(),
+            registry::MAX_PAGE_SIZE
+        );
+        let resources = env.cost_estimate().resources();
+        assert!(resources.read_entries + resources.write_entries <= 40);
+    }
 
-        let contract_id = env.register(Credit, ());
-        let client = CreditClient::new(&env, &contract_id);
+    #[test]
+    fn test_status_counts_and_lists_follow_transitions() {
+        let env = Env::default();
+        let (client, admin, opened) = setup_registry(&env, 4);
+        let (a, b, c, d) = (
+            opened.get(0).unwrap(),
+            opened.get(1).unwrap(),
+            opened.get(2).unwrap(),
+            opened.get(3).unwrap(),
+        );
 
-        client.init(&admin);
-        client.open_credit_line(&borrower, &1000_i128, &300_u32, &70_u32);
+        client.suspend_credit_line(&a);
+        client.default_credit_line(&c);
+        client.close_credit_line(&d, &admin);
 
-        let credit_line = client.get_credit_line(&borrower);
-        assert!(credit_line.is_some());
-        let credit_line = credit_line.unwrap();
-        assert_eq!(credit_line.borrower, borrower);
-        assert_eq!(credit_line.credit_limit, 1000);
-        assert_eq!(credit_line.utilized_amount, 0);
-        assert_eq!(credit_line.interest_rate_bps, 300);
-        assert_eq!(credit_line.risk_score, 70);
-        assert_eq!(credit_line.status, CreditStatus::Active);
+        assert_eq!(client.get_status_count(&CreditStatus::Active), 1);
+        assert_eq!(client.get_status_count(&CreditStatus::Suspended), 1);
+        assert_eq!(client.get_status_count(&CreditStatus::Defaulted), 1);
+        assert_eq!(client.get_status_count(&CreditStatus::Closed), 1);
+        assert_eq!(client.get_borrower_count(), 4);
+
+        let active = client.list_by_status(&CreditStatus::Active, &0, &10);
+        assert_eq!(
+            borrowers_of(&env, &active),
+            Vec::from_array(&env, [b.clone()])
+        );
+        let suspended = client.list_by_status(&CreditStatus::Suspended, &0, &10);
+        assert_eq!(
+            borrowers_of(&env, &suspended),
+            Vec::from_array(&env, [a.clone()])
+        );
+
+        client.reinstate_credit_line(&a, &None);
+        let active = client.list_by_status(&CreditStatus::Active, &0, &10);
+        assert!(same_borrowers(
+            &borrowers_of(&env, &active),
+            &Vec::from_array(&env, [a, b])
+        ));
+        assert_eq!(client.get_status_count(&CreditStatus::Suspended), 0);
+
+        // Reopening a closed line moves it back without registering it twice.
+        client.open_credit_line(&admin, &d, &500_i128, &300_u32, &70_u32);
+        assert_eq!(client.get_status_count(&CreditStatus::Closed), 0);
+        assert_eq!(client.get_status_count(&CreditStatus::Active), 3);
+        assert_eq!(client.get_borrower_count(), 4);
     }
 
     #[test]
-    fn test_suspend_credit_line() {
+    fn test_list_by_status_cursor_pages_through_status() {
         let env = Env::default();
-        env.mock_all_auths();
+        let (client, _admin, opened) = setup_registry(&env, 5);
+        let suspended = opened.slice(0..3);
+        for borrower in suspended.iter() {
+            client.suspend_credit_line(&borrower);
+        }
 
-        let admin = Address::generate(&env);
-        let borrower = Address::generate(&env);
+        let mut seen = borrowers_of(
+            &env,
+            &client.list_by_status(&CreditStatus::Suspended, &0, &2),
+        );
+        seen.append(&borrowers_of(
+            &env,
+            &client.list_by_status(&CreditStatus::Suspended, &2, &2),
+        ));
+        assert!(same_borrowers(&seen, &suspended));
+    }
 
-        let contract_id = env.register(Credit, ());
-        let client = CreditClient::new(&env, &contract_id);
+    #[test]
+    fn test_migrate_registers_legacy_borrowers() {
+        let env = Env::default();
+        let (client, _admin, _opened) = setup_registry(&env, 1);
+        let legacy_borrower = Address::generate(&env);
+        store_legacy_line(&env, &client.address, &legacy_borrower, 0);
+        assert_eq!(client.get_borrower_count(), 1);
+
+        let batch = Vec::from_array(&env, [legacy_borrower.clone()]);
+        client.migrate(&batch);
+        client.migrate(&batch);
+        assert_eq!(client.get_borrower_count(), 2);
+        assert_eq!(client.get_status_count(&CreditStatus::Active), 2);
+        assert_eq!(borrowers_of(&env, &client.list_credit_lines(&1, &1)), batch);
+    }
 
-        client.init(&admin);
-        client.open_credit_line(&borrower, &1000_i128, &300_u32, &70_u32);
-        client.suspend_credit_line(&borrower);
+    // --- portfolio stats ---
+
+    /// Recompute the portfolio from every stored line and compare with the running totals.
+    fn assert_portfolio_invariant(client: &CreditClient) {
+        let stats = client.get_portfolio_stats();
+        let mut expected = PortfolioStats {
+            total_credit_limit: 0,
+            total_utilized: 0,
+            total_accrued_interest: 0,
+            total_accrued_fees: 0,
+            // Term loans are not listed with the lines.
+            total_term_principal: stats.total_term_principal,
+            active_count: 0,
+            suspended_count: 0,
+            defaulted_count: 0,
+            closed_count: 0,
+        };
+        let mut start = 0;
+        loop {
+            let page = client.list_credit_lines(&start, &registry::MAX_PAGE_SIZE);
+            if page.is_empty() {
+                break;
+            }
+            start += page.len();
+            for line in page.iter() {
+                expected.total_utilized += line.utilized_amount;
+                expected.total_accrued_interest += line.accrued_interest;
+                expected.total_accrued_fees += line.accrued_fees;
+                match line.status {
+                    CreditStatus::Active => expected.active_count += 1,
+                    CreditStatus::Suspended => expected.suspended_count += 1,
+                    CreditStatus::Defaulted => expected.defaulted_count += 1,
+                    CreditStatus::Closed => expected.closed_count += 1,
+                }
+                if line.status != CreditStatus::Closed {
+                    expected.total_credit_limit += line.credit_limit;
+                }
+            }
+        }
+        assert_eq!(stats, expected);
+    }
 
-        let credit_line = client.get_credit_line(&borrower).unwrap();
-        assert_eq!(credit_line.status, CreditStatus::Suspended);
+    #[test]
+    fn test_portfolio_stats_track_lifecycle() {
+        let env = Env::default();
+        let (client, admin, opened) = setup_registry(&env, 3);
+        let (a, b, c) = (
+            opened.get(0).unwrap(),
+            opened.get(1).unwrap(),
+            opened.get(2).unwrap(),
+        );
+        let stats = client.get_portfolio_stats();
+        assert_eq!(stats.total_credit_limit, 3_000);
+        assert_eq!(stats.active_count, 3);
+        assert_portfolio_invariant(&client);
+
+        client.draw_credit(&a, &400_i128);
+        client.draw_credit(&b, &1_000_i128);
+        assert_eq!(client.get_portfolio_stats().total_utilized, 1_400);
+        assert_portfolio_invariant(&client);
+
+        advance_ledger(&env, interest::SECONDS_PER_YEAR);
+        client.accrue_interest(&a);
+        assert_eq!(client.get_portfolio_stats().total_accrued_interest, 12);
+        assert_portfolio_invariant(&client);
+
+        client.repay_credit(&a, &112_i128);
+        client.update_risk_parameters(&admin, &b, &2_500_i128, &500_u32, &60_u32);
+        assert_portfolio_invariant(&client);
+
+        client.suspend_credit_line(&b);
+        client.default_credit_line(&b);
+        client.close_credit_line(&c, &c);
+        let stats = client.get_portfolio_stats();
+        assert_eq!(stats.total_credit_limit, 3_500);
+        assert_eq!(stats.defaulted_count, 1);
+        assert_eq!(stats.closed_count, 1);
+        assert_portfolio_invariant(&client);
+
+        // A force-closed line drops its limit but keeps its balance.
+        client.close_credit_line(&b, &admin);
+        let stats = client.get_portfolio_stats();
+        assert_eq!(stats.total_credit_limit, 1_000);
+        assert!(stats.total_utilized >= 1_000);
+        assert_portfolio_invariant(&client);
+
+        client.open_credit_line(&admin, &c, &700_i128, &300_u32, &70_u32);
+        assert_eq!(client.get_portfolio_stats().total_credit_limit, 1_700);
+        assert_portfolio_invariant(&client);
     }
 
     #[test]
-    fn test_close_credit_line() {
+    fn test_portfolio_stats_count_migrated_legacy_lines() {
         let env = Env::default();
-        env.mock_all_auths();
+        let (client, _admin, _opened) = setup_registry(&env, 1);
+        let legacy_borrower = Address::generate(&env);
+        store_legacy_line(&env, &client.address, &legacy_borrower, 250);
+
+        client.migrate(&Vec::from_array(&env, [legacy_borrower]));
+        let stats = client.get_portfolio_stats();
+        assert_eq!(stats.total_credit_limit, 2_000);
+        assert_eq!(stats.total_utilized, 250);
+        assert_portfolio_invariant(&client);
+    }
 
-        let admin = Address::generate(&env);
-        let borrower = Address::generate(&env);
+    #[test]
+    fn test_portfolio_stats_invariant_under_mixed_operations() {
+        let env = Env::default();
+        env.cost_estimate().budget().reset_unlimited();
+        let (client, admin, opened) = setup_registry(&env, 6);
+
+        // Deterministic pseudo-random walk over every state-changing entrypoint; failed
+        // calls are rolled back and must leave the totals untouched as well.
+        let mut seed: u64 = 0x2545_f491_4f6c_dd1d;
+        for _ in 0..120 {
+            seed ^= seed << 13;
+            seed ^= seed >> 7;
+            seed ^= seed << 17;
+            let borrower = opened.get((seed % opened.len() as u64) as u32).unwrap();
+            let amount = ((seed >> 8) % 600) as i128 + 1;
+            let _ = match (seed >> 24) % 9 {
+                0 | 1 => client.try_draw_credit(&borrower, &amount).map(|_| ()),
+                2 | 3 => client.try_repay_credit(&borrower, &amount).map(|_| ()),
+                4 => client
+                    .try_update_risk_parameters(
+                        &admin,
+                        &borrower,
+                        &(amount * 3),
+                        &((seed >> 32) as u32 % 2_000),
+                        &50_u32,
+                    )
+                    .map(|_| ()),
+                5 => client.try_suspend_credit_line(&borrower).map(|_| ()),
+                6 => client
+                    .try_reinstate_credit_line(&borrower, &None)
+                    .map(|_| ()),
+                7 => client.try_default_credit_line(&borrower).map(|_| ()),
+                _ => match client.try_close_credit_line(&borrower, &admin) {
+                    Ok(_) => client
+                        .try_open_credit_line(&admin, &borrower, &1_000_i128, &800_u32, &70_u32)
+                        .map(|_| ()),
+                    Err(e) => Err(e),
+                },
+            };
+            advance_ledger(&env, 86_400 * ((seed >> 40) % 30));
+            assert_portfolio_invariant(&client);
+        }
+    }
 
-        let contract_id = env.register(Credit, ());
-        let client = CreditClient::new(&env, &contract_id);
+    // --- exposure caps ---
 
-        client.init(&admin);
-        client.open_credit_line(&borrower, &1000_i128, &300_u32, &70_u32);
-        client.close_credit_line(&borrower, &admin);
+    fn caps(reserve_bps: u32, borrower_limit: i128, committed_limit: i128) -> ExposureCaps {
+        ExposureCaps {
+            max_outstanding_reserve_bps: reserve_bps,
+            max_borrower_limit: borrower_limit,
+            max_total_committed_limit: committed_limit,
+        }
+    }
 
-        let credit_line = client.get_credit_line(&borrower).unwrap();
-        assert_eq!(credit_line.status, CreditStatus::Closed);
+    #[test]
+    fn test_exposure_caps_disabled_by_default() {
+        let env = Env::default();
+        let (client, _admin, _opened) = setup_registry(&env, 0);
+        assert_eq!(client.get_exposure_caps(), caps(0, 0, 0));
     }
 
     #[test]
-    fn test_default_credit_line() {
+    fn test_open_credit_line_rejected_above_borrower_cap() {
         let env = Env::default();
-        env.mock_all_auths();
+        let (client, admin, _opened) = setup_registry(&env, 0);
+        client.set_exposure_caps(&caps(0, 1_000, 0));
 
-        let admin = Address::generate(&env);
         let borrower = Address::generate(&env);
+        assert_eq!(
+            client.try_open_credit_line(&admin, &borrower, &1_001_i128, &300_u32, &70_u32),
+            Err(Ok(CreditError::BorrowerLimitCapExceeded))
+        );
+        client.open_credit_line(&admin, &borrower, &1_000_i128, &300_u32, &70_u32);
+    }
 
-        let contract_id = env.register(Credit, ());
-        let client = CreditClient::new(&env, &contract_id);
-
-        client.init(&admin);
-        client.open_credit_line(&borrower, &1000_i128, &300_u32, &70_u32);
-        client.default_credit_line(&borrower);
+    #[test]
+    fn test_open_credit_line_rejected_above_committed_cap() {
+        let env = Env::default();
+        let (client, admin, opened) = setup_registry(&env, 2);
+        client.set_exposure_caps(&caps(0, 0, 2_500));
 
-        let credit_line = client.get_credit_line(&borrower).unwrap();
-        assert_eq!(credit_line.status, CreditStatus::Defaulted);
+        let borrower = Address::generate(&env);
+        assert_eq!(
+            client.try_open_credit_line(&admin, &borrower, &600_i128, &300_u32, &70_u32),
+            Err(Ok(CreditError::CommittedLimitCapExceeded))
+        );
+        client.open_credit_line(&admin, &borrower, &500_i128, &300_u32, &70_u32);
+
+        // Closing a line frees its committed limit.
+        client.close_credit_line(&opened.get(0).unwrap(), &admin);
+        client.open_credit_line(
+            &admin,
+            &Address::generate(&env),
+            &1_000_i128,
+            &300_u32,
+            &70_u32,
+        );
     }
 
-    // ========== open_credit_line: duplicate borrower and invalid params (#28) ==========
-
-    /// open_credit_line must revert when the borrower already has an Active credit line.
     #[test]
-    #[should_panic(expected = "borrower already has an active credit line")]
-    fn test_open_credit_line_duplicate_active_borrower_reverts() {
+    fn test_limit_increase_checked_against_caps_but_decrease_allowed() {
         let env = Env::default();
-        env.mock_all_auths();
+        let (client, admin, opened) = setup_registry(&env, 2);
+        let borrower = opened.get(0).unwrap();
+        client.set_exposure_caps(&caps(0, 1_500, 2_200));
 
-        let admin = Address::generate(&env);
-        let borrower = Address::generate(&env);
+        assert_eq!(
+            client.try_update_risk_parameters(&admin, &borrower, &1_600_i128, &300_u32, &70_u32),
+            Err(Ok(CreditError::BorrowerLimitCapExceeded))
+        );
+        assert_eq!(
+            client.try_update_risk_parameters(&admin, &borrower, &1_300_i128, &300_u32, &70_u32),
+            Err(Ok(CreditError::CommittedLimitCapExceeded))
+        );
+        client.update_risk_parameters(&admin, &borrower, &1_200_i128, &300_u32, &70_u32);
 
-        let contract_id = env.register(Credit, ());
-        let client = CreditClient::new(&env, &contract_id);
+        // Tighter caps never block a decrease.
+        client.set_exposure_caps(&caps(0, 100, 100));
+        client.update_risk_parameters(&admin, &borrower, &900_i128, &300_u32, &70_u32);
 
-        client.init(&admin);
-        client.open_credit_line(&borrower, &1000_i128, &300_u32, &70_u32);
-        // Second open for same borrower while Active must revert.
-        client.open_credit_line(&borrower, &2000_i128, &400_u32, &60_u32);
+        client.suspend_credit_line(&borrower);
+        let params = RiskParameters {
+            credit_limit: 1_000,
+            interest_rate_bps: 300,
+            risk_score: 70,
+        };
+        assert_eq!(
+            client.try_reinstate_credit_line(&borrower, &Some(params)),
+            Err(Ok(CreditError::BorrowerLimitCapExceeded))
+        );
+        client.reinstate_credit_line(&borrower, &None);
     }
 
-    /// open_credit_line must revert when credit_limit is zero.
     #[test]
-    #[should_panic(expected = "credit_limit must be greater than zero")]
-    fn test_open_credit_line_zero_limit_reverts() {
+    fn test_draw_credit_rejected_above_reserve_cap() {
         let env = Env::default();
-        env.mock_all_auths();
-
-        let admin = Address::generate(&env);
-        let borrower = Address::generate(&env);
+        let (client, borrower, _token) = setup_token_line(&env, 1_000);
+        // Outstanding may reach half of the reserve balance seen at draw time.
+        client.set_exposure_caps(&caps(5_000, 0, 0));
 
-        let contract_id = env.register(Credit, ());
-        let client = CreditClient::new(&env, &contract_id);
+        client.draw_credit(&borrower, &300_i128);
+        // Reserve is now 700: a further 100 would make outstanding 400 > 350.
+        assert_eq!(
+            client.try_draw_credit(&borrower, &100_i128),
+            Err(Ok(CreditError::OutstandingCapExceeded))
+        );
+        client.draw_credit(&borrower, &50_i128);
+        assert_eq!(
+            client.get_credit_line(&borrower).unwrap().utilized_amount,
+            350
+        );
+    }
 
-        client.init(&admin);
-        client.open_credit_line(&borrower, &0_i128, &300_u32, &70_u32);
+    #[test]
+    fn test_set_exposure_caps_rejects_negative_caps() {
+        let env = Env::default();
+        let (client, _admin, _opened) = setup_registry(&env, 0);
+        assert_eq!(
+            client.try_set_exposure_caps(&caps(0, -1, 0)),
+            Err(Ok(CreditError::InvalidExposureCaps))
+        );
+        assert_eq!(
+            client.try_set_exposure_caps(&caps(0, 0, -1)),
+            Err(Ok(CreditError::InvalidExposureCaps))
+        );
     }
 
-    /// open_credit_line must revert when credit_limit is negative.
     #[test]
-    #[should_panic(expected = "credit_limit must be greater than zero")]
-    fn test_open_credit_line_negative_limit_reverts() {
+    #[should_panic]
+    fn test_set_exposure_caps_requires_admin_auth() {
         let env = Env::default();
-        env.mock_all_auths();
+        let (client, _admin, _opened) = setup_registry(&env, 0);
+        env.set_auths(&[]);
+        client.set_exposure_caps(&caps(0, 1, 1));
+    }
 
-        let admin = Address::generate(&env);
-        let borrower = Address::generate(&env);
+    // --- multi-asset lines ---
 
-        let contract_id = env.register(Credit, ());
-        let client = CreditClient::new(&env, &contract_id);
+    struct AssetSetup<'a> {
+        client: CreditClient<'a>,
+        admin: Address,
+        usdc: Address,
+        eurc: Address,
+        usdc_source: Address,
+        eurc_source: Address,
+    }
 
-        client.init(&admin);
-        client.open_credit_line(&borrower, &-1_i128, &300_u32, &70_u32);
+    fn asset_config(source: &Address) -> AssetConfig {
+        AssetConfig {
+            liquidity_source: source.clone(),
+            max_borrower_limit: 0,
+            max_outstanding: 0,
+            min_interest_rate_bps: 0,
+            max_interest_rate_bps: 2_000,
+        }
+    }
+
+    fn setup_assets(env: &Env) -> AssetSetup<'_> {
+        let (client, admin, _opened) = setup_registry(env, 0);
+        // Draws move funds out of each asset's liquidity source, not the contract.
+        env.mock_all_auths_allowing_non_root_auth();
+        let usdc = env.register_stellar_asset_contract_v2(Address::generate(env));
+        let eurc = env.register_stellar_asset_contract_v2(Address::generate(env));
+        let usdc_source = Address::generate(env);
+        let eurc_source = Address::generate(env);
+        StellarAssetClient::new(env, &usdc.address()).mint(&usdc_source, &10_000);
+        StellarAssetClient::new(env, &eurc.address()).mint(&eurc_source, &10_000);
+        client.set_asset_config(&usdc.address(), &asset_config(&usdc_source));
+        client.set_asset_config(&eurc.address(), &asset_config(&eurc_source));
+        AssetSetup {
+            client,
+            admin,
+            usdc: usdc.address(),
+            eurc: eurc.address(),
+            usdc_source,
+            eurc_source,
+        }
     }
 
-    /// open_credit_line must revert when interest_rate_bps exceeds 10000 (100%).
     #[test]
-    #[should_panic(expected = "interest_rate_bps cannot exceed 10000 (100%)")]
-    fn test_open_credit_line_interest_rate_exceeds_max_reverts() {
+    fn test_asset_lines_route_draws_and_repayments_to_their_asset() {
         let env = Env::default();
-        env.mock_all_auths();
-
-        let admin = Address::generate(&env);
-        let borrower = Address::generate(&env);
+        let s = setup_assets(&env);
+        let alice = Address::generate(&env);
+        let bob = Address::generate(&env);
+        s.client
+            .open_asset_credit_line(&s.admin, &alice, &s.usdc, &1_000_i128, &500_u32, &70_u32);
+        s.client
+            .open_asset_credit_line(&s.admin, &bob, &s.eurc, &1_000_i128, &500_u32, &70_u32);
+        assert_eq!(
+            s.client.get_credit_line(&alice).unwrap().asset,
+            Some(s.usdc.clone())
+        );
 
-        let contract_id = env.register(Credit, ());
-        let client = CreditClient::new(&env, &contract_id);
+        s.client.draw_credit(&alice, &300_i128);
+        s.client.draw_credit(&bob, &200_i128);
+        let usdc = token::Client::new(&env, &s.usdc);
+        let eurc = token::Client::new(&env, &s.eurc);
+        assert_eq!(usdc.balance(&alice), 300);
+        assert_eq!(usdc.balance(&s.usdc_source), 9_700);
+        assert_eq!(eurc.balance(&bob), 200);
+        assert_eq!(eurc.balance(&s.eurc_source), 9_800);
+
+        s.client.repay_credit(&alice, &100_i128);
+        assert_eq!(usdc.balance(&s.usdc_source), 9_800);
+        assert_eq!(eurc.balance(&s.eurc_source), 9_800);
+    }
 
-        client.init(&admin);
-        client.open_credit_line(&borrower, &1000_i128, &10_001_u32, &70_u32);
+    #[test]
+    fn test_open_asset_credit_line_requires_configured_asset() {
+        let env = Env::default();
+        let s = setup_assets(&env);
+        let unknown = Address::generate(&env);
+        assert_eq!(
+            s.client.try_open_asset_credit_line(
+                &s.admin,
+                &Address::generate(&env),
+                &unknown,
+                &1_000_i128,
+                &300_u32,
+                &70_u32
+            ),
+            Err(Ok(CreditError::AssetNotConfigured))
+        );
     }
 
-    /// open_credit_line must revert when risk_score exceeds 100.
     #[test]
-    #[should_panic(expected = "risk_score must be between 0 and 100")]
-    fn test_open_credit_line_risk_score_exceeds_max_reverts() {
+    fn test_asset_rate_band_and_limit_cap_enforced() {
         let env = Env::default();
-        env.mock_all_auths();
+        let s = setup_assets(&env);
+        let mut config = asset_config(&s.usdc_source);
+        config.min_interest_rate_bps = 200;
+        config.max_interest_rate_bps = 800;
+        config.max_borrower_limit = 5_000;
+        s.client.set_asset_config(&s.usdc, &config);
 
-        let admin = Address::generate(&env);
         let borrower = Address::generate(&env);
+        let open = |limit: i128, rate: u32| {
+            s.client
+                .try_open_asset_credit_line(&s.admin, &borrower, &s.usdc, &limit, &rate, &70_u32)
+        };
+        assert_eq!(open(1_000, 100), Err(Ok(CreditError::InvalidInterestRate)));
+        assert_eq!(open(1_000, 900), Err(Ok(CreditError::InvalidInterestRate)));
+        assert_eq!(
+            open(5_001, 500),
+            Err(Ok(CreditError::BorrowerLimitCapExceeded))
+        );
+        assert_eq!(open(5_000, 500), Ok(Ok(())));
 
-        let contract_id = env.register(Credit, ());
-        let client = CreditClient::new(&env, &contract_id);
-
-        client.init(&admin);
-        client.open_credit_line(&borrower, &1000_i128, &300_u32, &101_u32);
+        assert_eq!(
+            s.client.try_update_risk_parameters(
+                &s.admin,
+                &borrower,
+                &5_000_i128,
+                &900_u32,
+                &70_u32
+            ),
+            Err(Ok(CreditError::InvalidInterestRate))
+        );
+        // Lines on the global token are not bound by asset bands.
+        let other = Address::generate(&env);
+        s.client
+            .open_credit_line(&s.admin, &other, &9_000_i128, &5_000_u32, &70_u32);
     }
 
-    // ========== draw_credit within limit (#29) ==========
-
     #[test]
-    fn test_draw_credit() {
+    fn test_asset_outstanding_cap_enforced_per_asset() {
         let env = Env::default();
-        let (_admin, borrower, contract_id) = setup_test(&env);
+        let s = setup_assets(&env);
+        let mut config = asset_config(&s.usdc_source);
+        config.max_outstanding = 500;
+        s.client.set_asset_config(&s.usdc, &config);
+
+        let alice = Address::generate(&env);
+        let bob = Address::generate(&env);
+        let carol = Address::generate(&env);
+        s.client
+            .open_asset_credit_line(&s.admin, &alice, &s.usdc, &1_000_i128, &0_u32, &70_u32);
+        s.client
+            .open_asset_credit_line(&s.admin, &bob, &s.usdc, &1_000_i128, &0_u32, &70_u32);
+        s.client
+            .open_asset_credit_line(&s.admin, &carol, &s.eurc, &1_000_i128, &0_u32, &70_u32);
+
+        s.client.draw_credit(&alice, &300_i128);
+        assert_eq!(
+            s.client.try_draw_credit(&bob, &201_i128),
+            Err(Ok(CreditError::OutstandingCapExceeded))
+        );
+        s.client.draw_credit(&bob, &200_i128);
+        // EURC exposure is tracked separately.
+        s.client.draw_credit(&carol, &900_i128);
 
-        call_contract(&env, &contract_id, || {
-            Credit::draw_credit(env.clone(), borrower.clone(), 500_i128);
+        s.client.repay_credit(&alice, &100_i128);
+        s.client.draw_credit(&bob, &100_i128);
+    }
+
+    #[test]
+    fn test_reserve_cap_uses_outstanding_in_drawn_asset() {
+        let env = Env::default();
+        let s = setup_assets(&env);
+        let alice = Address::generate(&env);
+        let bob = Address::generate(&env);
+        s.client
+            .open_asset_credit_line(&s.admin, &alice, &s.usdc, &9_000_i128, &0_u32, &70_u32);
+        s.client
+            .open_asset_credit_line(&s.admin, &bob, &s.eurc, &9_000_i128, &0_u32, &70_u32);
+        s.client.set_exposure_caps(&ExposureCaps {
+            max_outstanding_reserve_bps: 5_000,
+            max_borrower_limit: 0,
+            max_total_committed_limit: 0,
         });
 
-        let credit_data = get_credit_data(&env, &contract_id, &borrower);
-        assert_eq!(credit_data.utilized_amount, 500_i128);
+        s.client.draw_credit(&alice, &3_000_i128);
+        // USDC outstanding does not count against the EURC reserve.
+        s.client.draw_credit(&bob, &3_000_i128);
+        assert_eq!(
+            s.client.try_draw_credit(&alice, &1_000_i128),
+            Err(Ok(CreditError::OutstandingCapExceeded))
+        );
+    }
 
-        // Events are emitted - functionality verified through storage changes
+    #[test]
+    fn test_set_asset_config_rejects_invalid_config() {
+        let env = Env::default();
+        let s = setup_assets(&env);
+        let mut config = asset_config(&s.usdc_source);
+        config.min_interest_rate_bps = 900;
+        config.max_interest_rate_bps = 800;
+        assert_eq!(
+            s.client.try_set_asset_config(&s.usdc, &config),
+            Err(Ok(CreditError::InvalidAssetConfig))
+        );
+        let mut config = asset_config(&s.usdc_source);
+        config.max_interest_rate_bps = MAX_INTEREST_RATE_BPS + 1;
+        assert_eq!(
+            s.client.try_set_asset_config(&s.usdc, &config),
+            Err(Ok(CreditError::InvalidAssetConfig))
+        );
+        let mut config = asset_config(&s.usdc_source);
+        config.max_outstanding = -1;
+        assert_eq!(
+            s.client.try_set_asset_config(&s.usdc, &config),
+            Err(Ok(CreditError::InvalidAssetConfig))
+        );
+        assert_eq!(
+            s.client.get_asset_config(&s.eurc),
+            Some(asset_config(&s.eurc_source))
+        );
     }
 
-    /// draw_credit within limit: single draw updates utilized_amount correctly.
     #[test]
-    fn test_draw_credit_single_within_limit_succeeds_and_updates_utilized() {
+    fn test_version_2_line_without_asset_is_read_and_migrated() {
         let env = Env::default();
-        env.mock_all_auths();
-
-        let admin = Address::generate(&env);
+        let (client, _admin, _opened) = setup_registry(&env, 0);
         let borrower = Address::generate(&env);
+        let v2 = migration::CreditLineDataV2 {
+            borrower: borrower.clone(),
+            credit_limit: 1_000,
+            utilized_amount: 400,
+            interest_rate_bps: 300,
+            risk_score: 60,
+            status: CreditStatus::Active,
+            accrued_interest: 7,
+            accrued_fees: 2,
+            last_accrual_ts: 0,
+        };
+        env.as_contract(&client.address, || {
+            env.storage()
+                .persistent()
+                .set(&DataKey::CreditLine(borrower.clone()), &v2);
+        });
 
-        let contract_id = env.register(Credit, ());
-        let client = CreditClient::new(&env, &contract_id);
-
-        client.init(&admin);
-        client.open_credit_line(&borrower, &1000_i128, &300_u32, &70_u32);
+        let line = client.get_credit_line(&borrower).unwrap();
+        assert_eq!(line.asset, None);
+        assert_eq!(line.accrued_interest, 7);
+        assert_eq!(line.accrued_fees, 2);
 
-        let line_before = client.get_credit_line(&borrower).unwrap();
-        assert_eq!(line_before.utilized_amount, 0);
+        assert_eq!(
+            client.migrate(&Vec::from_array(&env, [borrower.clone()])),
+            1
+        );
+        assert!(is_current_layout(&env, &client.address, &borrower));
+        assert_portfolio_invariant(&client);
+    }
 
-        client.draw_credit(&borrower, &400_i128);
+    // --- fees ---
 
-        let line_after = client.get_credit_line(&borrower).unwrap();
-        assert_eq!(line_after.utilized_amount, 400);
-        assert_eq!(line_after.credit_limit, 1000);
+    fn fee_config(collector: &Address) -> FeeConfig {
+        FeeConfig {
+            fee_collector: collector.clone(),
+            origination_fee_bps: 100,
+            draw_fee_bps: 50,
+            late_fee: 25,
+        }
     }
 
-    /// draw_credit within limit: multiple draws accumulate utilized_amount correctly.
     #[test]
-    fn test_draw_credit_multiple_draws_within_limit_accumulate_utilized() {
+    fn test_origination_fee_charged_on_open() {
         let env = Env::default();
-        env.mock_all_auths();
+        let (client, admin, _opened) = setup_registry(&env, 0);
+        client.set_fee_config(&fee_config(&Address::generate(&env)));
 
-        let admin = Address::generate(&env);
         let borrower = Address::generate(&env);
+        client.open_credit_line(&admin, &borrower, &5_000_i128, &300_u32, &70_u32);
 
-        let contract_id = env.register(Credit, ());
-        let client = CreditClient::new(&env, &contract_id);
-
-        client.init(&admin);
-        client.open_credit_line(&borrower, &1000_i128, &300_u32, &70_u32);
-
-        client.draw_credit(&borrower, &100_i128);
+        let event = env.events().all().last().unwrap();
         assert_eq!(
-            client.get_credit_line(&borrower).unwrap().utilized_amount,
-            100
+            event.1,
+            (symbol_short!("credit"), symbol_short!("orig_fee")).into_val(&env)
         );
+        let payload = FeeChargedEvent::try_from_val(&env, &event.2).unwrap();
+        assert_eq!(payload.fee, 50);
+        assert_eq!(payload.accrued_fees, 50);
+        assert_eq!(client.get_credit_line(&borrower).unwrap().accrued_fees, 50);
+        assert_eq!(client.get_portfolio_stats().total_accrued_fees, 50);
+    }
 
-        client.draw_credit(&borrower, &250_i128);
-        assert_eq!(
-            client.get_credit_line(&borrower).unwrap().utilized_amount,
-            350
-        );
+    #[test]
+    fn test_draw_fee_added_to_fee_balance_and_reported() {
+        let env = Env::default();
+        let (client, borrower, token_address) = setup_token_line(&env, 1_000);
+        client.set_fee_config(&fee_config(&Address::generate(&env)));
 
-        client.draw_credit(&borrower, &150_i128);
+        client.draw_credit(&borrower, &400_i128);
+
+        let event = env.events().all().last().unwrap();
+        let payload = DrawnEvent::try_from_val(&env, &event.2).unwrap();
+        assert_eq!(payload.amount, 400);
+        assert_eq!(payload.fee, 2);
+        assert_eq!(payload.accrued_fees, 2);
+        // The borrower receives the full draw; the fee is owed on the line.
         assert_eq!(
-            client.get_credit_line(&borrower).unwrap().utilized_amount,
-            500
+            token::Client::new(&env, &token_address).balance(&borrower),
+            400
         );
+        let line = client.get_credit_line(&borrower).unwrap();
+        assert_eq!(line.utilized_amount, 400);
+        assert_eq!(line.accrued_fees, 2);
+        assert_portfolio_invariant(&client);
     }
 
-    /// draw_credit within limit: drawing exact available limit succeeds and utilized equals limit.
     #[test]
-    fn test_draw_credit_exact_available_limit_succeeds() {
+    fn test_draw_fee_does_not_count_against_credit_limit() {
         let env = Env::default();
-        env.mock_all_auths();
-
-        let admin = Address::generate(&env);
-        let borrower = Address::generate(&env);
-
-        let contract_id = env.register(Credit, ());
-        let client = CreditClient::new(&env, &contract_id);
-
-        client.init(&admin);
-        let limit = 5000_i128;
-        client.open_credit_line(&borrower, &limit, &300_u32, &70_u32);
-
-        client.draw_credit(&borrower, &limit);
+        let (client, borrower, _token_address) = setup_token_line(&env, 1_000);
+        client.set_fee_config(&fee_config(&Address::generate(&env)));
 
+        client.draw_credit(&borrower, &1_000_i128);
         let line = client.get_credit_line(&borrower).unwrap();
-        assert_eq!(line.utilized_amount, limit);
-        assert_eq!(line.credit_limit, limit);
+        assert_eq!(line.utilized_amount, 1_000);
+        assert_eq!(line.accrued_fees, 5);
     }
 
     #[test]
-    fn test_repay_credit_partial() {
+    fn test_repay_sends_fee_share_to_fee_collector() {
         let env = Env::default();
-        let (_admin, borrower, contract_id) = setup_test(&env);
-
-        // First draw some credit
-        call_contract(&env, &contract_id, || {
-            Credit::draw_credit(env.clone(), borrower.clone(), 500_i128);
-        });
-        assert_eq!(
-            get_credit_data(&env, &contract_id, &borrower).utilized_amount,
-            500_i128
-        );
+        let (client, borrower, token_address) = setup_token_line(&env, 1_000);
+        let collector = Address::generate(&env);
+        client.set_fee_config(&fee_config(&collector));
+        let token_client = token::Client::new(&env, &token_address);
 
-        // Partial repayment
-        call_contract(&env, &contract_id, || {
-            Credit::repay_credit(env.clone(), borrower.clone(), 200_i128);
-        });
+        client.draw_credit(&borrower, &400_i128);
+        client.repay_credit(&borrower, &100_i128);
 
-        let credit_data = get_credit_data(&env, &contract_id, &borrower);
-        assert_eq!(credit_data.utilized_amount, 300_i128); // 500 - 200
+        let event = env.events().all().last().unwrap();
+        let payload = RepaymentEvent::try_from_val(&env, &event.2).unwrap();
+        assert_eq!(payload.fees_paid, 2);
+        assert_eq!(payload.principal_paid, 98);
+        assert_eq!(payload.accrued_fees, 0);
+        assert_eq!(token_client.balance(&collector), 2);
+        assert_eq!(token_client.balance(&client.address), 698);
     }
 
     #[test]
-    fn test_repay_credit_full() {
+    fn test_assess_late_fee_charges_missed_minimum_once() {
         let env = Env::default();
-        let (_admin, borrower, contract_id) = setup_test(&env);
-
-        // Draw some credit
-        call_contract(&env, &contract_id, || {
-            Credit::draw_credit(env.clone(), borrower.clone(), 500_i128);
+        let (client, admin, borrower, _start) = setup_billing(&env);
+        client.set_fee_config(&FeeConfig {
+            origination_fee_bps: 0,
+            draw_fee_bps: 0,
+            ..fee_config(&Address::generate(&env))
         });
+
+        // Nothing is charged before the first statement falls due.
+        client.assess_late_fee(&admin, &borrower);
+        advance_ledger(&env, BILLING_PERIOD + 500);
+        client.assess_late_fee(&admin, &borrower);
+        assert_eq!(client.get_credit_line(&borrower).unwrap().accrued_fees, 0);
+
+        advance_ledger(&env, 1);
+        client.assess_late_fee(&admin, &borrower);
+        let event = env.events().all().last().unwrap();
         assert_eq!(
-            get_credit_data(&env, &contract_id, &borrower).utilized_amount,
-            500_i128
+            event.1,
+            (symbol_short!("credit"), symbol_short!("late_fee")).into_val(&env)
         );
+        client.assess_late_fee(&admin, &borrower);
+        assert_eq!(client.get_credit_line(&borrower).unwrap().accrued_fees, 25);
+        assert!(client.get_statement(&borrower).unwrap().late_fee_charged);
+        assert_portfolio_invariant(&client);
+    }
 
-        // Full repayment
-        call_contract(&env, &contract_id, || {
-            Credit::repay_credit(env.clone(), borrower.clone(), 500_i128);
-        });
+    #[test]
+    fn test_assess_late_fee_skips_paid_minimum() {
+        let env = Env::default();
+        let (client, admin, borrower, _start) = setup_billing(&env);
+        client.set_fee_config(&fee_config(&Address::generate(&env)));
+        let fees_before = client.get_credit_line(&borrower).unwrap().accrued_fees;
+        advance_ledger(&env, BILLING_PERIOD);
+        let minimum = client.get_statement(&borrower).unwrap().minimum_due;
+        client.repay_credit(&borrower, &(fees_before + minimum));
+
+        advance_ledger(&env, 600);
+        client.assess_late_fee(&admin, &borrower);
+        assert_eq!(client.get_credit_line(&borrower).unwrap().accrued_fees, 0);
+    }
 
-        let credit_data = get_credit_data(&env, &contract_id, &borrower);
-        assert_eq!(credit_data.utilized_amount, 0_i128); // Fully repaid
+    #[test]
+    fn test_assess_late_fee_without_schedule_charges_nothing() {
+        let env = Env::default();
+        let (client, admin, opened) = setup_registry(&env, 1);
+        let borrower = opened.get(0).unwrap();
+
+        client.assess_late_fee(&admin, &borrower);
+        assert_eq!(client.get_credit_line(&borrower).unwrap().accrued_fees, 0);
     }
 
     #[test]
-    fn test_repay_credit_overpayment() {
+    fn test_assess_late_fee_requires_risk_engine_and_open_line() {
         let env = Env::default();
-        let (_admin, borrower, contract_id) = setup_test(&env);
+        let (client, admin, opened) = setup_registry(&env, 1);
+        let borrower = opened.get(0).unwrap();
+        client.set_fee_config(&fee_config(&Address::generate(&env)));
 
-        // Draw some credit
-        call_contract(&env, &contract_id, || {
-            Credit::draw_credit(env.clone(), borrower.clone(), 300_i128);
-        });
         assert_eq!(
-            get_credit_data(&env, &contract_id, &borrower).utilized_amount,
-            300_i128
+            client.try_assess_late_fee(&Address::generate(&env), &borrower),
+            Err(Ok(CreditError::Unauthorized))
+        );
+        client.close_credit_line(&borrower, &admin);
+        assert_eq!(
+            client.try_assess_late_fee(&admin, &borrower),
+            Err(Ok(CreditError::CreditLineClosed))
         );
-
-        // Overpayment (pay more than utilized)
-        call_contract(&env, &contract_id, || {
-            Credit::repay_credit(env.clone(), borrower.clone(), 500_i128);
-        });
-
-        let credit_data = get_credit_data(&env, &contract_id, &borrower);
-        assert_eq!(credit_data.utilized_amount, 0_i128); // Should be capped at 0
     }
 
     #[test]
-    fn test_repay_credit_zero_utilization() {
+    fn test_set_fee_config_rejects_invalid_config() {
         let env = Env::default();
-        let (_admin, borrower, contract_id) = setup_test(&env);
+        let (client, _admin, _opened) = setup_registry(&env, 0);
+        let collector = Address::generate(&env);
 
-        // Try to repay when no credit is utilized
-        call_contract(&env, &contract_id, || {
-            Credit::repay_credit(env.clone(), borrower.clone(), 100_i128);
-        });
+        let mut config = fee_config(&collector);
+        config.draw_fee_bps = 10_001;
+        assert_eq!(
+            client.try_set_fee_config(&config),
+            Err(Ok(CreditError::InvalidFeeConfig))
+        );
+        let mut config = fee_config(&collector);
+        config.late_fee = -1;
+        assert_eq!(
+            client.try_set_fee_config(&config),
+            Err(Ok(CreditError::InvalidFeeConfig))
+        );
+        assert_eq!(client.get_fee_config(), None);
 
-        let credit_data = get_credit_data(&env, &contract_id, &borrower);
-        assert_eq!(credit_data.utilized_amount, 0_i128); // Should remain 0
+        client.set_fee_config(&fee_config(&collector));
+        assert_eq!(client.get_fee_config(), Some(fee_config(&collector)));
     }
 
     #[test]
-    fn test_repay_credit_suspended_status() {
+    #[should_panic]
+    fn test_set_fee_config_requires_admin_auth() {
         let env = Env::default();
-        let (_admin, borrower, contract_id) = setup_test(&env);
+        let (client, _admin, _opened) = setup_registry(&env, 0);
+        env.set_auths(&[]);
+        client.set_fee_config(&fee_config(&Address::generate(&env)));
+    }
 
-        // Draw some credit
-        call_contract(&env, &contract_id, || {
-            Credit::draw_credit(env.clone(), borrower.clone(), 500_i128);
-        });
+    // --- billing cycles ---
 
-        // Manually set status to Suspended
-        let mut credit_data = get_credit_data(&env, &contract_id, &borrower);
-        credit_data.status = CreditStatus::Suspended;
-        env.as_contract(&contract_id, || {
-            env.storage().persistent().set(&borrower, &credit_data);
-        });
+    const BILLING_PERIOD: u64 = 1_000;
 
-        // Should be able to repay even when suspended
-        call_contract(&env, &contract_id, || {
-            Credit::repay_credit(env.clone(), borrower.clone(), 200_i128);
-        });
+    fn billing_config() -> BillingConfig {
+        BillingConfig {
+            period_secs: BILLING_PERIOD,
+            due_after_secs: 500,
+            min_payment_bps: 1_000,
+            min_payment_floor: 50,
+        }
+    }
 
-        let updated_data = get_credit_data(&env, &contract_id, &borrower);
-        assert_eq!(updated_data.utilized_amount, 300_i128);
-        assert_eq!(updated_data.status, CreditStatus::Suspended); // Status should remain Suspended
+    /// Billing enabled and one interest-free line with 1000 drawn, opened at `start`.
+    fn setup_billing(env: &Env) -> (CreditClient<'_>, Address, Address, u64) {
+        let (client, admin, _opened) = setup_registry(env, 0);
+        client.set_billing_config(&billing_config());
+        let borrower = Address::generate(env);
+        client.open_credit_line(&admin, &borrower, &10_000_i128, &0_u32, &70_u32);
+        client.draw_credit(&borrower, &1_000_i128);
+        (client, admin, borrower, env.ledger().timestamp())
     }
 
     #[test]
-    #[should_panic(expected = "amount must be positive")]
-    fn test_repay_credit_invalid_amount_zero() {
+    fn test_get_statement_none_while_billing_disabled() {
         let env = Env::default();
-        let (_admin, borrower, contract_id) = setup_test(&env);
-
-        call_contract(&env, &contract_id, || {
-            Credit::repay_credit(env.clone(), borrower.clone(), 0_i128);
-        });
+        let (client, _admin, opened) = setup_registry(&env, 1);
+        assert_eq!(client.get_statement(&opened.get(0).unwrap()), None);
+        assert_eq!(client.get_billing_config(), None);
     }
 
     #[test]
-    #[should_panic(expected = "amount must be positive")]
-    fn test_repay_credit_invalid_amount_negative() {
+    fn test_statement_open_cycle_before_period_ends() {
         let env = Env::default();
-        let (_admin, borrower, contract_id) = setup_test(&env);
-
-        let negative_amount: i128 = -100;
-        call_contract(&env, &contract_id, || {
-            Credit::repay_credit(env.clone(), borrower.clone(), negative_amount);
-        });
+        let (client, _admin, borrower, start) = setup_billing(&env);
+        advance_ledger(&env, BILLING_PERIOD - 1);
+
+        let statement = client.get_statement(&borrower).unwrap();
+        assert_eq!(statement.cycle_start, start);
+        assert_eq!(statement.closed_at, 0);
+        assert_eq!(statement.minimum_due, 0);
+        assert_eq!(statement.due_ts, 0);
     }
 
     #[test]
-    fn test_full_lifecycle() {
+    fn test_statement_closes_with_minimum_payment_and_due_date() {
         let env = Env::default();
-        env.mock_all_auths();
-
-        let admin = Address::generate(&env);
-        let borrower = Address::generate(&env);
-
-        let contract_id = env.register(Credit, ());
-        let client = CreditClient::new(&env, &contract_id);
-
-        client.init(&admin);
-
-        client.open_credit_line(&borrower, &5000_i128, &500_u32, &80_u32);
-        let credit_line = client.get_credit_line(&borrower).unwrap();
-        assert_eq!(credit_line.status, CreditStatus::Active);
+        let (client, _admin, borrower, start) = setup_billing(&env);
+        advance_ledger(&env, BILLING_PERIOD);
+
+        let expected = Statement {
+            cycle_start: start + BILLING_PERIOD,
+            closed_at: start + BILLING_PERIOD,
+            statement_balance: 1_000,
+            minimum_due: 100,
+            due_ts: start + BILLING_PERIOD + 500,
+            late_fee_charged: false,
+        };
+        // The view reports the ended cycle before anything touches the line.
+        assert_eq!(client.get_statement(&borrower), Some(expected.clone()));
 
-        client.suspend_credit_line(&borrower);
-        let credit_line = client.get_credit_line(&borrower).unwrap();
-        assert_eq!(credit_line.status, CreditStatus::Suspended);
+        client.accrue_interest(&borrower);
+        let event = env.events().all().last().unwrap();
+        assert_eq!(
+            event.1,
+            (symbol_short!("credit"), symbol_short!("statement")).into_val(&env)
+        );
+        let payload = StatementClosedEvent::try_from_val(&env, &event.2).unwrap();
+        assert_eq!(payload.minimum_due, 100);
+        assert_eq!(payload.due_ts, expected.due_ts);
+        assert_eq!(client.get_statement(&borrower), Some(expected));
+    }
 
-        client.close_credit_line(&borrower, &admin);
-        let credit_line = client.get_credit_line(&borrower).unwrap();
-        assert_eq!(credit_line.status, CreditStatus::Closed);
+    #[test]
+    fn test_minimum_payment_uses_floor_and_never_exceeds_balance() {
+        let env = Env::default();
+        let (client, _admin, borrower, _start) = setup_billing(&env);
+        client.repay_credit(&borrower, &800_i128);
+        advance_ledger(&env, BILLING_PERIOD);
+        assert_eq!(client.get_statement(&borrower).unwrap().minimum_due, 50);
+
+        client.repay_credit(&borrower, &170_i128);
+        advance_ledger(&env, BILLING_PERIOD);
+        let statement = client.get_statement(&borrower).unwrap();
+        assert_eq!(statement.statement_balance, 30);
+        assert_eq!(statement.minimum_due, 30);
     }
 
     #[test]
-    fn test_event_data_integrity() {
+    fn test_repayment_reduces_minimum_due() {
         let env = Env::default();
-        env.mock_all_auths();
+        let (client, _admin, borrower, _start) = setup_billing(&env);
+        advance_ledger(&env, BILLING_PERIOD);
 
-        let admin = Address::generate(&env);
-        let borrower = Address::generate(&env);
+        client.repay_credit(&borrower, &60_i128);
+        assert_eq!(client.get_statement(&borrower).unwrap().minimum_due, 40);
+        client.repay_credit(&borrower, &60_i128);
+        assert_eq!(client.get_statement(&borrower).unwrap().minimum_due, 0);
+    }
 
-        let contract_id = env.register(Credit, ());
-        let client = CreditClient::new(&env, &contract_id);
+    #[test]
+    fn test_missed_minimum_charges_late_fee_and_carries_over() {
+        let env = Env::default();
+        let (client, _admin, borrower, _start) = setup_billing(&env);
+        client.set_fee_config(&FeeConfig {
+            fee_collector: Address::generate(&env),
+            origination_fee_bps: 0,
+            draw_fee_bps: 0,
+            late_fee: 25,
+        });
+        advance_ledger(&env, BILLING_PERIOD);
+        client.accrue_interest(&borrower);
+        client.repay_credit(&borrower, &40_i128);
+
+        advance_ledger(&env, BILLING_PERIOD);
+        client.accrue_interest(&borrower);
+        let statement = client.get_statement(&borrower).unwrap();
+        assert_eq!(statement.statement_balance, 985);
+        // 10% of the new balance plus the 60 left unpaid from the last statement.
+        assert_eq!(statement.minimum_due, 98 + 60);
+        assert_eq!(client.get_credit_line(&borrower).unwrap().accrued_fees, 25);
+        assert_portfolio_invariant(&client);
+    }
 
-        client.init(&admin);
-        client.open_credit_line(&borrower, &2000_i128, &400_u32, &75_u32);
+    #[test]
+    fn test_minimum_due_at_cycle_boundary_is_missed() {
+        let env = Env::default();
+        let (client, _admin, borrower, start) = setup_billing(&env);
+        client.set_billing_config(&BillingConfig {
+            due_after_secs: BILLING_PERIOD,
+            ..billing_config()
+        });
+        client.set_fee_config(&FeeConfig {
+            fee_collector: Address::generate(&env),
+            origination_fee_bps: 0,
+            draw_fee_bps: 0,
+            late_fee: 25,
+        });
+        advance_ledger(&env, BILLING_PERIOD);
+        client.accrue_interest(&borrower);
+        assert_eq!(
+            client.get_statement(&borrower).unwrap().due_ts,
+            start + 2 * BILLING_PERIOD
+        );
 
-        let credit_line = client.get_credit_line(&borrower).unwrap();
-        assert_eq!(credit_line.borrower, borrower);
-        assert_eq!(credit_line.status, CreditStatus::Active);
-        assert_eq!(credit_line.credit_limit, 2000);
-        assert_eq!(credit_line.interest_rate_bps, 400);
-        assert_eq!(credit_line.risk_score, 75);
+        // Touched exactly when the unpaid minimum falls due and the next cycle ends.
+        advance_ledger(&env, BILLING_PERIOD);
+        client.accrue_interest(&borrower);
+        let statement = client.get_statement(&borrower).unwrap();
+        assert_eq!(statement.minimum_due, 102 + 100);
+        assert_eq!(client.get_credit_line(&borrower).unwrap().accrued_fees, 25);
     }
 
     #[test]
-    #[should_panic(expected = "Credit line not found")]
-    fn test_suspend_nonexistent_credit_line() {
+    fn test_statement_close_skips_late_fee_already_assessed() {
         let env = Env::default();
-        env.mock_all_auths();
-        let admin = Address::generate(&env);
-        let borrower = Address::generate(&env);
+        let (client, admin, borrower, _start) = setup_billing(&env);
+        client.set_fee_config(&FeeConfig {
+            fee_collector: Address::generate(&env),
+            origination_fee_bps: 0,
+            draw_fee_bps: 0,
+            late_fee: 25,
+        });
+        advance_ledger(&env, BILLING_PERIOD + 600);
+        client.assess_late_fee(&admin, &borrower);
+        assert_eq!(client.get_credit_line(&borrower).unwrap().accrued_fees, 25);
+
+        advance_ledger(&env, BILLING_PERIOD);
+        client.accrue_interest(&borrower);
+        let statement = client.get_statement(&borrower).unwrap();
+        assert_eq!(statement.minimum_due, 102 + 100);
+        assert!(!statement.late_fee_charged);
+        assert_eq!(client.get_credit_line(&borrower).unwrap().accrued_fees, 25);
+    }
 
-        let contract_id = env.register(Credit, ());
-        let client = CreditClient::new(&env, &contract_id);
+    #[test]
+    fn test_paid_minimum_is_not_charged_late_fee() {
+        let env = Env::default();
+        let (client, _admin, borrower, _start) = setup_billing(&env);
+        client.set_fee_config(&FeeConfig {
+            fee_collector: Address::generate(&env),
+            origination_fee_bps: 0,
+            draw_fee_bps: 0,
+            late_fee: 25,
+        });
+        advance_ledger(&env, BILLING_PERIOD);
+        client.repay_credit(&borrower, &100_i128);
 
-        client.init(&admin);
-        client.suspend_credit_line(&borrower);
+        advance_ledger(&env, BILLING_PERIOD);
+        client.accrue_interest(&borrower);
+        assert_eq!(client.get_credit_line(&borrower).unwrap().accrued_fees, 0);
+        assert_eq!(client.get_statement(&borrower).unwrap().minimum_due, 90);
     }
 
     #[test]
-    #[should_panic(expected = "Credit line not found")]
-    fn test_close_nonexistent_credit_line() {
+    fn test_untouched_cycles_close_one_statement_each() {
         let env = Env::default();
-        env.mock_all_auths();
-        let admin = Address::generate(&env);
-        let borrower = Address::generate(&env);
+        let (client, _admin, borrower, start) = setup_billing(&env);
+        client.set_fee_config(&FeeConfig {
+            fee_collector: Address::generate(&env),
+            origination_fee_bps: 0,
+            draw_fee_bps: 0,
+            late_fee: 25,
+        });
+        advance_ledger(&env, 3 * BILLING_PERIOD + 400);
+
+        client.accrue_interest(&borrower);
+        let statement = client.get_statement(&borrower).unwrap();
+        assert_eq!(statement.cycle_start, start + 3 * BILLING_PERIOD);
+        assert_eq!(statement.due_ts, start + 3 * BILLING_PERIOD + 500);
+        // The first and second statements' minimums were both missed.
+        assert_eq!(client.get_credit_line(&borrower).unwrap().accrued_fees, 50);
+    }
 
-        let contract_id = env.register(Credit, ());
-        let client = CreditClient::new(&env, &contract_id);
+    #[test]
+    fn test_line_opened_before_billing_starts_cycle_when_touched() {
+        let env = Env::default();
+        let (client, _admin, opened) = setup_registry(&env, 1);
+        let borrower = opened.get(0).unwrap();
+        client.set_billing_config(&billing_config());
+        advance_ledger(&env, 5 * BILLING_PERIOD);
+
+        client.accrue_interest(&borrower);
+        let statement = client.get_statement(&borrower).unwrap();
+        assert_eq!(statement.cycle_start, env.ledger().timestamp());
+        assert_eq!(statement.closed_at, 0);
+    }
 
-        client.init(&admin);
+    #[test]
+    fn test_closed_line_has_no_statement() {
+        let env = Env::default();
+        let (client, admin, borrower, _start) = setup_billing(&env);
+        client.repay_credit(&borrower, &1_000_i128);
         client.close_credit_line(&borrower, &admin);
+        assert_eq!(client.get_statement(&borrower), None);
     }
 
     #[test]
-    #[should_panic(expected = "Credit line not found")]
-    fn test_default_nonexistent_credit_line() {
+    fn test_set_billing_config_rejects_invalid_config() {
         let env = Env::default();
-        env.mock_all_auths();
-        let admin = Address::generate(&env);
-        let borrower = Address::generate(&env);
-
-        let contract_id = env.register(Credit, ());
-        let client = CreditClient::new(&env, &contract_id);
-
-        client.init(&admin);
-        client.default_credit_line(&borrower);
+        let (client, _admin, _opened) = setup_registry(&env, 0);
+        let invalid = [
+            BillingConfig {
+                period_secs: 0,
+                due_after_secs: 0,
+                ..billing_config()
+            },
+            BillingConfig {
+                due_after_secs: BILLING_PERIOD + 1,
+                ..billing_config()
+            },
+            BillingConfig {
+                min_payment_bps: 10_001,
+                ..billing_config()
+            },
+            BillingConfig {
+                min_payment_floor: -1,
+                ..billing_config()
+            },
+        ];
+        for config in invalid {
+            assert_eq!(
+                client.try_set_billing_config(&config),
+                Err(Ok(CreditError::InvalidBillingConfig))
+            );
+        }
+        client.set_billing_config(&billing_config());
+        assert_eq!(client.get_billing_config(), Some(billing_config()));
     }
 
     #[test]
-    fn test_multiple_borrowers() {
+    #[should_panic]
+    fn test_set_billing_config_requires_admin_auth() {
         let env = Env::default();
-        env.mock_all_auths();
-
-        let admin = Address::generate(&env);
-        let borrower1 = Address::generate(&env);
-        let borrower2 = Address::generate(&env);
+        let (client, _admin, _opened) = setup_registry(&env, 0);
+        env.set_auths(&[]);
+        client.set_billing_config(&billing_config());
+    }
 
-        let contract_id = env.register(Credit, ());
-        let client = CreditClient::new(&env, &contract_id);
+    // --- delinquency ---
 
-        client.init(&admin);
-        client.open_credit_line(&borrower1, &1000_i128, &300_u32, &70_u32);
-        client.open_credit_line(&borrower2, &2000_i128, &400_u32, &80_u32);
+    const DAY: u64 = 86_400;
 
-        let credit_line1 = client.get_credit_line(&borrower1).unwrap();
-        let credit_line2 = client.get_credit_line(&borrower2).unwrap();
+    /// Billing with 30-day cycles and payments due 20 days after each close; the line's
+    /// first minimum (100) is due on day 50.
+    fn setup_delinquency(env: &Env) -> (CreditClient<'_>, Address, Address) {
+        let (client, admin, borrower, _start) = setup_billing(env);
+        client.set_billing_config(&BillingConfig {
+            period_secs: 30 * DAY,
+            due_after_secs: 20 * DAY,
+            ..billing_config()
+        });
+        (client, admin, borrower)
+    }
 
-        assert_eq!(credit_line1.credit_limit, 1000);
-        assert_eq!(credit_line2.credit_limit, 2000);
-        assert_eq!(credit_line1.status, CreditStatus::Active);
-        assert_eq!(credit_line2.status, CreditStatus::Active);
+    fn set_time(env: &Env, timestamp: u64) {
+        env.ledger().with_mut(|li| li.timestamp = timestamp);
     }
 
     #[test]
-    fn test_lifecycle_transitions() {
+    fn test_delinquency_current_until_due_date_passes() {
         let env = Env::default();
-        env.mock_all_auths();
-
-        let admin = Address::generate(&env);
-        let borrower = Address::generate(&env);
-
-        let contract_id = env.register(Credit, ());
-        let client = CreditClient::new(&env, &contract_id);
-
-        client.init(&admin);
-
-        client.open_credit_line(&borrower, &1000_i128, &300_u32, &70_u32);
+        let (client, _admin, borrower) = setup_delinquency(&env);
+        set_time(&env, 50 * DAY);
+        let delinquency = client.process_delinquency(&borrower).unwrap();
+        assert_eq!(delinquency.bucket, DelinquencyBucket::Current);
+        assert_eq!(client.get_statement(&borrower).unwrap().due_ts, 50 * DAY);
+
+        set_time(&env, 50 * DAY + 1);
+        let delinquency = client.process_delinquency(&borrower).unwrap();
+        let event = env.events().all().last().unwrap();
         assert_eq!(
-            client.get_credit_line(&borrower).unwrap().status,
-            CreditStatus::Active
+            event.1,
+            (symbol_short!("credit"), symbol_short!("delinq")).into_val(&env)
+        );
+        let payload = DelinquencyEvent::try_from_val(&env, &event.2).unwrap();
+        assert_eq!(payload.previous, DelinquencyBucket::Current);
+        assert_eq!(payload.bucket, DelinquencyBucket::PastDue);
+        assert_eq!(
+            delinquency,
+            Delinquency {
+                bucket: DelinquencyBucket::PastDue,
+                days_past_due: 1,
+                past_due_since: 50 * DAY,
+            }
         );
+    }
 
-        client.default_credit_line(&borrower);
+    #[test]
+    fn test_delinquency_buckets_count_from_oldest_missed_due_date() {
+        let env = Env::default();
+        let (client, _admin, borrower) = setup_delinquency(&env);
+        let expected = [
+            (79, DelinquencyBucket::PastDue),
+            (80, DelinquencyBucket::Days30),
+            (110, DelinquencyBucket::Days60),
+            (140, DelinquencyBucket::Days90),
+            (400, DelinquencyBucket::Days90),
+        ];
+        for (day, bucket) in expected {
+            set_time(&env, day * DAY);
+            // The view sees ended cycles and missed minimums before any write.
+            assert_eq!(client.get_delinquency(&borrower).unwrap().bucket, bucket);
+            let delinquency = client.process_delinquency(&borrower).unwrap();
+            assert_eq!(delinquency.bucket, bucket);
+            assert_eq!(delinquency.past_due_since, 50 * DAY);
+            assert_eq!(delinquency.days_past_due, (day - 50) as u32);
+        }
         assert_eq!(
             client.get_credit_line(&borrower).unwrap().status,
-            CreditStatus::Defaulted
+            CreditStatus::Active
         );
     }
 
     #[test]
-    fn test_close_credit_line_borrower_when_utilized_zero() {
+    fn test_paying_minimum_cures_delinquency() {
         let env = Env::default();
-        env.mock_all_auths();
-
-        let admin = Address::generate(&env);
-        let borrower = Address::generate(&env);
-
-        let contract_id = env.register(Credit, ());
-        let client = CreditClient::new(&env, &contract_id);
-
-        client.init(&admin);
-        client.open_credit_line(&borrower, &1000_i128, &300_u32, &70_u32);
-        client.close_credit_line(&borrower, &borrower);
+        let (client, _admin, borrower) = setup_delinquency(&env);
+        set_time(&env, 85 * DAY);
+        client.process_delinquency(&borrower);
+
+        // The carried minimum from day 50 plus the day-60 statement's own minimum.
+        let minimum_due = client.get_statement(&borrower).unwrap().minimum_due;
+        client.repay_credit(&borrower, &minimum_due);
+        let topic = (symbol_short!("credit"), symbol_short!("delinq")).into_val(&env);
+        let event = env
+            .events()
+            .all()
+            .iter()
+            .find(|event| event.1 == topic)
+            .unwrap();
+        let payload = DelinquencyEvent::try_from_val(&env, &event.2).unwrap();
+        assert_eq!(payload.previous, DelinquencyBucket::Days30);
+        assert_eq!(payload.bucket, DelinquencyBucket::Current);
 
-        let credit_line = client.get_credit_line(&borrower).unwrap();
-        assert_eq!(credit_line.status, CreditStatus::Closed);
-        assert_eq!(credit_line.utilized_amount, 0);
+        // The day-90 statement's minimum is not due until day 110.
+        set_time(&env, 105 * DAY);
+        assert_eq!(
+            client.get_delinquency(&borrower),
+            Some(Delinquency {
+                bucket: DelinquencyBucket::Current,
+                days_past_due: 0,
+                past_due_since: 0,
+            })
+        );
     }
 
     #[test]
-    #[should_panic(expected = "cannot close: utilized amount not zero")]
-    fn test_close_credit_line_borrower_rejected_when_utilized_nonzero() {
+    fn test_partial_payment_keeps_line_past_due() {
         let env = Env::default();
-        env.mock_all_auths();
-
-        let admin = Address::generate(&env);
-        let borrower = Address::generate(&env);
-
-        let contract_id = env.register(Credit, ());
-        let client = CreditClient::new(&env, &contract_id);
-
-        client.init(&admin);
-        client.open_credit_line(&borrower, &1000_i128, &300_u32, &70_u32);
-        client.draw_credit(&borrower, &300_i128);
-
-        client.close_credit_line(&borrower, &borrower);
+        let (client, _admin, borrower) = setup_delinquency(&env);
+        set_time(&env, 55 * DAY);
+        client.repay_credit(&borrower, &99_i128);
+        let delinquency = client.get_delinquency(&borrower).unwrap();
+        assert_eq!(delinquency.bucket, DelinquencyBucket::PastDue);
+        assert_eq!(delinquency.days_past_due, 5);
     }
 
     #[test]
-    fn test_close_credit_line_admin_force_close_with_utilization() {
+    fn test_process_delinquency_defaults_after_grace_period() {
         let env = Env::default();
-        env.mock_all_auths();
-
-        let admin = Address::generate(&env);
-        let borrower = Address::generate(&env);
-
-        let contract_id = env.register(Credit, ());
-        let client = CreditClient::new(&env, &contract_id);
+        let (client, _admin, borrower) = setup_delinquency(&env);
+        client.set_default_grace_period(&(45 * DAY));
+        assert_eq!(client.get_default_grace_period(), 45 * DAY);
 
-        client.init(&admin);
-        client.open_credit_line(&borrower, &1000_i128, &300_u32, &70_u32);
-        client.draw_credit(&borrower, &300_i128);
+        set_time(&env, 95 * DAY - 1);
+        client.process_delinquency(&borrower);
         assert_eq!(
-            client.get_credit_line(&borrower).unwrap().utilized_amount,
-            300
+            client.get_credit_line(&borrower).unwrap().status,
+            CreditStatus::Active
         );
 
-        client.close_credit_line(&borrower, &admin);
+        set_time(&env, 95 * DAY);
+        client.process_delinquency(&borrower);
+        let event = env.events().all().last().unwrap();
+        assert_eq!(
+            event.1,
+            (symbol_short!("credit"), symbol_short!("default")).into_val(&env)
+        );
+        assert_eq!(
+            client.get_credit_line(&borrower).unwrap().status,
+            CreditStatus::Defaulted
+        );
+        assert_eq!(client.get_status_count(&CreditStatus::Defaulted), 1);
 
-        let credit_line = client.get_credit_line(&borrower).unwrap();
-        assert_eq!(credit_line.status, CreditStatus::Closed);
-        assert_eq!(credit_line.utilized_amount, 300);
+        // Defaulted lines keep being tracked.
+        set_time(&env, 150 * DAY);
+        let delinquency = client.process_delinquency(&borrower).unwrap();
+        assert_eq!(delinquency.bucket, DelinquencyBucket::Days90);
+        assert_portfolio_invariant(&client);
     }
 
     #[test]
-    fn test_close_credit_line_idempotent_when_already_closed() {
+    fn test_process_delinquency_without_grace_period_never_defaults() {
         let env = Env::default();
-        env.mock_all_auths();
-
-        let admin = Address::generate(&env);
-        let borrower = Address::generate(&env);
-
-        let contract_id = env.register(Credit, ());
-        let client = CreditClient::new(&env, &contract_id);
-
-        client.init(&admin);
-        client.open_credit_line(&borrower, &1000_i128, &300_u32, &70_u32);
-        client.close_credit_line(&borrower, &admin);
-        client.close_credit_line(&borrower, &admin);
-
+        let (client, _admin, borrower) = setup_delinquency(&env);
+        set_time(&env, 1_000 * DAY);
+        client.process_delinquency(&borrower);
         assert_eq!(
             client.get_credit_line(&borrower).unwrap().status,
-            CreditStatus::Closed
+            CreditStatus::Active
         );
     }
 
     #[test]
-    #[should_panic(expected = "credit line is closed")]
-    fn test_draw_credit_rejected_when_closed() {
+    fn test_process_delinquency_without_billing_or_on_closed_line() {
         let env = Env::default();
-        env.mock_all_auths();
-
-        let admin = Address::generate(&env);
-        let borrower = Address::generate(&env);
+        let (client, admin, opened) = setup_registry(&env, 1);
+        let borrower = opened.get(0).unwrap();
+        assert_eq!(client.process_delinquency(&borrower), None);
+        assert_eq!(client.get_delinquency(&borrower), None);
 
-        let contract_id = env.register(Credit, ());
-        let client = CreditClient::new(&env, &contract_id);
-
-        client.init(&admin);
-        client.open_credit_line(&borrower, &1000_i128, &300_u32, &70_u32);
         client.close_credit_line(&borrower, &admin);
-
-        client.draw_credit(&borrower, &100_i128);
+        assert_eq!(
+            client.try_process_delinquency(&borrower),
+            Err(Ok(CreditError::CreditLineClosed))
+        );
     }
 
     #[test]
-    #[should_panic(expected = "exceeds credit limit")]
-    fn test_draw_credit_rejected_when_exceeding_limit() {
+    fn test_reopened_line_starts_current() {
         let env = Env::default();
-        env.mock_all_auths();
-
-        let admin = Address::generate(&env);
-        let borrower = Address::generate(&env);
-
-        let contract_id = env.register(Credit, ());
-        let client = CreditClient::new(&env, &contract_id);
+        let (client, admin, borrower) = setup_delinquency(&env);
+        set_time(&env, 85 * DAY);
+        client.process_delinquency(&borrower);
+        let line = client.get_credit_line(&borrower).unwrap();
+        let owed = line.utilized_amount + line.accrued_interest + line.accrued_fees;
+        client.repay_credit(&borrower, &owed);
+        client.close_credit_line(&borrower, &admin);
 
-        client.init(&admin);
-        client.open_credit_line(&borrower, &100_i128, &300_u32, &70_u32);
-        client.draw_credit(&borrower, &101_i128);
+        client.open_credit_line(&admin, &borrower, &1_000_i128, &0_u32, &70_u32);
+        client.draw_credit(&borrower, &500_i128);
+        set_time(&env, 116 * DAY);
+        let delinquency = client.get_delinquency(&borrower).unwrap();
+        assert_eq!(delinquency.bucket, DelinquencyBucket::Current);
     }
 
     #[test]
-    #[should_panic(expected = "credit line is closed")]
-    fn test_repay_credit_rejected_when_closed() {
+    #[should_panic]
+    fn test_set_default_grace_period_requires_admin_auth() {
         let env = Env::default();
-        env.mock_all_auths();
+        let (client, _admin, _opened) = setup_registry(&env, 0);
+        env.set_auths(&[]);
+        client.set_default_grace_period(&DAY);
+    }
 
-        let admin = Address::generate(&env);
-        let borrower = Address::generate(&env);
+    // --- term loans ---
 
-        let contract_id = env.register(Credit, ());
-        let client = CreditClient::new(&env, &contract_id);
+    const MONTH: u64 = interest::SECONDS_PER_YEAR / 12;
 
-        client.init(&admin);
-        client.open_credit_line(&borrower, &1000_i128, &300_u32, &70_u32);
-        client.close_credit_line(&borrower, &admin);
+    /// Funded reserve, a risk engine admin and a borrower holding `wallet` tokens to cover
+    /// interest.
+    fn setup_term_loans(env: &Env, wallet: i128) -> (CreditClient<'_>, Address, Address, Address) {
+        let (client, admin, _opened) = setup_registry(env, 0);
+        let token = env.register_stellar_asset_contract_v2(Address::generate(env));
+        client.set_liquidity_token(&token.address());
+        let borrower = Address::generate(env);
+        let token_admin = StellarAssetClient::new(env, &token.address());
+        token_admin.mint(&client.address, &100_000);
+        token_admin.mint(&borrower, &wallet);
+        (client, admin, borrower, token.address())
+    }
 
-        client.repay_credit(&borrower, &100_i128);
+    #[test]
+    fn test_term_loan_installment_matches_annuity_formula() {
+        // 12% a year over 12 monthly installments: 12_000 * 0.01 / (1 - 1.01^-12) = 1066.19.
+        assert_eq!(
+            term_loan::installment_amount(12_000, 1_200, 12, MONTH),
+            Ok(1_067)
+        );
+        assert_eq!(term_loan::installment_amount(1_000, 0, 3, MONTH), Ok(334));
     }
 
     #[test]
-    #[should_panic(expected = "unauthorized")]
-    fn test_close_credit_line_unauthorized_closer() {
+    fn test_amortization_schedule_repays_principal_exactly() {
         let env = Env::default();
-        env.mock_all_auths();
-
-        let admin = Address::generate(&env);
-        let borrower = Address::generate(&env);
-        let other = Address::generate(&env);
-
-        let contract_id = env.register(Credit, ());
-        let client = CreditClient::new(&env, &contract_id);
-
-        client.init(&admin);
-        client.open_credit_line(&borrower, &1000_i128, &300_u32, &70_u32);
-        client.close_credit_line(&borrower, &other);
+        let (client, admin, borrower, _token) = setup_term_loans(&env, 0);
+        client.originate_term_loan(&admin, &borrower, &12_000_i128, &1_200_u32, &12_u32, &MONTH);
+
+        let schedule = client.get_amortization_schedule(&borrower);
+        assert_eq!(schedule.len(), 12);
+        let first = schedule.get(0).unwrap();
+        assert_eq!(first.due_ts, MONTH);
+        assert_eq!(first.interest, 120);
+        assert_eq!(first.principal, 947);
+        assert_eq!(first.payment, 1_067);
+
+        let mut principal = 0;
+        for (number, installment) in schedule.iter().enumerate() {
+            assert_eq!(installment.number, number as u32);
+            assert_eq!(installment.due_ts, (number as u64 + 1) * MONTH);
+            principal += installment.principal;
+        }
+        assert_eq!(principal, 12_000);
+        let last = schedule.get(11).unwrap();
+        assert_eq!(last.remaining_principal, 0);
+        assert!(last.payment <= 1_067);
     }
 
     #[test]
-    fn test_draw_credit_updates_utilized() {
+    fn test_originate_term_loan_disburses_from_reserve() {
         let env = Env::default();
-        env.mock_all_auths();
-
-        let admin = Address::generate(&env);
-        let borrower = Address::generate(&env);
-
-        let contract_id = env.register(Credit, ());
-        let client = CreditClient::new(&env, &contract_id);
+        let (client, admin, borrower, token_address) = setup_term_loans(&env, 0);
+        client.originate_term_loan(&admin, &borrower, &6_000_i128, &600_u32, &6_u32, &MONTH);
 
-        client.init(&admin);
-        client.open_credit_line(&borrower, &1000_i128, &300_u32, &70_u32);
+        let event = env.events().all().last().unwrap();
+        assert_eq!(
+            event.1,
+            (symbol_short!("credit"), symbol_short!("loan_orig")).into_val(&env)
+        );
+        let token_client = token::Client::new(&env, &token_address);
+        assert_eq!(token_client.balance(&borrower), 6_000);
+        assert_eq!(token_client.balance(&client.address), 94_000);
+
+        let loan = client.get_term_loan(&borrower).unwrap();
+        assert_eq!(loan.outstanding_principal, 6_000);
+        assert_eq!(loan.installments_paid, 0);
+        assert_eq!(loan.start_ts, 0);
+    }
 
-        client.draw_credit(&borrower, &200_i128);
+    #[test]
+    fn test_originate_term_loan_checks_reserve_cap() {
+        let env = Env::default();
+        let (client, admin, borrower, _token) = setup_term_loans(&env, 0);
+        client.set_exposure_caps(&ExposureCaps {
+            max_outstanding_reserve_bps: 1_000,
+            max_borrower_limit: 0,
+            max_total_committed_limit: 0,
+        });
         assert_eq!(
-            client.get_credit_line(&borrower).unwrap().utilized_amount,
-            200
+            client.try_originate_term_loan(
+                &admin,
+                &borrower,
+                &90_000_i128,
+                &600_u32,
+                &6_u32,
+                &MONTH
+            ),
+            Err(Ok(CreditError::OutstandingCapExceeded))
         );
 
-        client.draw_credit(&borrower, &300_i128);
+        // The loan's principal counts against the cap for later draws.
+        client.originate_term_loan(&admin, &borrower, &6_000_i128, &600_u32, &6_u32, &MONTH);
+        let line_borrower = Address::generate(&env);
+        client.open_credit_line(&admin, &line_borrower, &10_000_i128, &300_u32, &70_u32);
         assert_eq!(
-            client.get_credit_line(&borrower).unwrap().utilized_amount,
-            500
+            client.try_draw_credit(&line_borrower, &4_000_i128),
+            Err(Ok(CreditError::OutstandingCapExceeded))
         );
+        client.draw_credit(&line_borrower, &3_000_i128);
     }
 
-    // --- draw_credit: zero and negative amount guards ---
-
     #[test]
-    #[should_panic(expected = "amount must be positive")]
-    fn test_draw_credit_rejected_when_amount_is_zero() {
+    fn test_term_principal_tracked_in_portfolio_stats() {
         let env = Env::default();
-        env.mock_all_auths();
-
-        let admin = Address::generate(&env);
-        let borrower = Address::generate(&env);
+        let (client, admin, borrower, _token) = setup_term_loans(&env, 1_000);
+        client.originate_term_loan(&admin, &borrower, &12_000_i128, &1_200_u32, &12_u32, &MONTH);
+        let stats = client.get_portfolio_stats();
+        assert_eq!(stats.total_term_principal, 12_000);
+        assert_eq!(stats.total_utilized, 0);
 
-        let contract_id = env.register(Credit, ());
-        let client = CreditClient::new(&env, &contract_id);
+        let installment = client.pay_installment(&borrower);
+        assert_eq!(
+            client.get_portfolio_stats().total_term_principal,
+            installment.remaining_principal
+        );
+        client.payoff_term_loan(&borrower);
+        assert_eq!(client.get_portfolio_stats().total_term_principal, 0);
+    }
 
-        client.init(&admin);
-        client.open_credit_line(&borrower, &1000_i128, &300_u32, &70_u32);
+    #[test]
+    fn test_pay_installments_in_order_until_repaid() {
+        let env = Env::default();
+        let (client, admin, borrower, token_address) = setup_term_loans(&env, 1_000);
+        client.originate_term_loan(&admin, &borrower, &12_000_i128, &1_200_u32, &12_u32, &MONTH);
+        let schedule = client.get_amortization_schedule(&borrower);
+
+        let mut paid = 0;
+        for expected in schedule.iter() {
+            let installment = client.pay_installment(&borrower);
+            assert_eq!(installment, expected);
+            paid += installment.payment;
+        }
+        let loan = client.get_term_loan(&borrower).unwrap();
+        assert_eq!(loan.outstanding_principal, 0);
+        assert_eq!(loan.installments_paid, 12);
+        let token_client = token::Client::new(&env, &token_address);
+        assert_eq!(
+            token_client.balance(&client.address),
+            100_000 - 12_000 + paid
+        );
+        assert_eq!(
+            client.try_pay_installment(&borrower),
+            Err(Ok(CreditError::TermLoanNotFound))
+        );
 
-        // Should panic: zero is not a positive amount
-        client.draw_credit(&borrower, &0_i128);
+        // A repaid loan can be replaced.
+        client.originate_term_loan(&admin, &borrower, &1_000_i128, &0_u32, &2_u32, &MONTH);
     }
 
     #[test]
-    #[should_panic(expected = "amount must be positive")]
-    fn test_draw_credit_rejected_when_amount_is_negative() {
+    fn test_installment_payment_event() {
         let env = Env::default();
-        env.mock_all_auths();
-
-        let admin = Address::generate(&env);
-        let borrower = Address::generate(&env);
+        let (client, admin, borrower, _token) = setup_term_loans(&env, 1_000);
+        client.originate_term_loan(&admin, &borrower, &12_000_i128, &1_200_u32, &12_u32, &MONTH);
+        client.pay_installment(&borrower);
 
-        let contract_id = env.register(Credit, ());
-        let client = CreditClient::new(&env, &contract_id);
+        let event = env.events().all().last().unwrap();
+        assert_eq!(
+            event.1,
+            (symbol_short!("credit"), symbol_short!("loan_pay")).into_val(&env)
+        );
+        let payload = TermLoanPaymentEvent::try_from_val(&env, &event.2).unwrap();
+        assert_eq!(payload.amount, 1_067);
+        assert_eq!(payload.interest_paid, 120);
+        assert_eq!(payload.remaining_principal, 12_000 - 947);
+        assert_eq!(payload.installments_paid, 1);
+    }
 
-        client.init(&admin);
-        client.open_credit_line(&borrower, &1000_i128, &300_u32, &70_u32);
+    #[test]
+    fn test_payoff_term_loan_charges_interest_since_last_due_date() {
+        let env = Env::default();
+        let (client, admin, borrower, token_address) = setup_term_loans(&env, 1_000);
+        client.originate_term_loan(&admin, &borrower, &12_000_i128, &1_200_u32, &12_u32, &MONTH);
+        client.pay_installment(&borrower);
+        client.pay_installment(&borrower);
+        let outstanding = client
+            .get_term_loan(&borrower)
+            .unwrap()
+            .outstanding_principal;
+
+        // Half way through the third period.
+        advance_ledger(&env, 2 * MONTH + MONTH / 2);
+        let interest = interest::interest_for(outstanding, 1_200, MONTH / 2).unwrap();
+        let token_client = token::Client::new(&env, &token_address);
+        let before = token_client.balance(&borrower);
+        assert_eq!(client.payoff_term_loan(&borrower), outstanding + interest);
+        assert_eq!(
+            token_client.balance(&borrower),
+            before - outstanding - interest
+        );
 
-        // i128 allows negatives — the guard `amount <= 0` must catch this
-        client.draw_credit(&borrower, &-1_i128);
+        let loan = client.get_term_loan(&borrower).unwrap();
+        assert_eq!(loan.outstanding_principal, 0);
+        assert_eq!(
+            client.try_payoff_term_loan(&borrower),
+            Err(Ok(CreditError::TermLoanNotFound))
+        );
     }
 
-    // --- repay_credit: zero and negative amount guards ---
-
     #[test]
-    #[should_panic(expected = "amount must be positive")]
-    fn test_repay_credit_rejects_non_positive_amount() {
+    fn test_payoff_term_loan_charges_interest_for_missed_periods() {
         let env = Env::default();
-        env.mock_all_auths();
-
-        let admin = Address::generate(&env);
-        let borrower = Address::generate(&env);
+        let (client, admin, borrower, _token) = setup_term_loans(&env, 1_000);
+        client.originate_term_loan(&admin, &borrower, &12_000_i128, &1_200_u32, &12_u32, &MONTH);
+        client.pay_installment(&borrower);
+        let outstanding = client
+            .get_term_loan(&borrower)
+            .unwrap()
+            .outstanding_principal;
+
+        // Two installments missed, then paid off half way through the fourth period.
+        advance_ledger(&env, 3 * MONTH + MONTH / 2);
+        let interest = interest::interest_for(outstanding, 1_200, 2 * MONTH + MONTH / 2).unwrap();
+        assert_eq!(client.payoff_term_loan(&borrower), outstanding + interest);
+
+        // Paying late costs more than the interest of the installments it skipped.
+        let skipped: i128 = client
+            .get_amortization_schedule(&borrower)
+            .iter()
+            .skip(1)
+            .take(2)
+            .map(|installment| installment.interest)
+            .sum();
+        assert!(interest > skipped);
+    }
 
-        let contract_id = env.register(Credit, ());
-        let client = CreditClient::new(&env, &contract_id);
+    #[test]
+    fn test_originate_term_loan_validation() {
+        let env = Env::default();
+        let (client, admin, borrower, _token) = setup_term_loans(&env, 0);
+        let originate = |principal: i128, rate: u32, periods: u32, period: u64| {
+            client.try_originate_term_loan(&admin, &borrower, &principal, &rate, &periods, &period)
+        };
+        let invalid = Err(Ok(CreditError::InvalidLoanTerms));
+        assert_eq!(originate(0, 500, 12, MONTH), invalid);
+        assert_eq!(originate(1_000, 10_001, 12, MONTH), invalid);
+        assert_eq!(originate(1_000, 500, 0, MONTH), invalid);
+        assert_eq!(
+            originate(1_000, 500, term_loan::MAX_TERM_PERIODS + 1, MONTH),
+            invalid
+        );
+        assert_eq!(originate(1_000, 500, 12, 0), invalid);
+        // Schedules whose installment or last due date overflow are rejected up front.
+        let overflow = Err(Ok(CreditError::Overflow));
+        assert_eq!(originate(i128::MAX, 0, 12, MONTH), overflow);
+        assert_eq!(originate(1_000, 0, 12, u64::MAX / 4), overflow);
+        assert_eq!(
+            originate(200_000, 500, 12, MONTH),
+            Err(Ok(CreditError::InsufficientLiquidity))
+        );
 
-        client.init(&admin);
-        client.open_credit_line(&borrower, &1000_i128, &300_u32, &70_u32);
+        assert_eq!(originate(1_000, 500, 12, MONTH), Ok(Ok(())));
+        assert_eq!(
+            originate(1_000, 500, 12, MONTH),
+            Err(Ok(CreditError::TermLoanExists))
+        );
+    }
 
-        // Should panic: repaying zero is meaningless and must be rejected
-        client.repay_credit(&borrower, &0_i128);
+    #[test]
+    fn test_originate_term_loan_requires_risk_engine() {
+        let env = Env::default();
+        let (client, _admin, borrower, _token) = setup_term_loans(&env, 0);
+        assert_eq!(
+            client.try_originate_term_loan(
+                &Address::generate(&env),
+                &borrower,
+                &1_000_i128,
+                &500_u32,
+                &12_u32,
+                &MONTH
+            ),
+            Err(Ok(CreditError::Unauthorized))
+        );
+        assert_eq!(
+            client.try_get_amortization_schedule(&borrower),
+            Err(Ok(CreditError::TermLoanNotFound))
+        );
     }
 
     #[test]
-    #[should_panic(expected = "amount must be positive")]
-    fn test_repay_credit_rejected_when_amount_is_negative() {
+    fn test_originate_term_loan_blocked_while_paused() {
         let env = Env::default();
-        env.mock_all_auths();
-
-        let admin = Address::generate(&env);
-        let borrower = Address::generate(&env);
-
-        let contract_id = env.register(Credit, ());
-        let client = CreditClient::new(&env, &contract_id);
-
-        client.init(&admin);
-        client.open_credit_line(&borrower, &1000_i128, &300_u32, &70_u32);
-
-        // Negative repayment would effectively be a draw — must be rejected
-        client.repay_credit(&borrower, &-500_i128);
+        let (client, admin, borrower, _token) = setup_term_loans(&env, 0);
+        client.pause(&admin);
+        assert_eq!(
+            client.try_originate_term_loan(
+                &admin,
+                &borrower,
+                &1_000_i128,
+                &500_u32,
+                &12_u32,
+                &MONTH
+            ),
+            Err(Ok(CreditError::ContractPaused))
+        );
     }
 
-    // --- update_risk_parameters ---
+    // --- collateral ---
+
+    /// Line of 10_000 at `rate_bps` in a funded reserve, and a collateral token worth 2 per
+    /// unit at a 150% minimum ratio of which the borrower holds 10_000.
+    fn setup_collateral(env: &Env, rate_bps: u32) -> (CreditClient<'_>, Address, Address, Address) {
+        let (client, admin, borrower, _liquidity) = setup_term_loans(env, 0);
+        let collateral = env
+            .register_stellar_asset_contract_v2(Address::generate(env))
+            .address();
+        StellarAssetClient::new(env, &collateral).mint(&borrower, &10_000);
+        client.set_collateral_config(
+            &collateral,
+            &CollateralConfig {
+                price: 2 * collateral::PRICE_SCALE,
+                min_collateral_ratio_bps: 15_000,
+                liquidation_ratio_bps: 12_500,
+            },
+        );
+        client.open_credit_line(&admin, &borrower, &10_000_i128, &rate_bps, &70_u32);
+        (client, admin, borrower, collateral)
+    }
 
     #[test]
-    fn test_update_risk_parameters_success() {
+    fn test_deposit_and_withdraw_collateral() {
         let env = Env::default();
-        env.mock_all_auths();
-
-        let admin = Address::generate(&env);
-        let borrower = Address::generate(&env);
+        let (client, _admin, borrower, collateral) = setup_collateral(&env, 0);
+        client.deposit_collateral(&borrower, &collateral, &1_500_i128);
 
-        let contract_id = env.register(Credit, ());
-        let client = CreditClient::new(&env, &contract_id);
-
-        client.init(&admin);
-        client.open_credit_line(&borrower, &1000_i128, &300_u32, &70_u32);
-
-        client.update_risk_parameters(&borrower, &2000_i128, &400_u32, &85_u32);
+        let event = env.events().all().last().unwrap();
+        assert_eq!(
+            event.1,
+            (symbol_short!("credit"), symbol_short!("coll_dep")).into_val(&env)
+        );
+        let payload = CollateralEvent::try_from_val(&env, &event.2).unwrap();
+        assert_eq!(payload.amount, 1_500);
+        assert_eq!(payload.balance, 1_500);
+        assert_eq!(client.get_collateral(&borrower, &collateral), 1_500);
+        assert_eq!(
+            client.get_collateral_position(&borrower),
+            CollateralPosition {
+                collateral_value: 3_000,
+                borrowing_power: 2_000,
+                liquidation_threshold: 2_400,
+                debt: 0,
+            }
+        );
 
-        let credit_line = client.get_credit_line(&borrower).unwrap();
-        assert_eq!(credit_line.credit_limit, 2000);
-        assert_eq!(credit_line.interest_rate_bps, 400);
-        assert_eq!(credit_line.risk_score, 85);
+        client.withdraw_collateral(&borrower, &collateral, &500_i128);
+        let event = env.events().all().last().unwrap();
+        assert_eq!(
+            event.1,
+            (symbol_short!("credit"), symbol_short!("coll_wd")).into_val(&env)
+        );
+        let payload = CollateralEvent::try_from_val(&env, &event.2).unwrap();
+        assert_eq!(payload.balance, 1_000);
+        let token_client = token::Client::new(&env, &collateral);
+        assert_eq!(token_client.balance(&client.address), 1_000);
+        assert_eq!(token_client.balance(&borrower), 9_000);
+        assert_eq!(
+            client.try_withdraw_collateral(&borrower, &collateral, &1_001_i128),
+            Err(Ok(CreditError::InsufficientCollateral))
+        );
     }
 
     #[test]
-    #[should_panic]
-    fn test_update_risk_parameters_unauthorized_caller() {
+    fn test_withdraw_collateral_keeps_debt_within_borrowing_power() {
         let env = Env::default();
-        // Do not use mock_all_auths: no auth means admin.require_auth() will fail.
-        let admin = Address::generate(&env);
-        let borrower = Address::generate(&env);
+        let (client, _admin, borrower, collateral) = setup_collateral(&env, 1_000);
+        client.deposit_collateral(&borrower, &collateral, &1_500_i128);
+        client.draw_credit(&borrower, &1_000_i128);
 
-        let contract_id = env.register(Credit, ());
-        let client = CreditClient::new(&env, &contract_id);
+        // 1_000 of debt needs 1_500 of value, i.e. 750 units at a price of 2.
+        assert_eq!(
+            client.try_withdraw_collateral(&borrower, &collateral, &751_i128),
+            Err(Ok(CreditError::CollateralRatioTooLow))
+        );
+        client.withdraw_collateral(&borrower, &collateral, &750_i128);
 
-        client.init(&admin);
-        client.open_credit_line(&borrower, &1000_i128, &300_u32, &70_u32);
-        client.update_risk_parameters(&borrower, &2000_i128, &400_u32, &85_u32);
+        // Interest not yet accrued counts as debt.
+        advance_ledger(&env, 30 * DAY);
+        let position = client.get_collateral_position(&borrower);
+        assert!(position.debt > 1_000);
+        assert_eq!(position.borrowing_power, 1_000);
+        assert_eq!(
+            client.try_withdraw_collateral(&borrower, &collateral, &1_i128),
+            Err(Ok(CreditError::CollateralRatioTooLow))
+        );
     }
 
     #[test]
-    #[should_panic(expected = "Credit line not found")]
-    fn test_update_risk_parameters_nonexistent_line() {
+    fn test_secured_line_draws_limited_by_borrowing_power() {
         let env = Env::default();
-        env.mock_all_auths();
-
-        let admin = Address::generate(&env);
-        let borrower = Address::generate(&env);
+        let (client, admin, borrower, collateral) = setup_collateral(&env, 0);
+        client.set_secured_line(&admin, &borrower, &true);
+        client.deposit_collateral(&borrower, &collateral, &1_500_i128);
 
-        let contract_id = env.register(Credit, ());
-        let client = CreditClient::new(&env, &contract_id);
+        assert_eq!(
+            client.try_draw_credit(&borrower, &2_001_i128),
+            Err(Ok(CreditError::CreditLimitExceeded))
+        );
+        client.draw_credit(&borrower, &2_000_i128);
 
-        client.init(&admin);
-        client.update_risk_parameters(&borrower, &1000_i128, &300_u32, &70_u32);
+        // More collateral raises the limit, up to the credit limit itself.
+        client.deposit_collateral(&borrower, &collateral, &8_500_i128);
+        client.draw_credit(&borrower, &8_000_i128);
+        assert_eq!(
+            client.try_draw_credit(&borrower, &1_i128),
+            Err(Ok(CreditError::CreditLimitExceeded))
+        );
     }
 
     #[test]
-    #[should_panic(expected = "credit_limit cannot be less than utilized amount")]
-    fn test_update_risk_parameters_credit_limit_below_utilized() {
+    fn test_unsecured_line_draws_ignore_collateral() {
         let env = Env::default();
-        env.mock_all_auths();
-
-        let admin = Address::generate(&env);
-        let borrower = Address::generate(&env);
-
-        let contract_id = env.register(Credit, ());
-        let client = CreditClient::new(&env, &contract_id);
-
-        client.init(&admin);
-        client.open_credit_line(&borrower, &1000_i128, &300_u32, &70_u32);
-        client.draw_credit(&borrower, &500_i128);
-
-        client.update_risk_parameters(&borrower, &300_i128, &300_u32, &70_u32);
+        let (client, admin, borrower, collateral) = setup_collateral(&env, 0);
+        client.set_secured_line(&admin, &borrower, &true);
+        client.set_secured_line(&admin, &borrower, &false);
+        client.deposit_collateral(&borrower, &collateral, &1_500_i128);
+        client.draw_credit(&borrower, &5_000_i128);
+        assert_eq!(
+            client.try_set_secured_line(&admin, &Address::generate(&env), &true),
+            Err(Ok(CreditError::CreditLineNotFound))
+        );
     }
 
     #[test]
-    #[should_panic(expected = "credit_limit must be non-negative")]
-    fn test_update_risk_parameters_negative_credit_limit() {
+    fn test_defaulted_line_collateral_is_frozen() {
         let env = Env::default();
-        env.mock_all_auths();
-
-        let admin = Address::generate(&env);
-        let borrower = Address::generate(&env);
+        let (client, _admin, borrower, collateral) = setup_collateral(&env, 0);
+        client.deposit_collateral(&borrower, &collateral, &1_500_i128);
+        client.default_credit_line(&borrower);
+        assert_eq!(
+            client.try_withdraw_collateral(&borrower, &collateral, &1_i128),
+            Err(Ok(CreditError::CreditLineDefaulted))
+        );
+    }
 
-        let contract_id = env.register(Credit, ());
-        let client = CreditClient::new(&env, &contract_id);
+    #[test]
+    fn test_deposit_collateral_validation() {
+        let env = Env::default();
+        let (client, _admin, borrower, collateral) = setup_collateral(&env, 0);
+        assert_eq!(
+            client.try_deposit_collateral(&borrower, &Address::generate(&env), &100_i128),
+            Err(Ok(CreditError::CollateralNotSupported))
+        );
+        assert_eq!(
+            client.try_deposit_collateral(&borrower, &collateral, &0_i128),
+            Err(Ok(CreditError::InvalidAmount))
+        );
+    }
 
-        client.init(&admin);
-        client.open_credit_line(&borrower, &1000_i128, &300_u32, &70_u32);
-        client.update_risk_parameters(&borrower, &(-1_i128), &300_u32, &70_u32);
+    #[test]
+    fn test_deposit_collateral_allowed_while_paused() {
+        let env = Env::default();
+        let (client, admin, borrower, collateral) = setup_collateral(&env, 0);
+        client.pause(&admin);
+        client.deposit_collateral(&borrower, &collateral, &100_i128);
+        assert_eq!(client.get_collateral(&borrower, &collateral), 100);
+        assert_eq!(
+            client.try_withdraw_collateral(&borrower, &collateral, &100_i128),
+            Err(Ok(CreditError::ContractPaused))
+        );
     }
 
     #[test]
-    #[should_panic(expected = "interest_rate_bps exceeds maximum")]
-    fn test_update_risk_parameters_interest_rate_exceeds_max() {
+    fn test_set_collateral_config_validation() {
         let env = Env::default();
-        env.mock_all_auths();
+        let (client, _admin, _borrower, collateral) = setup_collateral(&env, 0);
+        let invalid = Err(Ok(CreditError::InvalidCollateralConfig));
+        let config = |price: i128, ratio: u32, liquidation_ratio: u32| CollateralConfig {
+            price,
+            min_collateral_ratio_bps: ratio,
+            liquidation_ratio_bps: liquidation_ratio,
+        };
+        assert_eq!(
+            client.try_set_collateral_config(&collateral, &config(0, 15_000, 12_500)),
+            invalid
+        );
+        assert_eq!(
+            client.try_set_collateral_config(&collateral, &config(1, 9_999, 9_999)),
+            invalid
+        );
+        assert_eq!(
+            client.try_set_collateral_config(&collateral, &config(1, 15_000, 15_001)),
+            invalid
+        );
+        client.set_collateral_config(&collateral, &config(1, 10_000, 10_000));
+        assert_eq!(
+            client.get_collateral_config(&collateral),
+            Some(config(1, 10_000, 10_000))
+        );
+    }
 
-        let admin = Address::generate(&env);
-        let borrower = Address::generate(&env);
+    #[test]
+    #[should_panic]
+    fn test_set_collateral_config_requires_admin() {
+        let env = Env::default();
+        let (client, _admin, _borrower, collateral) = setup_collateral(&env, 0);
+        env.set_auths(&[]);
+        client.set_collateral_config(
+            &collateral,
+            &CollateralConfig {
+                price: 1,
+                min_collateral_ratio_bps: 10_000,
+                liquidation_ratio_bps: 10_000,
+            },
+        );
+    }
 
-        let contract_id = env.register(Credit, ());
-        let client = CreditClient::new(&env, &contract_id);
+    // --- liquidation ---
+
+    /// Secured line from `setup_collateral` with 1_500 units of collateral (worth 3_000,
+    /// borrowing power 2_000) and 2_000 drawn from a fresh liquidity token, and a liquidator
+    /// holding 10_000 of that token.
+    fn setup_liquidation(env: &Env) -> (CreditClient<'_>, Address, Address, Address, Address) {
+        let (client, admin, borrower, collateral) = setup_collateral(env, 0);
+        client.set_secured_line(&admin, &borrower, &true);
+        let liquidity = env
+            .register_stellar_asset_contract_v2(Address::generate(env))
+            .address();
+        client.set_liquidity_token(&liquidity);
+        let liquidator = Address::generate(env);
+        let token_admin = StellarAssetClient::new(env, &liquidity);
+        token_admin.mint(&client.address, &100_000);
+        token_admin.mint(&liquidator, &10_000);
+        client.deposit_collateral(&borrower, &collateral, &1_500_i128);
+        client.draw_credit(&borrower, &2_000_i128);
+        (client, borrower, collateral, liquidator, liquidity)
+    }
 
-        client.init(&admin);
-        client.open_credit_line(&borrower, &1000_i128, &300_u32, &70_u32);
-        client.update_risk_parameters(&borrower, &1000_i128, &10001_u32, &70_u32);
+    fn set_collateral_price(client: &CreditClient, collateral: &Address, price: i128) {
+        client.set_collateral_config(
+            collateral,
+            &CollateralConfig {
+                price,
+                min_collateral_ratio_bps: 15_000,
+                liquidation_ratio_bps: 12_500,
+            },
+        );
     }
 
     #[test]
-    #[should_panic(expected = "risk_score exceeds maximum")]
-    fn test_update_risk_parameters_risk_score_exceeds_max() {
+    fn test_liquidate_undercollateralized_line() {
         let env = Env::default();
-        env.mock_all_auths();
-
-        let admin = Address::generate(&env);
-        let borrower = Address::generate(&env);
-
-        let contract_id = env.register(Credit, ());
-        let client = CreditClient::new(&env, &contract_id);
+        let (client, borrower, collateral, liquidator, liquidity) = setup_liquidation(&env);
+        // Collateral now worth 1_500 with borrowing power 1_000 against 2_000 of debt.
+        set_collateral_price(&client, &collateral, collateral::PRICE_SCALE);
+
+        // The default 50% close factor limits the repayment to 1_000, which buys collateral
+        // worth 1_000 / 0.95 = 1_052.
+        assert_eq!(client.liquidate(&liquidator, &borrower, &5_000_i128), 1_000);
+        let events = env.events().all();
+        let event = events.last().unwrap();
+        assert_eq!(
+            event.1,
+            (symbol_short!("credit"), symbol_short!("liquidate")).into_val(&env)
+        );
+        let payload = LiquidationEvent::try_from_val(&env, &event.2).unwrap();
+        assert_eq!(payload.repaid, 1_000);
+        assert_eq!(payload.principal_paid, 1_000);
+        assert_eq!(payload.collateral_seized, 1_052);
+        assert_eq!(payload.new_utilized_amount, 1_000);
+        let seized = events
+            .iter()
+            .find(|e| e.1 == (symbol_short!("credit"), symbol_short!("coll_seiz")).into_val(&env))
+            .unwrap();
+        let seized = CollateralEvent::try_from_val(&env, &seized.2).unwrap();
+        assert_eq!(seized.amount, 1_052);
+        assert_eq!(seized.balance, 448);
 
-        client.init(&admin);
-        client.open_credit_line(&borrower, &1000_i128, &300_u32, &70_u32);
-        client.update_risk_parameters(&borrower, &1000_i128, &300_u32, &101_u32);
+        assert_eq!(
+            client.get_credit_line(&borrower).unwrap().utilized_amount,
+            1_000
+        );
+        assert_eq!(client.get_portfolio_stats().total_utilized, 1_000);
+        assert_eq!(client.get_collateral(&borrower, &collateral), 448);
+        assert_eq!(
+            token::Client::new(&env, &liquidity).balance(&liquidator),
+            9_000
+        );
+        assert_eq!(
+            token::Client::new(&env, &collateral).balance(&liquidator),
+            1_052
+        );
     }
 
     #[test]
-    fn test_update_risk_parameters_at_boundaries() {
+    fn test_liquidate_healthy_line_rejected() {
         let env = Env::default();
-        env.mock_all_auths();
-
-        let admin = Address::generate(&env);
-        let borrower = Address::generate(&env);
+        let (client, borrower, _collateral, liquidator, _liquidity) = setup_liquidation(&env);
+        assert_eq!(
+            client.try_liquidate(&liquidator, &borrower, &1_000_i128),
+            Err(Ok(CreditError::LineNotLiquidatable))
+        );
+        assert_eq!(
+            client.try_liquidate(&liquidator, &borrower, &0_i128),
+            Err(Ok(CreditError::InvalidAmount))
+        );
+    }
 
-        let contract_id = env.register(Credit, ());
-        let client = CreditClient::new(&env, &contract_id);
+    #[test]
+    fn test_max_drawn_secured_line_not_liquidatable_after_accrual() {
+        let env = Env::default();
+        let (client, admin, borrower, collateral) = setup_collateral(&env, 1_000);
+        client.set_secured_line(&admin, &borrower, &true);
+        client.deposit_collateral(&borrower, &collateral, &1_500_i128);
+        client.draw_credit(&borrower, &2_000_i128);
+
+        advance_ledger(&env, 30 * DAY);
+        let position = client.get_collateral_position(&borrower);
+        assert!(position.debt > position.borrowing_power);
+        assert!(position.debt <= position.liquidation_threshold);
+        assert_eq!(
+            client.try_liquidate(&Address::generate(&env), &borrower, &1_000_i128),
+            Err(Ok(CreditError::LineNotLiquidatable))
+        );
+    }
 
-        client.init(&admin);
-        client.open_credit_line(&borrower, &1000_i128, &300_u32, &70_u32);
-        client.update_risk_parameters(&borrower, &1000_i128, &10000_u32, &100_u32);
+    #[test]
+    fn test_liquidate_unsecured_line_with_collateral_rejected() {
+        let env = Env::default();
+        let (client, admin, borrower, collateral) = setup_collateral(&env, 0);
+        client.set_secured_line(&admin, &borrower, &false);
+        client.deposit_collateral(&borrower, &collateral, &75_i128);
+        // Drawn well past the collateral's borrowing power of 100, within the credit limit.
+        client.draw_credit(&borrower, &2_000_i128);
+        assert_eq!(
+            client.get_collateral_position(&borrower).borrowing_power,
+            100
+        );
 
-        let credit_line = client.get_credit_line(&borrower).unwrap();
-        assert_eq!(credit_line.interest_rate_bps, 10000);
-        assert_eq!(credit_line.risk_score, 100);
+        assert_eq!(
+            client.try_liquidate(&Address::generate(&env), &borrower, &1_000_i128),
+            Err(Ok(CreditError::LineNotLiquidatable))
+        );
+        assert_eq!(client.get_collateral(&borrower, &collateral), 75);
     }
 
-    // --- repay_credit: happy path and event emission ---
-
     #[test]
-    fn test_repay_credit_reduces_utilized_and_emits_event() {
+    fn test_liquidate_defaulted_line() {
         let env = Env::default();
-        env.mock_all_auths();
-
-        let admin = Address::generate(&env);
-        let borrower = Address::generate(&env);
+        let (client, borrower, collateral, liquidator, _liquidity) = setup_liquidation(&env);
+        client.set_liquidation_config(&LiquidationConfig {
+            close_factor_bps: 10_000,
+            discount_bps: 0,
+        });
+        client.default_credit_line(&borrower);
 
-        let contract_id = env.register(Credit, ());
-        let client = CreditClient::new(&env, &contract_id);
+        // A Defaulted line is liquidatable even while its collateral covers it.
+        assert_eq!(client.liquidate(&liquidator, &borrower, &2_000_i128), 2_000);
+        assert_eq!(
+            client.get_credit_line(&borrower).unwrap().utilized_amount,
+            0
+        );
+        assert_eq!(client.get_collateral(&borrower, &collateral), 500);
+        assert_eq!(
+            client.try_liquidate(&liquidator, &borrower, &1_i128),
+            Err(Ok(CreditError::LineNotLiquidatable))
+        );
+    }
 
-        client.init(&admin);
-        client.open_credit_line(&borrower, &1000_i128, &300_u32, &70_u32);
+    #[test]
+    fn test_liquidate_defaulted_line_without_collateral_rejected() {
+        let env = Env::default();
+        let (client, admin, borrower, _token) = setup_term_loans(&env, 0);
+        client.open_credit_line(&admin, &borrower, &1_000_i128, &0_u32, &70_u32);
         client.draw_credit(&borrower, &500_i128);
+        client.default_credit_line(&borrower);
+        assert_eq!(
+            client.try_liquidate(&Address::generate(&env), &borrower, &500_i128),
+            Err(Ok(CreditError::LineNotLiquidatable))
+        );
+    }
 
-        let _ = env.events().all();
-        client.repay_credit(&borrower, &200_i128);
-        let events_after = env.events().all().len();
+    #[test]
+    fn test_liquidation_capped_by_collateral() {
+        let env = Env::default();
+        let (client, borrower, collateral, liquidator, _liquidity) = setup_liquidation(&env);
+        client.set_liquidation_config(&LiquidationConfig {
+            close_factor_bps: 10_000,
+            discount_bps: 5_000,
+        });
+        set_collateral_price(&client, &collateral, collateral::PRICE_SCALE);
 
-        let credit_line = client.get_credit_line(&borrower).unwrap();
-        assert_eq!(credit_line.utilized_amount, 300);
+        // 1_500 of collateral at a 50% discount pays for 750 of debt.
+        assert_eq!(client.liquidate(&liquidator, &borrower, &2_000_i128), 750);
+        assert_eq!(client.get_collateral(&borrower, &collateral), 0);
         assert_eq!(
-            events_after, 1,
-            "repay_credit must emit exactly one RepaymentEvent"
+            client.get_credit_line(&borrower).unwrap().utilized_amount,
+            1_250
         );
     }
 
     #[test]
-    fn test_repay_credit_saturates_at_zero() {
+    fn test_set_liquidation_config_validation() {
         let env = Env::default();
-        env.mock_all_auths();
+        let (client, _admin, _opened) = setup_registry(&env, 0);
+        assert_eq!(
+            client.get_liquidation_config(),
+            liquidation::DEFAULT_LIQUIDATION_CONFIG
+        );
+        let config = |close_factor_bps: u32, discount_bps: u32| LiquidationConfig {
+            close_factor_bps,
+            discount_bps,
+        };
+        let invalid = Err(Ok(CreditError::InvalidLiquidationConfig));
+        assert_eq!(client.try_set_liquidation_config(&config(0, 500)), invalid);
+        assert_eq!(
+            client.try_set_liquidation_config(&config(10_001, 500)),
+            invalid
+        );
+        assert_eq!(
+            client.try_set_liquidation_config(&config(5_000, liquidation::MAX_DISCOUNT_BPS + 1)),
+            invalid
+        );
+        client.set_liquidation_config(&config(10_000, liquidation::MAX_DISCOUNT_BPS));
+        assert_eq!(
+            client.get_liquidation_config(),
+            config(10_000, liquidation::MAX_DISCOUNT_BPS)
+        );
+    }
 
-        let admin = Address::generate(&env);
-        let borrower = Address::generate(&env);
+    // --- price oracle ---
+
+    /// SEP-40 price feed with 14 decimals whose prices are pushed by tests.
+    #[contract]
+    struct MockOracle;
+
+    #[contractimpl]
+    impl MockOracle {
+        pub fn push_price(env: Env, token: Address, price: i128, timestamp: u64) {
+            let asset = oracle::Asset::Stellar(token);
+            let mut records: Vec<oracle::PriceData> = env
+                .storage()
+                .instance()
+                .get(&asset)
+                .unwrap_or(Vec::new(&env));
+            records.push_front(oracle::PriceData { price, timestamp });
+            env.storage().instance().set(&asset, &records);
+        }
+    }
 
-        let contract_id = env.register(Credit, ());
-        let client = CreditClient::new(&env, &contract_id);
+    #[contractimpl]
+    impl PriceFeed for MockOracle {
+        fn decimals(_env: Env) -> u32 {
+            14
+        }
 
-        client.init(&admin);
-        client.open_credit_line(&borrower, &1000_i128, &300_u32, &70_u32);
-        client.draw_credit(&borrower, &100_i128);
-        client.repay_credit(&borrower, &500_i128);
+        fn lastprice(env: Env, asset: oracle::Asset) -> Option<oracle::PriceData> {
+            Self::prices(env, asset, 1).and_then(|records| records.first())
+        }
 
-        let credit_line = client.get_credit_line(&borrower).unwrap();
-        assert_eq!(credit_line.utilized_amount, 0);
+        fn prices(env: Env, asset: oracle::Asset, records: u32) -> Option<Vec<oracle::PriceData>> {
+            let all: Vec<oracle::PriceData> = env.storage().instance().get(&asset)?;
+            Some(all.slice(0..records.min(all.len())))
+        }
     }
 
-    #[test]
-    #[should_panic(expected = "Credit line not found")]
-    fn test_repay_credit_nonexistent_line() {
-        let env = Env::default();
-        env.mock_all_auths();
-
-        let admin = Address::generate(&env);
-        let borrower = Address::generate(&env);
+    const ORACLE_UNIT: i128 = 100_000_000_000_000;
 
-        let contract_id = env.register(Credit, ());
-        let client = CreditClient::new(&env, &contract_id);
+    fn liquidity_token(env: &Env, client: &CreditClient) -> Address {
+        env.as_contract(&client.address, || {
+            env.storage()
+                .instance()
+                .get(&DataKey::LiquidityToken)
+                .unwrap()
+        })
+    }
 
-        client.init(&admin);
-        client.repay_credit(&borrower, &100_i128);
+    /// `setup_collateral` with its collateral token priced by a mock oracle at 3 per unit,
+    /// accepting prices up to an hour old that moved at most 20%.
+    fn setup_oracle(env: &Env) -> (CreditClient<'_>, Address, Address, MockOracleClient<'_>) {
+        let (client, _admin, borrower, collateral) = setup_collateral(env, 0);
+        set_time(env, 1_000_000);
+        let feed = MockOracleClient::new(env, &env.register(MockOracle, ()));
+        feed.push_price(&collateral, &(3 * ORACLE_UNIT), &1_000_000);
+        client.set_oracle_config(
+            &collateral,
+            &OracleConfig {
+                oracle: feed.address.clone(),
+                max_age_secs: 3_600,
+                max_deviation_bps: 2_000,
+            },
+        );
+        (client, borrower, collateral, feed)
     }
 
-    // --- suspend/default: unauthorized caller ---
+    #[test]
+    fn test_oracle_price_replaces_fixed_price() {
+        let env = Env::default();
+        let (client, borrower, collateral, _feed) = setup_oracle(&env);
+        assert_eq!(
+            client.get_collateral_price(&collateral),
+            3 * collateral::PRICE_SCALE
+        );
+        client.deposit_collateral(&borrower, &collateral, &1_500_i128);
+        assert_eq!(
+            client.get_collateral_position(&borrower).collateral_value,
+            4_500
+        );
+
+        client.remove_oracle_config(&collateral);
+        assert_eq!(client.get_oracle_config(&collateral), None);
+        assert_eq!(
+            client.get_collateral_price(&collateral),
+            2 * collateral::PRICE_SCALE
+        );
+    }
 
     #[test]
-    #[should_panic]
-    fn test_suspend_credit_line_unauthorized() {
+    fn test_stale_oracle_price_rejected() {
         let env = Env::default();
-        let admin = Address::generate(&env);
-        let borrower = Address::generate(&env);
+        let (client, borrower, collateral, feed) = setup_oracle(&env);
+        client.deposit_collateral(&borrower, &collateral, &1_500_i128);
+        client.draw_credit(&borrower, &1_000_i128);
 
-        let contract_id = env.register(Credit, ());
-        let client = CreditClient::new(&env, &contract_id);
+        set_time(&env, 1_000_000 + 3_601);
+        assert_eq!(
+            client.try_get_collateral_price(&collateral),
+            Err(Ok(CreditError::StalePrice))
+        );
+        assert_eq!(
+            client.try_withdraw_collateral(&borrower, &collateral, &1_i128),
+            Err(Ok(CreditError::StalePrice))
+        );
 
-        client.init(&admin);
-        client.open_credit_line(&borrower, &1000_i128, &300_u32, &70_u32);
-        client.suspend_credit_line(&borrower);
+        feed.push_price(&collateral, &(3 * ORACLE_UNIT), &(1_000_000 + 3_601));
+        client.withdraw_collateral(&borrower, &collateral, &1_i128);
     }
 
     #[test]
-    #[should_panic]
-    fn test_default_credit_line_unauthorized() {
+    fn test_oracle_price_deviation_rejected() {
         let env = Env::default();
-        let admin = Address::generate(&env);
-        let borrower = Address::generate(&env);
-
-        let contract_id = env.register(Credit, ());
-        let client = CreditClient::new(&env, &contract_id);
+        let (client, _borrower, collateral, feed) = setup_oracle(&env);
+        feed.push_price(&collateral, &(4 * ORACLE_UNIT), &1_000_010);
+        assert_eq!(
+            client.try_get_collateral_price(&collateral),
+            Err(Ok(CreditError::PriceDeviationTooHigh))
+        );
 
-        client.init(&admin);
-        client.open_credit_line(&borrower, &1000_i128, &300_u32, &70_u32);
-        client.default_credit_line(&borrower);
+        // 10% away from the previous price.
+        feed.push_price(&collateral, &(44 * ORACLE_UNIT / 10), &1_000_020);
+        assert_eq!(
+            client.get_collateral_price(&collateral),
+            44 * collateral::PRICE_SCALE / 10
+        );
     }
 
-    // --- Reentrancy guard: cleared correctly after draw and repay ---
-    //
-    // We cannot simulate a token callback in unit tests without a mock contract.
-    // These tests verify the guard is cleared on the happy path so that sequential
-    // calls succeed, proving no guard leak occurs on successful execution.
+    #[test]
+    fn test_oracle_without_price_rejected() {
+        let env = Env::default();
+        let (client, _borrower, collateral, _feed) = setup_oracle(&env);
+        let config = OracleConfig {
+            oracle: env.register(MockOracle, ()),
+            max_age_secs: 3_600,
+            max_deviation_bps: 0,
+        };
+        client.set_oracle_config(&collateral, &config);
+        assert_eq!(
+            client.try_get_collateral_price(&collateral),
+            Err(Ok(CreditError::OraclePriceUnavailable))
+        );
+        assert_eq!(
+            client.try_set_oracle_config(
+                &collateral,
+                &OracleConfig {
+                    max_age_secs: 0,
+                    ..config
+                }
+            ),
+            Err(Ok(CreditError::InvalidOracleConfig))
+        );
+    }
 
     #[test]
-    fn test_reentrancy_guard_cleared_after_draw() {
+    fn test_oracle_price_drop_makes_line_liquidatable() {
         let env = Env::default();
-        env.mock_all_auths();
+        let (client, borrower, collateral, feed) = setup_oracle(&env);
+        let liquidator = Address::generate(&env);
+        client.set_secured_line(&client.get_admin(), &borrower, &true);
+        client.deposit_collateral(&borrower, &collateral, &1_500_i128);
+        client.draw_credit(&borrower, &2_000_i128);
+        assert_eq!(
+            client.try_liquidate(&liquidator, &borrower, &1_000_i128),
+            Err(Ok(CreditError::LineNotLiquidatable))
+        );
 
-        let admin = Address::generate(&env);
-        let borrower = Address::generate(&env);
+        // Three 20% drops take the collateral from 4_500 to 2_304, below the 2_500 needed
+        // at the liquidation ratio.
+        feed.push_price(&collateral, &(24 * ORACLE_UNIT / 10), &1_000_010);
+        feed.push_price(&collateral, &(192 * ORACLE_UNIT / 100), &1_000_020);
+        feed.push_price(&collateral, &(1_536 * ORACLE_UNIT / 1_000), &1_000_030);
+        let position = client.get_collateral_position(&borrower);
+        assert_eq!(position.collateral_value, 2_304);
+        assert!(position.liquidation_threshold < position.debt);
+        StellarAssetClient::new(&env, &liquidity_token(&env, &client)).mint(&liquidator, &1_000);
+        assert_eq!(client.liquidate(&liquidator, &borrower, &1_000_i128), 1_000);
+    }
 
-        let contract_id = env.register(Credit, ());
-        let client = CreditClient::new(&env, &contract_id);
+    // --- lending pool ---
+
+    /// Credit contract drawing from a lending pool in which a lender has deposited
+    /// `deposit`, and a borrower with a 5_000 line at 10%.
+    fn setup_pool(
+        env: &Env,
+        deposit: i128,
+    ) -> (
+        CreditClient<'_>,
+        creditra_pool::PoolClient<'_>,
+        Address,
+        Address,
+    ) {
+        let (client, admin, _opened) = setup_registry(env, 0);
+        let token = env
+            .register_stellar_asset_contract_v2(Address::generate(env))
+            .address();
+        let pool = creditra_pool::PoolClient::new(env, &env.register(creditra_pool::Pool, ()));
+        pool.init(&admin, &token, &client.address);
+        client.set_lending_pool(&pool.address);
+
+        let lender = Address::generate(env);
+        let borrower = Address::generate(env);
+        let token_admin = StellarAssetClient::new(env, &token);
+        token_admin.mint(&lender, &deposit);
+        token_admin.mint(&borrower, &1_000);
+        pool.deposit(&lender, &deposit);
+        client.open_credit_line(&admin, &borrower, &5_000_i128, &1_000_u32, &70_u32);
+        (client, pool, lender, borrower)
+    }
 
-        client.init(&admin);
-        client.open_credit_line(&borrower, &1000_i128, &300_u32, &70_u32);
-        client.draw_credit(&borrower, &100_i128);
-        client.draw_credit(&borrower, &100_i128);
+    #[test]
+    fn test_draw_and_repay_through_lending_pool() {
+        let env = Env::default();
+        let (client, pool, lender, borrower) = setup_pool(&env, 10_000);
+        assert_eq!(client.get_lending_pool(), Some(pool.address.clone()));
+
+        client.draw_credit(&borrower, &2_000_i128);
+        let token_client = token::Client::new(&env, &pool.token());
+        assert_eq!(token_client.balance(&borrower), 3_000);
+        assert_eq!(pool.borrowed(), 2_000);
+        assert_eq!(pool.available_liquidity(), 8_000);
+
+        // A year at 10% on 2_000 earns the lender 200.
+        advance_ledger(&env, interest::SECONDS_PER_YEAR);
+        client.repay_credit(&borrower, &2_200_i128);
         assert_eq!(
             client.get_credit_line(&borrower).unwrap().utilized_amount,
-            200
+            0
         );
+        assert_eq!(pool.borrowed(), 0);
+        assert_eq!(pool.total_assets(), 10_200);
+        // The pool's virtual share keeps the rounding.
+        assert_eq!(pool.withdraw(&lender, &10_000_i128), 10_199);
     }
 
     #[test]
-    fn test_reentrancy_guard_cleared_after_repay() {
+    fn test_set_lending_pool_rejected_while_principal_outstanding() {
         let env = Env::default();
-        env.mock_all_auths();
+        let (client, admin, borrower, token) = setup_term_loans(&env, 0);
+        client.open_credit_line(&admin, &borrower, &5_000_i128, &0_u32, &70_u32);
+        client.draw_credit(&borrower, &5_000_i128);
+        let pool = creditra_pool::PoolClient::new(&env, &env.register(creditra_pool::Pool, ()));
+        pool.init(&admin, &token, &client.address);
 
-        let admin = Address::generate(&env);
-        let borrower = Address::generate(&env);
+        assert_eq!(
+            client.try_set_lending_pool(&pool.address),
+            Err(Ok(CreditError::UtilizationNotZero))
+        );
+        client.repay_credit(&borrower, &5_000_i128);
+        client.set_lending_pool(&pool.address);
+        assert_eq!(client.get_lending_pool(), Some(pool.address));
+    }
 
-        let contract_id = env.register(Credit, ());
-        let client = CreditClient::new(&env, &contract_id);
+    #[test]
+    fn test_draw_limited_by_lending_pool_liquidity() {
+        let env = Env::default();
+        let (client, pool, lender, borrower) = setup_pool(&env, 1_000);
+        assert_eq!(
+            client.try_draw_credit(&borrower, &1_001_i128),
+            Err(Ok(CreditError::InsufficientLiquidity))
+        );
+        client.draw_credit(&borrower, &600_i128);
 
-        client.init(&admin);
-        client.open_credit_line(&borrower, &1000_i128, &300_u32, &70_u32);
-        client.draw_credit(&borrower, &200_i128);
-        client.repay_credit(&borrower, &50_i128);
-        client.repay_credit(&borrower, &50_i128);
+        // Lent-out liquidity cannot be withdrawn until it is repaid.
         assert_eq!(
-            client.get_credit_line(&borrower).unwrap().utilized_amount,
-            100
+            pool.try_withdraw(&lender, &1_000_i128),
+            Err(Ok(creditra_pool::PoolError::InsufficientLiquidity))
         );
+        client.repay_credit(&borrower, &600_i128);
+        assert_eq!(pool.withdraw(&lender, &1_000_i128), 1_000);
     }
 
     #[test]
-    fn test_draw_credit_with_sufficient_liquidity() {
+    fn test_write_off_defaulted_line_recognizes_pool_loss() {
         let env = Env::default();
-        env.mock_all_auths();
+        let (client, pool, lender, borrower) = setup_pool(&env, 10_000);
+        client.draw_credit(&borrower, &2_000_i128);
+        advance_ledger(&env, interest::SECONDS_PER_YEAR);
+        client.default_credit_line(&borrower);
+        assert_eq!(
+            client.get_credit_line(&borrower).unwrap().accrued_interest,
+            200
+        );
 
-        let admin = Address::generate(&env);
-        let borrower = Address::generate(&env);
-        let token_admin = Address::generate(&env);
+        assert_eq!(client.write_off_credit_line(&borrower), 2_200);
+        let event = env.events().all().last().unwrap();
+        assert_eq!(
+            event.1,
+            (symbol_short!("credit"), symbol_short!("write_off")).into_val(&env)
+        );
+        let payload = WriteOffEvent::try_from_val(&env, &event.2).unwrap();
+        assert_eq!(payload.principal, 2_000);
+        assert_eq!(payload.interest, 200);
+        let line = client.get_credit_line(&borrower).unwrap();
+        assert_eq!(line.status, CreditStatus::Defaulted);
+        assert_eq!(line.utilized_amount, 0);
+        assert_eq!(client.get_portfolio_stats().total_utilized, 0);
+
+        // Lenders share the 2_000 of principal lost.
+        assert_eq!(pool.borrowed(), 0);
+        assert_eq!(pool.total_assets(), 8_000);
+        assert_eq!(pool.preview_withdraw(&pool.shares(&lender)), 8_000);
+
+        // Nothing is left to write off, and the line can be closed and reopened.
+        assert_eq!(client.write_off_credit_line(&borrower), 0);
+        let admin = client.get_admin();
+        client.close_credit_line(&borrower, &admin);
+        client.open_credit_line(&admin, &borrower, &5_000_i128, &1_000_u32, &70_u32);
+    }
 
-        let contract_id = env.register(Credit, ());
-        let client = CreditClient::new(&env, &contract_id);
+    #[test]
+    fn test_write_off_requires_defaulted_or_closed_line() {
+        let env = Env::default();
+        let (client, _pool, _lender, borrower) = setup_pool(&env, 10_000);
+        client.draw_credit(&borrower, &1_000_i128);
+        assert_eq!(
+            client.try_write_off_credit_line(&borrower),
+            Err(Ok(CreditError::ActiveCreditLineExists))
+        );
+        client.suspend_credit_line(&borrower);
+        assert_eq!(
+            client.try_write_off_credit_line(&borrower),
+            Err(Ok(CreditError::CreditLineSuspended))
+        );
 
-        client.init(&admin);
-        client.open_credit_line(&borrower, &1_000_i128, &300_u32, &70_u32);
+        // A force-closed line's balance can be written off.
+        client.close_credit_line(&borrower, &client.get_admin());
+        assert_eq!(client.write_off_credit_line(&borrower), 1_000);
+        assert_eq!(client.get_portfolio_stats().total_utilized, 0);
+    }
 
-        let token = env.register_stellar_asset_contract_v2(token_admin);
-        let token_admin_client = StellarAssetClient::new(&env, &token.address());
-        let token_client = token::Client::new(&env, &token.address());
+    #[test]
+    fn test_term_loan_through_lending_pool() {
+        let env = Env::default();
+        let (client, pool, _lender, borrower) = setup_pool(&env, 10_000);
+        let admin = client.get_admin();
+        client.originate_term_loan(&admin, &borrower, &3_000_i128, &1_200_u32, &3_u32, &MONTH);
+        assert_eq!(pool.borrowed(), 3_000);
+
+        client.pay_installment(&borrower);
+        let outstanding = client
+            .get_term_loan(&borrower)
+            .unwrap()
+            .outstanding_principal;
+        assert_eq!(pool.borrowed(), outstanding);
+        client.payoff_term_loan(&borrower);
+        assert_eq!(pool.borrowed(), 0);
+        assert!(pool.total_assets() > 10_000);
+    }
 
-        client.set_liquidity_token(&token.address());
+    // --- rate model ---
 
-        token_admin_client.mint(&contract_id, &500_i128);
-        client.draw_credit(&borrower, &200_i128);
+    fn rate_model() -> RateModel {
+        RateModel {
+            base_rate_bps: 200,
+            slope_low_bps: 400,
+            slope_high_bps: 6_000,
+            optimal_utilization_bps: 8_000,
+            risk_spread_bps: 1_000,
+        }
+    }
 
-        assert_eq!(token_client.balance(&contract_id), 300_i128);
-        assert_eq!(token_client.balance(&borrower), 200_i128);
-        assert_eq!(
-            client.get_credit_line(&borrower).unwrap().utilized_amount,
-            200_i128
-        );
+    #[test]
+    fn test_rate_model_kinked_curve() {
+        let model = rate_model();
+        let rate = |utilization: u32, risk_score: u32| {
+            rate_model::rate(&model, utilization, risk_score).rate_bps
+        };
+        assert_eq!(rate(0, 0), 200);
+        assert_eq!(rate(4_000, 0), 400);
+        assert_eq!(rate(8_000, 0), 600);
+        assert_eq!(rate(9_000, 0), 3_600);
+        assert_eq!(rate(10_000, 0), 6_600);
+        assert_eq!(rate(10_000, 100), 7_600);
+        assert_eq!(rate(0, 70), 900);
+
+        let steep = RateModel {
+            base_rate_bps: 5_000,
+            ..model
+        };
+        let breakdown = rate_model::rate(&steep, 10_000, 100);
+        assert_eq!(breakdown.utilization_rate_bps, 6_400);
+        assert_eq!(breakdown.risk_spread_bps, 1_000);
+        assert_eq!(breakdown.rate_bps, MAX_INTEREST_RATE_BPS);
     }
 
     #[test]
-    fn test_set_liquidity_source_updates_instance_storage() {
+    fn test_effective_rate_tracks_pool_utilization() {
         let env = Env::default();
-        env.mock_all_auths();
+        let (client, _pool, _lender, borrower) = setup_pool(&env, 10_000);
+        client.set_rate_model(&rate_model());
+        client.accrue_interest(&borrower);
+        assert_eq!(
+            client.get_credit_line(&borrower).unwrap().interest_rate_bps,
+            900
+        );
 
-        let admin = Address::generate(&env);
-        let reserve = Address::generate(&env);
+        // Drawing 4_000 of 10_000 moves utilization to 40%, which the line takes the next
+        // time it is touched.
+        client.draw_credit(&borrower, &4_000_i128);
+        assert_eq!(
+            client.get_effective_rate(&borrower),
+            RateBreakdown {
+                utilization_bps: 4_000,
+                base_rate_bps: 200,
+                utilization_rate_bps: 200,
+                risk_spread_bps: 700,
+                rate_bps: 1_100,
+            }
+        );
 
-        let contract_id = env.register(Credit, ());
-        let client = CreditClient::new(&env, &contract_id);
+        // The year before the touch accrues at the old 9%.
+        advance_ledger(&env, interest::SECONDS_PER_YEAR);
+        assert_eq!(client.accrue_interest(&borrower), 360);
+        assert_eq!(
+            client.get_credit_line(&borrower).unwrap().interest_rate_bps,
+            1_100
+        );
+    }
 
-        client.init(&admin);
-        client.set_liquidity_source(&reserve);
+    #[test]
+    fn test_effective_rate_uses_reserve_utilization() {
+        let env = Env::default();
+        let (client, admin, borrower, _token) = setup_term_loans(&env, 0);
+        client.set_rate_model(&rate_model());
+        client.open_credit_line(&admin, &borrower, &50_000_i128, &300_u32, &0_u32);
+        client.draw_credit(&borrower, &25_000_i128);
+
+        // 25_000 lent against 75_000 left in the reserve.
+        let breakdown = client.get_effective_rate(&borrower);
+        assert_eq!(breakdown.utilization_bps, 2_500);
+        assert_eq!(breakdown.rate_bps, 200 + 125);
+    }
 
-        let stored: Address = env
-            .as_contract(&contract_id, || {
-                env.storage().instance().get(&DataKey::LiquiditySource)
-            })
-            .unwrap();
-        assert_eq!(stored, reserve);
+    fn risk_update(env: &Env) -> RiskParametersUpdatedEvent {
+        env.events()
+            .all()
+            .iter()
+            .find(|e| e.1 == (symbol_short!("credit"), symbol_short!("risk_upd")).into_val(env))
+            .map(|e| RiskParametersUpdatedEvent::try_from_val(env, &e.2).unwrap())
+            .unwrap()
     }
 
     #[test]
-    fn test_draw_credit_uses_configured_external_liquidity_source() {
+    fn test_risk_update_reprices_under_rate_model() {
         let env = Env::default();
-        env.mock_all_auths();
+        let (client, _pool, _lender, borrower) = setup_pool(&env, 10_000);
+        let admin = client.get_admin();
+        client.set_rate_model(&rate_model());
+        client.update_risk_parameters(&admin, &borrower, &5_000_i128, &300_u32, &100_u32);
+        // The event reports the model's rate, not the 3% requested.
+        assert_eq!(risk_update(&env).interest_rate_bps, 1_200);
+        assert_eq!(
+            client.get_credit_line(&borrower).unwrap().interest_rate_bps,
+            1_200
+        );
+    }
 
-        let admin = Address::generate(&env);
+    #[test]
+    fn test_rate_model_bounded_by_asset_rate_band() {
+        let env = Env::default();
+        let s = setup_assets(&env);
+        let mut config = asset_config(&s.usdc_source);
+        config.min_interest_rate_bps = 300;
+        config.max_interest_rate_bps = 800;
+        s.client.set_asset_config(&s.usdc, &config);
+        s.client.set_rate_model(&rate_model());
+
+        // The model prices a score of 70 at 200 + 700 bps, above the asset's 8% cap.
         let borrower = Address::generate(&env);
-        let token_admin = Address::generate(&env);
+        s.client.open_asset_credit_line(
+            &s.admin,
+            &borrower,
+            &s.usdc,
+            &1_000_i128,
+            &500_u32,
+            &70_u32,
+        );
+        assert_eq!(
+            s.client
+                .get_credit_line(&borrower)
+                .unwrap()
+                .interest_rate_bps,
+            800
+        );
+        assert_eq!(s.client.get_effective_rate(&borrower).rate_bps, 800);
 
-        let contract_id = env.register(Credit, ());
-        let client = CreditClient::new(&env, &contract_id);
+        // A score of 0 prices at 2%, below the asset's 3% floor.
+        s.client
+            .update_risk_parameters(&s.admin, &borrower, &1_000_i128, &500_u32, &0_u32);
+        assert_eq!(risk_update(&env).interest_rate_bps, 300);
+        assert_eq!(
+            s.client
+                .get_credit_line(&borrower)
+                .unwrap()
+                .interest_rate_bps,
+            300
+        );
+    }
 
-        client.init(&admin);
-        client.open_credit_line(&borrower, &1_000_i128, &300_u32, &70_u32);
+    #[test]
+    fn test_rate_model_validation_and_removal() {
+        let env = Env::default();
+        let (client, _pool, _lender, borrower) = setup_pool(&env, 10_000);
+        let invalid = Err(Ok(CreditError::InvalidRateModel));
+        let model = rate_model();
+        assert_eq!(
+            client.try_set_rate_model(&RateModel {
+                optimal_utilization_bps: 0,
+                ..model.clone()
+            }),
+            invalid
+        );
+        assert_eq!(
+            client.try_set_rate_model(&RateModel {
+                optimal_utilization_bps: 10_001,
+                ..model.clone()
+            }),
+            invalid
+        );
+        assert_eq!(
+            client.try_set_rate_model(&RateModel {
+                slope_high_bps: 10_001,
+                ..model.clone()
+            }),
+            invalid
+        );
 
-        let token = env.register_stellar_asset_contract_v2(token_admin);
-        let token_admin_client = StellarAssetClient::new(&env, &token.address());
-        let token_client = token::Client::new(&env, &token.address());
-        let reserve = contract_id.clone();
+        client.set_rate_model(&model);
+        assert_eq!(client.get_rate_model(), Some(model));
+        client.remove_rate_model();
+        assert_eq!(client.get_rate_model(), None);
+        let breakdown = client.get_effective_rate(&borrower);
+        assert_eq!(breakdown.base_rate_bps, 1_000);
+        assert_eq!(breakdown.rate_bps, 1_000);
+    }
 
-        client.set_liquidity_token(&token.address());
-        client.set_liquidity_source(&reserve);
+    // --- risk tiers ---
+
+    /// Tier whose minimum rate is also its spread under the rate model.
+    fn risk_tier(min_score: u32, max_score: u32, limit: i128, rate: u32, draw: i128) -> RiskTier {
+        RiskTier {
+            min_score,
+            max_score,
+            max_credit_limit: limit,
+            min_rate_bps: rate,
+            rate_spread_bps: rate,
+            max_draw: draw,
+        }
+    }
 
-        token_admin_client.mint(&reserve, &500_i128);
-        client.draw_credit(&borrower, &120_i128);
+    /// Funded contract with three tiers: scores 0-39, 40-79 and 80-100.
+    fn setup_tiers(env: &Env) -> (CreditClient<'_>, Address, Address) {
+        let (client, admin, borrower, _token) = setup_term_loans(env, 0);
+        let mut tiers = Vec::new(env);
+        tiers.push_back(risk_tier(0, 39, 10_000, 300, 5_000));
+        tiers.push_back(risk_tier(40, 79, 5_000, 600, 2_000));
+        tiers.push_back(risk_tier(80, 100, 1_000, 1_200, 500));
+        client.set_risk_tiers(&tiers);
+        (client, admin, borrower)
+    }
 
-        assert_eq!(token_client.balance(&reserve), 380_i128);
-        assert_eq!(token_client.balance(&borrower), 120_i128);
-        assert_eq!(token_client.balance(&contract_id), 380_i128);
+    /// The `TierChangedEvent` published by the last invocation, if any.
+    fn tier_change(env: &Env) -> Option<TierChangedEvent> {
+        env.events()
+            .all()
+            .iter()
+            .find(|e| e.1 == (symbol_short!("credit"), symbol_short!("tier_chg")).into_val(env))
+            .map(|e| TierChangedEvent::try_from_val(env, &e.2).unwrap())
     }
 
     #[test]
-    #[should_panic]
-    fn test_set_liquidity_token_requires_admin_auth() {
+    fn test_open_credit_line_validated_against_tier() {
         let env = Env::default();
-        let admin = Address::generate(&env);
-        let token_admin = Address::generate(&env);
-
-        let contract_id = env.register(Credit, ());
-        let client = CreditClient::new(&env, &contract_id);
-
-        client.init(&admin);
+        let (client, admin, borrower) = setup_tiers(&env);
+        let exceeded = Err(Ok(CreditError::RiskTierExceeded));
+        assert_eq!(
+            client.try_open_credit_line(&admin, &borrower, &5_001_i128, &600_u32, &70_u32),
+            exceeded
+        );
+        assert_eq!(
+            client.try_open_credit_line(&admin, &borrower, &5_000_i128, &599_u32, &70_u32),
+            exceeded
+        );
 
-        let token = env.register_stellar_asset_contract_v2(token_admin);
-        client.set_liquidity_token(&token.address());
+        client.open_credit_line(&admin, &borrower, &5_000_i128, &600_u32, &70_u32);
+        assert_eq!(
+            tier_change(&env),
+            Some(TierChangedEvent {
+                borrower: borrower.clone(),
+                risk_score: 70,
+                old_tier: None,
+                new_tier: Some(1),
+            })
+        );
+        assert_eq!(client.get_risk_tier(&borrower), Some(1));
     }
 
     #[test]
-    #[should_panic]
-    fn test_set_liquidity_source_requires_admin_auth() {
+    fn test_risk_update_moves_line_between_tiers() {
         let env = Env::default();
-        let admin = Address::generate(&env);
-        let reserve = Address::generate(&env);
+        let (client, admin, borrower) = setup_tiers(&env);
+        client.open_credit_line(&admin, &borrower, &5_000_i128, &600_u32, &70_u32);
 
-        let contract_id = env.register(Credit, ());
-        let client = CreditClient::new(&env, &contract_id);
+        client.update_risk_parameters(&admin, &borrower, &4_000_i128, &700_u32, &75_u32);
+        assert_eq!(tier_change(&env), None);
 
-        client.init(&admin);
-        client.set_liquidity_source(&reserve);
+        assert_eq!(
+            client.try_update_risk_parameters(&admin, &borrower, &2_000_i128, &1_200_u32, &90_u32),
+            Err(Ok(CreditError::RiskTierExceeded))
+        );
+        client.update_risk_parameters(&admin, &borrower, &1_000_i128, &1_200_u32, &90_u32);
+        let change = tier_change(&env).unwrap();
+        assert_eq!(change.old_tier, Some(1));
+        assert_eq!(change.new_tier, Some(2));
+        assert_eq!(client.get_risk_tier(&borrower), Some(2));
     }
 
     #[test]
-    #[should_panic(expected = "Insufficient liquidity reserve for requested draw amount")]
-    fn test_draw_credit_with_insufficient_liquidity() {
+    fn test_draw_capped_by_tier() {
         let env = Env::default();
-        env.mock_all_auths();
+        let (client, admin, borrower) = setup_tiers(&env);
+        client.open_credit_line(&admin, &borrower, &5_000_i128, &600_u32, &70_u32);
+        assert_eq!(
+            client.try_draw_credit(&borrower, &2_001_i128),
+            Err(Ok(CreditError::RiskTierExceeded))
+        );
+        client.draw_credit(&borrower, &2_000_i128);
+        client.draw_credit(&borrower, &2_000_i128);
+    }
 
-        let admin = Address::generate(&env);
-        let borrower = Address::generate(&env);
-        let token_admin = Address::generate(&env);
+    #[test]
+    fn test_score_outside_every_tier_rejected() {
+        let env = Env::default();
+        let (client, admin, borrower, _token) = setup_term_loans(&env, 0);
+        let mut tiers = Vec::new(&env);
+        tiers.push_back(risk_tier(0, 79, 0, 0, 0));
+        client.set_risk_tiers(&tiers);
+        assert_eq!(
+            client.try_open_credit_line(&admin, &borrower, &1_000_i128, &300_u32, &90_u32),
+            Err(Ok(CreditError::NoRiskTier))
+        );
+        // Zero caps leave limits and draws unbounded.
+        client.open_credit_line(&admin, &borrower, &50_000_i128, &0_u32, &79_u32);
+        client.draw_credit(&borrower, &50_000_i128);
+    }
 
-        let contract_id = env.register(Credit, ());
-        let client = CreditClient::new(&env, &contract_id);
+    #[test]
+    fn test_set_risk_tiers_validation() {
+        let env = Env::default();
+        let (client, _admin, _opened) = setup_registry(&env, 0);
+        let invalid = Err(Ok(CreditError::InvalidRiskTiers));
+        let set = |tiers: &[RiskTier]| {
+            let mut list = Vec::new(&env);
+            for tier in tiers {
+                list.push_back(tier.clone());
+            }
+            client.try_set_risk_tiers(&list)
+        };
+        let low = risk_tier(0, 49, 0, 0, 0);
+        let high = risk_tier(50, 100, 0, 0, 0);
+        assert_eq!(set(&[high.clone(), low.clone()]), invalid);
+        assert_eq!(set(&[low.clone(), risk_tier(49, 100, 0, 0, 0)]), invalid);
+        assert_eq!(set(&[risk_tier(60, 50, 0, 0, 0)]), invalid);
+        assert_eq!(set(&[risk_tier(0, 101, 0, 0, 0)]), invalid);
+        assert_eq!(set(&[risk_tier(0, 100, -1, 0, 0)]), invalid);
+        assert_eq!(set(&[risk_tier(0, 100, 0, 10_001, 0)]), invalid);
+        let mut steep = risk_tier(0, 100, 0, 0, 0);
+        steep.min_rate_bps = 10_001;
+        assert_eq!(set(&[steep]), invalid);
+        assert_eq!(set(&[risk_tier(0, 100, 0, 0, -1)]), invalid);
+        let many: [RiskTier; 11] =
+            core::array::from_fn(|i| risk_tier(i as u32 * 9, i as u32 * 9 + 8, 0, 0, 0));
+        assert_eq!(set(&many), invalid);
+
+        assert_eq!(set(&[low.clone(), high.clone()]), Ok(Ok(())));
+        let event = env.events().all().last().unwrap();
+        assert_eq!(
+            event.1,
+            (symbol_short!("credit"), symbol_short!("tiers_set")).into_val(&env)
+        );
+        let published = Vec::<RiskTier>::try_from_val(&env, &event.2).unwrap();
+        assert_eq!(published, vec![&env, low, high]);
+        assert_eq!(client.get_risk_tiers().len(), 2);
+        assert_eq!(set(&[]), Ok(Ok(())));
+        assert!(client.get_risk_tiers().is_empty());
+    }
 
-        client.init(&admin);
-        client.open_credit_line(&borrower, &1_000_i128, &300_u32, &70_u32);
+    #[test]
+    fn test_rate_model_uses_tier_spread() {
+        let env = Env::default();
+        let (client, admin, borrower) = setup_tiers(&env);
+        client.set_rate_model(&rate_model());
+        client.open_credit_line(&admin, &borrower, &5_000_i128, &600_u32, &70_u32);
+        let breakdown = client.get_effective_rate(&borrower);
+        assert_eq!(breakdown.risk_spread_bps, 600);
+        assert_eq!(breakdown.rate_bps, 200 + 600);
+    }
 
-        let token = env.register_stellar_asset_contract_v2(token_admin);
-        let token_admin_client = StellarAssetClient::new(&env, &token.address());
+    #[test]
+    fn test_tier_min_rate_separate_from_spread() {
+        let env = Env::default();
+        let (client, admin, borrower) = setup_tiers(&env);
+        let mut tier = risk_tier(0, 100, 10_000, 900, 0);
+        tier.rate_spread_bps = 100;
+        client.set_risk_tiers(&vec![&env, tier]);
 
-        client.set_liquidity_token(&token.address());
+        // The floor applies to requested rates, not the spread.
+        assert_eq!(
+            client.try_open_credit_line(&admin, &borrower, &5_000_i128, &800_u32, &70_u32),
+            Err(Ok(CreditError::RiskTierExceeded))
+        );
+        client.open_credit_line(&admin, &borrower, &5_000_i128, &900_u32, &70_u32);
 
-        token_admin_client.mint(&contract_id, &50_i128);
-        client.draw_credit(&borrower, &100_i128);
+        // The model adds the 1% spread, and the 9% floor still holds.
+        client.set_rate_model(&rate_model());
+        let breakdown = client.get_effective_rate(&borrower);
+        assert_eq!(breakdown.risk_spread_bps, 100);
+        assert_eq!(breakdown.rate_bps, 900);
     }
 }
@@ -0,0 +1,122 @@
+//! Liquidation of defaulted and undercollateralized lines.
+//!
+//! Any Defaulted line with collateral can be liquidated; an Active or Suspended line only
+//! if it is secured and its debt exceeds its collateral's liquidation threshold, which
+//! sits above the borrowing power it may draw to.
+//!
+//! A liquidator repays part of a line's debt and receives the borrower's collateral at a
+//! discount: each unit of debt repaid buys collateral worth `1 / (1 - discount)` of it.
+//! The close factor bounds the share of the debt repaid per call, and the repayment is
+//! further capped so the seized collateral never exceeds what the borrower deposited.
+
+use soroban_sdk::{Address, Env, Vec};
+
+use crate::collateral::{self, PRICE_SCALE};
+use crate::types::{CollateralPosition, CreditLineData, CreditStatus, LiquidationConfig};
+use crate::{CreditError, DataKey};
+
+/// Basis-point denominator (10000 = 100%).
+const BPS_DENOMINATOR: i128 = 10_000;
+
+/// Largest discount the admin may set, in basis points.
+pub const MAX_DISCOUNT_BPS: u32 = 5_000;
+
+/// Settings used until the admin sets a `LiquidationConfig`.
+pub const DEFAULT_LIQUIDATION_CONFIG: LiquidationConfig = LiquidationConfig {
+    close_factor_bps: 5_000,
+    discount_bps: 500,
+};
+
+pub fn config(env: &Env) -> LiquidationConfig {
+    env.storage()
+        .instance()
+        .get(&DataKey::LiquidationConfig)
+        .unwrap_or(DEFAULT_LIQUIDATION_CONFIG)
+}
+
+/// Whether `config` is usable: a close factor in 1..=10000 and a discount of at most
+/// `MAX_DISCOUNT_BPS`.
+pub fn is_valid(config: &LiquidationConfig) -> bool {
+    config.close_factor_bps > 0
+        && config.close_factor_bps <= BPS_DENOMINATOR as u32
+        && config.discount_bps <= MAX_DISCOUNT_BPS
+}
+
+/// Whether `line` may be liquidated: it is Defaulted, or it is `secured` and its debt
+/// exceeds the liquidation threshold of the collateral backing it. Unsecured lines draw against
+/// their credit limit, so collateral they post does not put them at risk of liquidation.
+pub fn is_liquidatable(
+    line: &CreditLineData,
+    position: &CollateralPosition,
+    secured: bool,
+) -> bool {
+    line.status == CreditStatus::Defaulted
+        || (secured
+            && position.collateral_value > 0
+            && position.debt > position.liquidation_threshold)
+}
+
+/// Most of `position`'s debt a single liquidation may repay: the close factor's share of
+/// the debt, rounded up so small debts can be cleared, and no more than the collateral can
+/// pay for at the discount.
+pub fn max_repay(
+    config: &LiquidationConfig,
+    position: &CollateralPosition,
+) -> Result<i128, CreditError> {
+    let by_close_factor = position
+        .debt
+        .checked_mul(config.close_factor_bps as i128)
+        .map(|v| (v + BPS_DENOMINATOR - 1) / BPS_DENOMINATOR)
+        .ok_or(CreditError::Overflow)?;
+    let by_collateral = position
+        .collateral_value
+        .checked_mul(BPS_DENOMINATOR - config.discount_bps as i128)
+        .map(|v| v / BPS_DENOMINATOR)
+        .ok_or(CreditError::Overflow)?;
+    Ok(by_close_factor.min(by_collateral))
+}
+
+/// Collateral value a liquidator receives for repaying `repaid`, rounded down.
+pub fn seized_value(config: &LiquidationConfig, repaid: i128) -> Result<i128, CreditError> {
+    repaid
+        .checked_mul(BPS_DENOMINATOR)
+        .map(|v| v / (BPS_DENOMINATOR - config.discount_bps as i128))
+        .ok_or(CreditError::Overflow)
+}
+
+/// Amounts of each collateral token, in deposit order, that together are worth at most
+/// `value`. Tokens without a config are skipped, as they count for nothing.
+pub fn plan_seizure(
+    env: &Env,
+    borrower: &Address,
+    value: i128,
+) -> Result<Vec<(Address, i128)>, CreditError> {
+    let mut seizure = Vec::new(env);
+    let mut remaining = value;
+    for token in collateral::tokens(env, borrower).iter() {
+        if remaining <= 0 {
+            break;
+        }
+        let Some(config) = collateral::config(env, &token) else {
+            continue;
+        };
+        let price = collateral::price(env, &token, &config)?;
+        let balance = collateral::balance(env, borrower, &token);
+        let token_value = collateral::value_of(price, balance)?;
+        let amount = if token_value <= remaining {
+            remaining -= token_value;
+            balance
+        } else {
+            let amount = remaining
+                .checked_mul(PRICE_SCALE)
+                .map(|v| v / price)
+                .ok_or(CreditError::Overflow)?;
+            remaining = 0;
+            amount
+        };
+        if amount > 0 {
+            seizure.push_back((token, amount));
+        }
+    }
+    Ok(seizure)
+}
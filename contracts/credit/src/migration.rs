@@ -0,0 +1,147 @@
+//! Storage schema versions and migration of stored credit lines.
+//!
+//! * Version 1 is the original layout, whose `CreditLineData` had no interest or fee fields.
+//! * Version 2 added `accrued_interest`, `accrued_fees` and `last_accrual_ts`.
+//! * Version 3 moved every key into `DataKey`: credit lines from the bare borrower
+//!   `Address` to `DataKey::CreditLine`, and the admin and reentrancy guard from
+//!   `Symbol` keys to `DataKey::Admin` and `DataKey::Reentrancy`.
+//! * Version 4 added `CreditLineData::asset`; older lines use the global liquidity token.
+//!
+//! Reads accept every older layout and key, so data stays usable until `migrate`
+//! rewrites it.
+
+use soroban_sdk::{contracttype, Address, Env, Map, Symbol, TryFromVal, Val};
+
+use crate::types::{CreditLineData, CreditStatus};
+use crate::DataKey;
+
+/// Schema version written by this build of the contract.
+pub const SCHEMA_VERSION: u32 = 4;
+
+/// Schema version assumed for deployments that predate version tracking.
+pub const LEGACY_SCHEMA_VERSION: u32 = 1;
+
+/// Maximum number of borrowers `migrate` rewrites in one call.
+pub const MAX_MIGRATION_BATCH: u32 = 50;
+
+/// Credit line layout of schema version 1.
+#[contracttype]
+pub struct CreditLineDataV1 {
+    pub borrower: Address,
+    pub credit_limit: i128,
+    pub utilized_amount: i128,
+    pub interest_rate_bps: u32,
+    pub risk_score: u32,
+    pub status: CreditStatus,
+}
+
+/// Credit line layout of schema versions 2 and 3.
+#[contracttype]
+pub struct CreditLineDataV2 {
+    pub borrower: Address,
+    pub credit_limit: i128,
+    pub utilized_amount: i128,
+    pub interest_rate_bps: u32,
+    pub risk_score: u32,
+    pub status: CreditStatus,
+    pub accrued_interest: i128,
+    pub accrued_fees: i128,
+    pub last_accrual_ts: u64,
+}
+
+/// How a stored credit line was decoded.
+pub enum Decoded {
+    Current(CreditLineData),
+    /// A record in an older layout, upgraded in memory but not yet rewritten.
+    Legacy(CreditLineData),
+}
+
+/// Instance key the admin was stored under before version 3.
+pub fn legacy_admin_key(env: &Env) -> Symbol {
+    Symbol::new(env, "admin")
+}
+
+/// Instance key the reentrancy guard was stored under before version 3.
+fn legacy_reentrancy_key(env: &Env) -> Symbol {
+    Symbol::new(env, "reentrancy")
+}
+
+/// A stored credit line and whether it still uses a pre-version-3 key or an older layout.
+pub struct StoredLine {
+    pub line: CreditLineData,
+    pub legacy_key: bool,
+    pub legacy_layout: bool,
+}
+
+/// Read `borrower`'s credit line from `DataKey::CreditLine`, falling back to the bare
+/// `Address` key used before version 3.
+pub fn read_credit_line(env: &Env, borrower: &Address) -> Option<StoredLine> {
+    let persistent = env.storage().persistent();
+    let (val, legacy_key) = match persistent.get::<_, Val>(&DataKey::CreditLine(borrower.clone())) {
+        Some(val) => (val, false),
+        None => (persistent.get::<_, Val>(borrower)?, true),
+    };
+    let (line, legacy_layout) = match decode_credit_line(env, val)? {
+        Decoded::Current(line) => (line, false),
+        Decoded::Legacy(line) => (line, true),
+    };
+    Some(StoredLine {
+        line,
+        legacy_key,
+        legacy_layout,
+    })
+}
+
+/// Move the admin to `DataKey::Admin` and drop the legacy reentrancy flag, which is
+/// always cleared between calls.
+pub fn migrate_instance_keys(env: &Env) {
+    let instance = env.storage().instance();
+    let legacy_admin = legacy_admin_key(env);
+    if let Some(admin) = instance.get::<_, Address>(&legacy_admin) {
+        if !instance.has(&DataKey::Admin) {
+            instance.set(&DataKey::Admin, &admin);
+        }
+        instance.remove(&legacy_admin);
+    }
+    instance.remove(&legacy_reentrancy_key(env));
+}
+
+/// Decode a stored credit line in any layout. Version 1 lines start accruing interest
+/// from the current ledger, since that layout never tracked it.
+pub fn decode_credit_line(env: &Env, val: Val) -> Option<Decoded> {
+    // Struct decoding traps on unknown layouts, so pick the layout from the field names.
+    let fields = Map::<Symbol, Val>::try_from_val(env, &val).ok()?;
+    if fields.contains_key(Symbol::new(env, "asset")) {
+        return CreditLineData::try_from_val(env, &val)
+            .ok()
+            .map(Decoded::Current);
+    }
+    if fields.contains_key(Symbol::new(env, "last_accrual_ts")) {
+        let legacy = CreditLineDataV2::try_from_val(env, &val).ok()?;
+        return Some(Decoded::Legacy(CreditLineData {
+            borrower: legacy.borrower,
+            asset: None,
+            credit_limit: legacy.credit_limit,
+            utilized_amount: legacy.utilized_amount,
+            interest_rate_bps: legacy.interest_rate_bps,
+            risk_score: legacy.risk_score,
+            status: legacy.status,
+            accrued_interest: legacy.accrued_interest,
+            accrued_fees: legacy.accrued_fees,
+            last_accrual_ts: legacy.last_accrual_ts,
+        }));
+    }
+    let legacy = CreditLineDataV1::try_from_val(env, &val).ok()?;
+    Some(Decoded::Legacy(CreditLineData {
+        borrower: legacy.borrower,
+        asset: None,
+        credit_limit: legacy.credit_limit,
+        utilized_amount: legacy.utilized_amount,
+        interest_rate_bps: legacy.interest_rate_bps,
+        risk_score: legacy.risk_score,
+        status: legacy.status,
+        accrued_interest: 0,
+        accrued_fees: 0,
+        last_accrual_ts: env.ledger().timestamp(),
+    }))
+}
@@ -0,0 +1,114 @@
+//! Collateral prices from SEP-40 price feeds.
+//!
+//! The admin may point a collateral token at an oracle contract. Its `lastprice` is then
+//! used instead of the token's fixed `CollateralConfig::price`, converted from the feed's
+//! `decimals` to `collateral::PRICE_SCALE`. A price older than the config's maximum age is
+//! rejected, as is one that moved from the feed's previous record by more than the maximum
+//! deviation.
+
+use soroban_sdk::{contractclient, contracttype, Address, Env, Symbol, Vec};
+
+use crate::collateral::PRICE_SCALE;
+use crate::types::OracleConfig;
+use crate::{CreditError, DataKey};
+
+/// Basis-point denominator (10000 = 100%).
+const BPS_DENOMINATOR: i128 = 10_000;
+
+/// Asset identifier of the SEP-40 interface.
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum Asset {
+    Stellar(Address),
+    Other(Symbol),
+}
+
+/// A price record of the SEP-40 interface.
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct PriceData {
+    pub price: i128,
+    pub timestamp: u64,
+}
+
+/// The part of the SEP-40 price feed interface the contract reads. Only the generated
+/// client is used outside tests; feeds implement the trait.
+#[allow(dead_code)]
+#[contractclient(name = "PriceFeedClient")]
+pub trait PriceFeed {
+    /// Number of decimals of the feed's prices.
+    fn decimals(env: Env) -> u32;
+    /// Most recent price of `asset`.
+    fn lastprice(env: Env, asset: Asset) -> Option<PriceData>;
+    /// Up to `records` most recent prices of `asset`, newest first.
+    fn prices(env: Env, asset: Asset, records: u32) -> Option<Vec<PriceData>>;
+}
+
+pub fn config(env: &Env, token: &Address) -> Option<OracleConfig> {
+    env.storage()
+        .instance()
+        .get(&DataKey::OracleConfig(token.clone()))
+}
+
+/// Whether `config` is usable: prices must have a positive maximum age.
+pub fn is_valid(config: &OracleConfig) -> bool {
+    config.max_age_secs > 0
+}
+
+/// Price of `token` from the feed in `config`, scaled by `PRICE_SCALE`.
+///
+/// # Errors
+/// * `OraclePriceUnavailable` if the feed has no positive price for the token.
+/// * `StalePrice` if the price is older than `max_age_secs`.
+/// * `PriceDeviationTooHigh` if the price moved from the previous record by more than
+///   `max_deviation_bps`.
+pub fn price(env: &Env, token: &Address, config: &OracleConfig) -> Result<i128, CreditError> {
+    let feed = PriceFeedClient::new(env, &config.oracle);
+    let asset = Asset::Stellar(token.clone());
+    let latest = feed
+        .lastprice(&asset)
+        .filter(|data| data.price > 0)
+        .ok_or(CreditError::OraclePriceUnavailable)?;
+    if env.ledger().timestamp().saturating_sub(latest.timestamp) > config.max_age_secs {
+        return Err(CreditError::StalePrice);
+    }
+    if config.max_deviation_bps > 0 {
+        check_deviation(&feed, &asset, &latest, config.max_deviation_bps)?;
+    }
+    scale(latest.price, feed.decimals())
+}
+
+/// Fail with `PriceDeviationTooHigh` if `latest` moved from the record before it by more
+/// than `max_deviation_bps`. Feeds with a single record pass.
+fn check_deviation(
+    feed: &PriceFeedClient,
+    asset: &Asset,
+    latest: &PriceData,
+    max_deviation_bps: u32,
+) -> Result<(), CreditError> {
+    let Some(previous) = feed
+        .prices(asset, &2)
+        .and_then(|records| records.get(1))
+        .filter(|data| data.price > 0 && data.timestamp < latest.timestamp)
+    else {
+        return Ok(());
+    };
+    let deviation = (latest.price - previous.price)
+        .checked_abs()
+        .and_then(|v| v.checked_mul(BPS_DENOMINATOR))
+        .map(|v| v / previous.price)
+        .ok_or(CreditError::Overflow)?;
+    if deviation > max_deviation_bps as i128 {
+        return Err(CreditError::PriceDeviationTooHigh);
+    }
+    Ok(())
+}
+
+/// Convert a price with `decimals` decimals to `PRICE_SCALE`, rounding down.
+fn scale(price: i128, decimals: u32) -> Result<i128, CreditError> {
+    let unit = 10_i128.checked_pow(decimals).ok_or(CreditError::Overflow)?;
+    price
+        .checked_mul(PRICE_SCALE)
+        .map(|v| v / unit)
+        .ok_or(CreditError::Overflow)
+}
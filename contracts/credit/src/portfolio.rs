@@ -0,0 +1,161 @@
+//! Protocol-wide totals over all credit lines, kept up to date on every save.
+//!
+//! Each save applies the difference between a line's previous and new contribution, so
+//! the totals always equal the sum over stored lines without scanning them. Closed lines
+//! contribute no credit limit, since it can no longer be drawn, but still contribute any
+//! balance left on them.
+//!
+//! Amounts are summed across assets as-is. Principal outstanding is also tracked per
+//! asset, so caps can compare it with the matching liquidity reserve. Term loans are
+//! counted separately from lines, as principal outstanding in the global token.
+
+use soroban_sdk::{contracttype, Address, Env};
+
+use crate::types::{CreditLineData, CreditStatus, PortfolioStats};
+use crate::{registry, CreditError, DataKey};
+
+/// Stored running totals; counts come from the registry.
+#[contracttype]
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct PortfolioTotals {
+    pub credit_limit: i128,
+    pub utilized: i128,
+    pub accrued_interest: i128,
+    pub accrued_fees: i128,
+}
+
+pub fn totals(env: &Env) -> PortfolioTotals {
+    env.storage()
+        .instance()
+        .get(&DataKey::PortfolioTotals)
+        .unwrap_or_default()
+}
+
+fn committed_limit(line: &CreditLineData) -> i128 {
+    if line.status == CreditStatus::Closed {
+        0
+    } else {
+        line.credit_limit
+    }
+}
+
+fn shift(total: i128, before: i128, after: i128) -> Result<i128, CreditError> {
+    total
+        .checked_sub(before)
+        .and_then(|v| v.checked_add(after))
+        .ok_or(CreditError::Overflow)
+}
+
+/// Replace `before`'s contribution to the totals with `after`'s. `before` is `None` for a
+/// line that has not been counted yet.
+pub fn apply(
+    env: &Env,
+    before: Option<&CreditLineData>,
+    after: &CreditLineData,
+) -> Result<(), CreditError> {
+    let (limit, utilized, interest, fees) = match before {
+        Some(line) => (
+            committed_limit(line),
+            line.utilized_amount,
+            line.accrued_interest,
+            line.accrued_fees,
+        ),
+        None => (0, 0, 0, 0),
+    };
+    let current = totals(env);
+    let updated = PortfolioTotals {
+        credit_limit: shift(current.credit_limit, limit, committed_limit(after))?,
+        utilized: shift(current.utilized, utilized, after.utilized_amount)?,
+        accrued_interest: shift(current.accrued_interest, interest, after.accrued_interest)?,
+        accrued_fees: shift(current.accrued_fees, fees, after.accrued_fees)?,
+    };
+    if updated != current {
+        env.storage()
+            .instance()
+            .set(&DataKey::PortfolioTotals, &updated);
+    }
+
+    let before_asset = before.and_then(|line| line.asset.clone());
+    if before_asset == after.asset {
+        if let Some(asset) = &after.asset {
+            add_asset_utilized(env, asset, after.utilized_amount - utilized)?;
+        }
+    } else {
+        if let Some(asset) = &before_asset {
+            add_asset_utilized(env, asset, -utilized)?;
+        }
+        if let Some(asset) = &after.asset {
+            add_asset_utilized(env, asset, after.utilized_amount)?;
+        }
+    }
+    Ok(())
+}
+
+fn add_asset_utilized(env: &Env, asset: &Address, delta: i128) -> Result<(), CreditError> {
+    if delta == 0 {
+        return Ok(());
+    }
+    let instance = env.storage().instance();
+    let key = DataKey::AssetUtilized(asset.clone());
+    let utilized: i128 = instance.get(&key).unwrap_or(0);
+    let total: i128 = instance.get(&DataKey::AssetUtilizedTotal).unwrap_or(0);
+    instance.set(
+        &key,
+        &utilized.checked_add(delta).ok_or(CreditError::Overflow)?,
+    );
+    instance.set(
+        &DataKey::AssetUtilizedTotal,
+        &total.checked_add(delta).ok_or(CreditError::Overflow)?,
+    );
+    Ok(())
+}
+
+/// Principal outstanding on term loans.
+pub fn term_principal(env: &Env) -> i128 {
+    env.storage()
+        .instance()
+        .get(&DataKey::TermPrincipal)
+        .unwrap_or(0)
+}
+
+/// Replace a term loan's outstanding principal `before` with `after` in the totals.
+pub fn apply_term_loan(env: &Env, before: i128, after: i128) -> Result<(), CreditError> {
+    if before == after {
+        return Ok(());
+    }
+    let total = shift(term_principal(env), before, after)?;
+    env.storage()
+        .instance()
+        .set(&DataKey::TermPrincipal, &total);
+    Ok(())
+}
+
+/// Principal outstanding in `asset`; `None` means lines and term loans on the global
+/// liquidity token.
+pub fn pool_utilized(env: &Env, asset: &Option<Address>) -> i128 {
+    let instance = env.storage().instance();
+    match asset {
+        Some(asset) => instance
+            .get(&DataKey::AssetUtilized(asset.clone()))
+            .unwrap_or(0),
+        None => {
+            let per_asset: i128 = instance.get(&DataKey::AssetUtilizedTotal).unwrap_or(0);
+            totals(env).utilized - per_asset + term_principal(env)
+        }
+    }
+}
+
+pub fn stats(env: &Env) -> PortfolioStats {
+    let totals = totals(env);
+    PortfolioStats {
+        total_credit_limit: totals.credit_limit,
+        total_utilized: totals.utilized,
+        total_accrued_interest: totals.accrued_interest,
+        total_accrued_fees: totals.accrued_fees,
+        total_term_principal: term_principal(env),
+        active_count: registry::status_count(env, CreditStatus::Active),
+        suspended_count: registry::status_count(env, CreditStatus::Suspended),
+        defaulted_count: registry::status_count(env, CreditStatus::Defaulted),
+        closed_count: registry::status_count(env, CreditStatus::Closed),
+    }
+}
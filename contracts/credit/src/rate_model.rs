@@ -0,0 +1,147 @@
+//! Utilization-based interest rates.
+//!
+//! With a `RateModel` set, a line's rate is no longer the static `interest_rate_bps` passed
+//! by the risk engine but follows a kinked curve of its liquidity source's utilization:
+//! the base rate, plus `slope_low_bps` spread linearly up to the optimal utilization, plus
+//! `slope_high_bps` spread linearly from there to full utilization. A risk spread of
+//! `risk_spread_bps * risk_score / 100`, or the spread of the line's risk tier if it has
+//! one, is added on top, and the total is capped at 100%. The line's rate then never falls
+//! below its tier's floor and stays within its asset's `[min, max]` rate band.
+//!
+//! Lines take the model's rate whenever they are touched, after interest has accrued at
+//! the previous rate, so `interest_rate_bps` always holds the rate accruing now.
+
+use soroban_sdk::{token, Address, Env};
+
+use crate::lending_pool::{self, LendingPoolClient};
+use crate::types::{CreditLineData, RateBreakdown, RateModel};
+use crate::{
+    assets, portfolio, risk_tiers, CreditError, DataKey, MAX_INTEREST_RATE_BPS, MAX_RISK_SCORE,
+};
+
+/// Basis-point denominator (10000 = 100%).
+const BPS_DENOMINATOR: u32 = 10_000;
+
+pub fn model(env: &Env) -> Option<RateModel> {
+    env.storage().instance().get(&DataKey::RateModel)
+}
+
+/// Whether `model` is usable: an optimal utilization in 1..=10000 bps and no component
+/// above 10000 bps.
+pub fn is_valid(model: &RateModel) -> bool {
+    model.optimal_utilization_bps > 0
+        && model.optimal_utilization_bps <= BPS_DENOMINATOR
+        && model.base_rate_bps <= MAX_INTEREST_RATE_BPS
+        && model.slope_low_bps <= MAX_INTEREST_RATE_BPS
+        && model.slope_high_bps <= MAX_INTEREST_RATE_BPS
+        && model.risk_spread_bps <= MAX_INTEREST_RATE_BPS
+}
+
+/// Share of `asset`'s liquidity lent out, in basis points: principal outstanding over
+/// principal outstanding plus the liquidity source's balance. 0 when both are zero or no
+/// liquidity token is configured.
+pub fn utilization_bps(env: &Env, asset: &Option<Address>) -> Result<u32, CreditError> {
+    let (borrowed, available) = match lending_pool::for_asset(env, asset) {
+        Some(pool) => {
+            let pool = LendingPoolClient::new(env, &pool);
+            (pool.borrowed(), pool.available_liquidity())
+        }
+        None => {
+            let (token_address, reserve_address) = assets::liquidity(env, asset)?;
+            let Some(token_address) = token_address else {
+                return Ok(0);
+            };
+            let available = token::Client::new(env, &token_address).balance(&reserve_address);
+            (portfolio::pool_utilized(env, asset), available)
+        }
+    };
+    let total = borrowed
+        .checked_add(available)
+        .ok_or(CreditError::Overflow)?;
+    if borrowed <= 0 || total <= 0 {
+        return Ok(0);
+    }
+    let utilization = borrowed
+        .checked_mul(BPS_DENOMINATOR as i128)
+        .map(|v| v / total)
+        .ok_or(CreditError::Overflow)?;
+    Ok(utilization.min(BPS_DENOMINATOR as i128) as u32)
+}
+
+/// How `model` prices a line with `risk_score` at `utilization_bps`.
+pub fn rate(model: &RateModel, utilization_bps: u32, risk_score: u32) -> RateBreakdown {
+    let optimal = model.optimal_utilization_bps as u64;
+    let utilization = utilization_bps as u64;
+    let utilization_rate = if utilization <= optimal {
+        model.slope_low_bps as u64 * utilization / optimal
+    } else {
+        let above = utilization - optimal;
+        let range = BPS_DENOMINATOR as u64 - optimal;
+        model.slope_low_bps as u64 + model.slope_high_bps as u64 * above / range
+    };
+    let risk_spread = model.risk_spread_bps as u64 * risk_score.min(MAX_RISK_SCORE) as u64
+        / MAX_RISK_SCORE as u64;
+    let total = model.base_rate_bps as u64 + utilization_rate + risk_spread;
+    RateBreakdown {
+        utilization_bps,
+        base_rate_bps: model.base_rate_bps,
+        utilization_rate_bps: utilization_rate as u32,
+        risk_spread_bps: risk_spread as u32,
+        rate_bps: total.min(MAX_INTEREST_RATE_BPS as u64) as u32,
+    }
+}
+
+/// How `model` prices `line` at `utilization_bps`, taking the risk spread from the line's
+/// tier when it has one, then bounding the rate by the tier's floor and the asset's band.
+fn price(
+    env: &Env,
+    model: &RateModel,
+    utilization_bps: u32,
+    line: &CreditLineData,
+) -> RateBreakdown {
+    let mut breakdown = rate(model, utilization_bps, line.risk_score);
+    if let Some((_, tier)) = risk_tiers::tier_for(env, line.risk_score) {
+        let total = breakdown.base_rate_bps as u64
+            + breakdown.utilization_rate_bps as u64
+            + tier.rate_spread_bps as u64;
+        breakdown.risk_spread_bps = tier.rate_spread_bps;
+        breakdown.rate_bps =
+            (total.min(MAX_INTEREST_RATE_BPS as u64) as u32).max(tier.min_rate_bps);
+    }
+    if let Some(config) = line
+        .asset
+        .as_ref()
+        .and_then(|asset| assets::config(env, asset))
+    {
+        breakdown.rate_bps = breakdown
+            .rate_bps
+            .clamp(config.min_interest_rate_bps, config.max_interest_rate_bps);
+    }
+    breakdown
+}
+
+/// How `line`'s rate is computed now. Without a model, the static rate is reported as the
+/// base rate.
+pub fn breakdown(env: &Env, line: &CreditLineData) -> Result<RateBreakdown, CreditError> {
+    let utilization_bps = utilization_bps(env, &line.asset)?;
+    Ok(match model(env) {
+        Some(model) => price(env, &model, utilization_bps, line),
+        None => RateBreakdown {
+            utilization_bps,
+            base_rate_bps: line.interest_rate_bps,
+            utilization_rate_bps: 0,
+            risk_spread_bps: 0,
+            rate_bps: line.interest_rate_bps,
+        },
+    })
+}
+
+/// Set `line`'s rate to the model's current rate, bounded as `price` describes. No-op
+/// without a model.
+pub fn apply(env: &Env, line: &mut CreditLineData) -> Result<(), CreditError> {
+    if let Some(model) = model(env) {
+        let utilization_bps = utilization_bps(env, &line.asset)?;
+        line.interest_rate_bps = price(env, &model, utilization_bps, line).rate_bps;
+    }
+    Ok(())
+}
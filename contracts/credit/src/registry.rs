@@ -0,0 +1,157 @@
+//! On-chain index of borrowers for enumeration without off-chain event replay.
+//!
+//! Every borrower with a credit line appears once in a global, append-only list and once
+//! in the list for its current status. Status lists use swap-remove, so their order is
+//! not stable across status changes; the list lengths double as per-status counts.
+
+use soroban_sdk::{contracttype, Address, Env, IntoVal, Val, Vec};
+
+use crate::types::CreditStatus;
+use crate::{ttl, DataKey};
+
+/// Maximum number of credit lines a list view returns in one call. Each line costs two
+/// ledger reads, its index entry and the line itself, so a full page fits the read limit.
+pub const MAX_PAGE_SIZE: u32 = 15;
+
+/// Where a borrower sits in the registry lists.
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct RegistryEntry {
+    /// Position in the global borrower list.
+    pub index: u32,
+    pub status: CreditStatus,
+    /// Position in the list for `status`.
+    pub status_index: u32,
+}
+
+pub fn borrower_count(env: &Env) -> u32 {
+    env.storage()
+        .instance()
+        .get(&DataKey::BorrowerCount)
+        .unwrap_or(0)
+}
+
+pub fn status_count(env: &Env, status: CreditStatus) -> u32 {
+    env.storage()
+        .instance()
+        .get(&DataKey::StatusCount(status))
+        .unwrap_or(0)
+}
+
+/// Whether `borrower` has been registered (and counted in the portfolio totals).
+pub fn is_registered(env: &Env, borrower: &Address) -> bool {
+    env.storage()
+        .persistent()
+        .has(&DataKey::RegistryEntry(borrower.clone()))
+}
+
+fn entry(env: &Env, borrower: &Address) -> Option<RegistryEntry> {
+    env.storage()
+        .persistent()
+        .get(&DataKey::RegistryEntry(borrower.clone()))
+}
+
+fn set_persistent<V: IntoVal<Env, Val>>(env: &Env, key: &DataKey, value: &V) {
+    env.storage().persistent().set(key, value);
+    ttl::extend_persistent(env, key);
+}
+
+/// Append `borrower` to the list for `status` and return its position there.
+fn push_status(env: &Env, status: CreditStatus, borrower: &Address) -> u32 {
+    let position = status_count(env, status);
+    set_persistent(env, &DataKey::StatusBorrower(status, position), borrower);
+    env.storage()
+        .instance()
+        .set(&DataKey::StatusCount(status), &(position + 1));
+    position
+}
+
+/// Remove the borrower at `position` from the list for `status`, moving the last
+/// borrower of that list into the gap.
+fn remove_status(env: &Env, status: CreditStatus, position: u32) {
+    let last = status_count(env, status) - 1;
+    let persistent = env.storage().persistent();
+    if position != last {
+        let moved: Address = persistent
+            .get(&DataKey::StatusBorrower(status, last))
+            .expect("registry status list is contiguous");
+        set_persistent(env, &DataKey::StatusBorrower(status, position), &moved);
+        let mut moved_entry = entry(env, &moved).expect("listed borrower has an entry");
+        moved_entry.status_index = position;
+        set_persistent(env, &DataKey::RegistryEntry(moved), &moved_entry);
+    }
+    persistent.remove(&DataKey::StatusBorrower(status, last));
+    env.storage()
+        .instance()
+        .set(&DataKey::StatusCount(status), &last);
+}
+
+/// Record that `borrower`'s line now has `status`, registering the borrower on first sight.
+pub fn record_status(env: &Env, borrower: &Address, status: CreditStatus) {
+    let entry = match entry(env, borrower) {
+        Some(entry) if entry.status == status => return,
+        Some(entry) => {
+            remove_status(env, entry.status, entry.status_index);
+            RegistryEntry {
+                index: entry.index,
+                status,
+                status_index: push_status(env, status, borrower),
+            }
+        }
+        None => {
+            let index = borrower_count(env);
+            set_persistent(env, &DataKey::Borrower(index), borrower);
+            env.storage()
+                .instance()
+                .set(&DataKey::BorrowerCount, &(index + 1));
+            RegistryEntry {
+                index,
+                status,
+                status_index: push_status(env, status, borrower),
+            }
+        }
+    };
+    set_persistent(env, &DataKey::RegistryEntry(borrower.clone()), &entry);
+}
+
+/// Extend the TTL of every registry record belonging to `borrower`.
+pub fn extend(env: &Env, borrower: &Address) {
+    if let Some(entry) = entry(env, borrower) {
+        ttl::extend_persistent(env, &DataKey::RegistryEntry(borrower.clone()));
+        ttl::extend_persistent(env, &DataKey::Borrower(entry.index));
+        ttl::extend_persistent(
+            env,
+            &DataKey::StatusBorrower(entry.status, entry.status_index),
+        );
+    }
+}
+
+/// Borrowers at positions `start..start + limit` of the global list, capped at
+/// `MAX_PAGE_SIZE`.
+pub fn borrowers(env: &Env, start: u32, limit: u32) -> Vec<Address> {
+    page(env, start, limit, borrower_count(env), DataKey::Borrower)
+}
+
+/// Borrowers at positions `cursor..cursor + limit` of the list for `status`, capped at
+/// `MAX_PAGE_SIZE`.
+pub fn borrowers_by_status(
+    env: &Env,
+    status: CreditStatus,
+    cursor: u32,
+    limit: u32,
+) -> Vec<Address> {
+    page(env, cursor, limit, status_count(env, status), |i| {
+        DataKey::StatusBorrower(status, i)
+    })
+}
+
+fn page(env: &Env, start: u32, limit: u32, len: u32, key: impl Fn(u32) -> DataKey) -> Vec<Address> {
+    let end = start.saturating_add(limit.min(MAX_PAGE_SIZE)).min(len);
+    let mut result = Vec::new(env);
+    for i in start..end {
+        if let Some(borrower) = env.storage().persistent().get(&key(i)) {
+            result.push_back(borrower);
+        }
+    }
+    result
+}
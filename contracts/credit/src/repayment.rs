@@ -0,0 +1,44 @@
+//! Repayment waterfall for credit lines.
+//!
+//! A repayment is applied to outstanding fees first, then accrued interest, then
+//! principal (`utilized_amount`). Any amount beyond the total owed is not applied.
+
+use crate::types::CreditLineData;
+
+/// How a repayment was split across the fee, interest and principal buckets.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub struct RepaymentAllocation {
+    pub fees: i128,
+    pub interest: i128,
+    pub principal: i128,
+}
+
+impl RepaymentAllocation {
+    /// Total amount applied to the line.
+    pub fn total(&self) -> i128 {
+        self.fees + self.interest + self.principal
+    }
+}
+
+/// Apply up to `amount` to `line` in waterfall order and return the allocation.
+/// `amount` must be non-negative; the line's balances never go below zero.
+pub fn apply(line: &mut CreditLineData, amount: i128) -> RepaymentAllocation {
+    let mut remaining = amount;
+
+    let fees = remaining.min(line.accrued_fees).max(0);
+    line.accrued_fees -= fees;
+    remaining -= fees;
+
+    let interest = remaining.min(line.accrued_interest).max(0);
+    line.accrued_interest -= interest;
+    remaining -= interest;
+
+    let principal = remaining.min(line.utilized_amount).max(0);
+    line.utilized_amount -= principal;
+
+    RepaymentAllocation {
+        fees,
+        interest,
+        principal,
+    }
+}
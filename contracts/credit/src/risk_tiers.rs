@@ -0,0 +1,90 @@
+//! Risk tiers: admin-configured risk score ranges and the terms lines in them may take.
+//!
+//! Each tier covers an inclusive range of risk scores and caps the credit limit and the
+//! size of a single draw (zero disables a cap). Its `min_rate_bps` is the lowest rate a
+//! line in the tier may be given or priced at, and its `rate_spread_bps` replaces the
+//! linear risk spread of the rate model.
+//! Tiers are identified by their position in the configured list.
+
+use soroban_sdk::{Env, Vec};
+
+use crate::types::RiskTier;
+use crate::{CreditError, DataKey, MAX_INTEREST_RATE_BPS, MAX_RISK_SCORE};
+
+/// Most tiers the admin may configure.
+pub const MAX_TIERS: u32 = 10;
+
+pub fn tiers(env: &Env) -> Vec<RiskTier> {
+    env.storage()
+        .instance()
+        .get(&DataKey::RiskTiers)
+        .unwrap_or(Vec::new(env))
+}
+
+/// Whether `tiers` is usable: at most `MAX_TIERS` tiers, in ascending order of
+/// non-overlapping score ranges within 0..=100, with non-negative caps and rates of at
+/// most 10000 bps.
+pub fn is_valid(tiers: &Vec<RiskTier>) -> bool {
+    if tiers.len() > MAX_TIERS {
+        return false;
+    }
+    let mut next_min = 0;
+    for tier in tiers.iter() {
+        if tier.min_score < next_min
+            || tier.min_score > tier.max_score
+            || tier.max_score > MAX_RISK_SCORE
+            || tier.max_credit_limit < 0
+            || tier.max_draw < 0
+            || tier.min_rate_bps > MAX_INTEREST_RATE_BPS
+            || tier.rate_spread_bps > MAX_INTEREST_RATE_BPS
+        {
+            return false;
+        }
+        next_min = tier.max_score + 1;
+    }
+    true
+}
+
+/// Position and terms of the tier covering `risk_score`, if any.
+pub fn tier_for(env: &Env, risk_score: u32) -> Option<(u32, RiskTier)> {
+    tiers(env)
+        .iter()
+        .enumerate()
+        .find(|(_, tier)| tier.min_score <= risk_score && risk_score <= tier.max_score)
+        .map(|(index, tier)| (index as u32, tier))
+}
+
+/// Check requested terms against the tier of `risk_score`. No-op while no tiers are set.
+///
+/// # Errors
+/// * `NoRiskTier` if no tier covers `risk_score`.
+/// * `RiskTierExceeded` if `credit_limit` is above the tier's cap or `interest_rate_bps`
+///   is below its minimum rate.
+pub fn check_terms(
+    env: &Env,
+    risk_score: u32,
+    credit_limit: i128,
+    interest_rate_bps: u32,
+) -> Result<(), CreditError> {
+    if tiers(env).is_empty() {
+        return Ok(());
+    }
+    let (_, tier) = tier_for(env, risk_score).ok_or(CreditError::NoRiskTier)?;
+    if (tier.max_credit_limit > 0 && credit_limit > tier.max_credit_limit)
+        || interest_rate_bps < tier.min_rate_bps
+    {
+        return Err(CreditError::RiskTierExceeded);
+    }
+    Ok(())
+}
+
+/// Fail with `RiskTierExceeded` if `amount` is above the draw cap of `risk_score`'s tier.
+/// Lines outside every tier, e.g. opened before tiers were set, are not capped.
+pub fn check_draw(env: &Env, risk_score: u32, amount: i128) -> Result<(), CreditError> {
+    match tier_for(env, risk_score) {
+        Some((_, tier)) if tier.max_draw > 0 && amount > tier.max_draw => {
+            Err(CreditError::RiskTierExceeded)
+        }
+        _ => Ok(()),
+    }
+}
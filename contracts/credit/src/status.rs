@@ -0,0 +1,103 @@
+//! Credit line state machine.
+//!
+//! Two tables drive every status check in the contract: the status transitions that are
+//! allowed, and the operations each status permits. Entrypoints consult them through
+//! [`ensure_transition`] and [`ensure_operation`] instead of comparing statuses inline.
+
+use crate::types::CreditStatus;
+use crate::CreditError;
+
+/// Operations on an existing credit line that are gated by its status.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum Operation {
+    Draw,
+    Repay,
+    UpdateRisk,
+    Accrue,
+    /// Replace the line with a fresh one via `open_credit_line`.
+    Reopen,
+    /// Take collateral back via `withdraw_collateral`.
+    WithdrawCollateral,
+    /// Clear what the line owes via `write_off_credit_line`.
+    WriteOff,
+}
+
+/// Allowed `(from, to)` status transitions.
+const TRANSITIONS: [(CreditStatus, CreditStatus); 7] = [
+    (CreditStatus::Active, CreditStatus::Suspended),
+    (CreditStatus::Active, CreditStatus::Defaulted),
+    (CreditStatus::Active, CreditStatus::Closed),
+    (CreditStatus::Suspended, CreditStatus::Active),
+    (CreditStatus::Suspended, CreditStatus::Defaulted),
+    (CreditStatus::Suspended, CreditStatus::Closed),
+    (CreditStatus::Defaulted, CreditStatus::Closed),
+];
+
+/// Operations each status permits.
+const OPERATIONS: [(CreditStatus, &[Operation]); 4] = [
+    (
+        CreditStatus::Active,
+        &[
+            Operation::Draw,
+            Operation::Repay,
+            Operation::UpdateRisk,
+            Operation::Accrue,
+            Operation::WithdrawCollateral,
+        ],
+    ),
+    (
+        CreditStatus::Suspended,
+        &[
+            Operation::Repay,
+            Operation::UpdateRisk,
+            Operation::Accrue,
+            Operation::WithdrawCollateral,
+        ],
+    ),
+    (
+        CreditStatus::Defaulted,
+        &[Operation::Repay, Operation::Accrue, Operation::WriteOff],
+    ),
+    (
+        CreditStatus::Closed,
+        &[
+            Operation::Reopen,
+            Operation::WithdrawCollateral,
+            Operation::WriteOff,
+        ],
+    ),
+];
+
+/// Whether a line may move from `from` to `to`.
+pub fn can_transition(from: CreditStatus, to: CreditStatus) -> bool {
+    TRANSITIONS.contains(&(from, to))
+}
+
+/// Whether a line in `status` permits `operation`.
+pub fn allows(status: CreditStatus, operation: Operation) -> bool {
+    OPERATIONS
+        .iter()
+        .any(|(s, ops)| *s == status && ops.contains(&operation))
+}
+
+/// Fail with `InvalidStatusTransition` unless `from -> to` is allowed.
+pub fn ensure_transition(from: CreditStatus, to: CreditStatus) -> Result<(), CreditError> {
+    if can_transition(from, to) {
+        Ok(())
+    } else {
+        Err(CreditError::InvalidStatusTransition)
+    }
+}
+
+/// Fail with the error describing `status` unless it permits `operation`.
+pub fn ensure_operation(status: CreditStatus, operation: Operation) -> Result<(), CreditError> {
+    if allows(status, operation) {
+        return Ok(());
+    }
+    Err(match status {
+        CreditStatus::Active => CreditError::ActiveCreditLineExists,
+        CreditStatus::Suspended => CreditError::CreditLineSuspended,
+        CreditStatus::Defaulted => CreditError::CreditLineDefaulted,
+        CreditStatus::Closed => CreditError::CreditLineClosed,
+    })
+}
@@ -0,0 +1,166 @@
+//! Amortizing term loans.
+//!
+//! A term loan is repaid in `term_periods` equal installments, one per `period_secs`,
+//! with interest charged each period on the principal still outstanding. The installment
+//! amount is fixed at origination from the standard annuity formula and rounded up; the
+//! last installment settles whatever principal is left. Installments are paid in order,
+//! and the loan can be paid off early at any time.
+
+use soroban_sdk::{Address, Env, Vec};
+
+use crate::types::{Installment, TermLoan};
+use crate::{interest, portfolio, ttl, CreditError, DataKey, MAX_INTEREST_RATE_BPS};
+
+/// Maximum number of installments in a term loan.
+pub const MAX_TERM_PERIODS: u32 = 360;
+
+/// Fixed-point scale for the per-period rate in the annuity formula.
+const SCALE: i128 = 1_000_000_000;
+
+/// Basis-point denominator (10000 = 100%).
+const BPS_DENOMINATOR: i128 = 10_000;
+
+pub fn load(env: &Env, borrower: &Address) -> Option<TermLoan> {
+    let key = DataKey::TermLoan(borrower.clone());
+    let loan = env.storage().persistent().get(&key)?;
+    ttl::extend_persistent(env, &key);
+    Some(loan)
+}
+
+/// Store `loan`, moving the change in its outstanding principal into the portfolio
+/// totals.
+pub fn save(env: &Env, loan: &TermLoan) -> Result<(), CreditError> {
+    let key = DataKey::TermLoan(loan.borrower.clone());
+    let before = env
+        .storage()
+        .persistent()
+        .get::<_, TermLoan>(&key)
+        .map_or(0, |stored| stored.outstanding_principal);
+    portfolio::apply_term_loan(env, before, loan.outstanding_principal)?;
+    env.storage().persistent().set(&key, loan);
+    ttl::extend_persistent(env, &key);
+    Ok(())
+}
+
+/// Extend the TTL of `borrower`'s term loan, if any. Returns whether one exists.
+pub fn extend(env: &Env, borrower: &Address) -> bool {
+    let key = DataKey::TermLoan(borrower.clone());
+    let exists = env.storage().persistent().has(&key);
+    if exists {
+        ttl::extend_persistent(env, &key);
+    }
+    exists
+}
+
+/// Whether the loan terms are in range.
+pub fn is_valid(
+    principal: i128,
+    interest_rate_bps: u32,
+    term_periods: u32,
+    period_secs: u64,
+) -> bool {
+    principal > 0
+        && interest_rate_bps <= MAX_INTEREST_RATE_BPS
+        && term_periods > 0
+        && term_periods <= MAX_TERM_PERIODS
+        && period_secs > 0
+}
+
+/// Equal installment that repays `principal` with interest over `term_periods`, rounded
+/// up: `P * r / (1 - (1 + r)^-n)`, or `P / n` without interest.
+pub fn installment_amount(
+    principal: i128,
+    interest_rate_bps: u32,
+    term_periods: u32,
+    period_secs: u64,
+) -> Result<i128, CreditError> {
+    let periods = term_periods as i128;
+    // Per-period rate, scaled by SCALE.
+    let rate = (interest_rate_bps as i128)
+        .checked_mul(period_secs as i128)
+        .and_then(|v| v.checked_mul(SCALE))
+        .map(|v| v / (BPS_DENOMINATOR * interest::SECONDS_PER_YEAR as i128))
+        .ok_or(CreditError::Overflow)?;
+    if rate == 0 {
+        return principal
+            .checked_add(periods - 1)
+            .map(|v| v / periods)
+            .ok_or(CreditError::Overflow);
+    }
+    // (1 + r)^n, scaled by SCALE.
+    let mut growth = SCALE;
+    for _ in 0..term_periods {
+        growth = growth
+            .checked_mul(SCALE + rate)
+            .map(|v| v / SCALE)
+            .ok_or(CreditError::Overflow)?;
+    }
+    let factor = rate
+        .checked_mul(growth)
+        .map(|v| v / (growth - SCALE))
+        .ok_or(CreditError::Overflow)?;
+    principal
+        .checked_mul(factor)
+        .map(|v| (v + SCALE - 1) / SCALE)
+        .ok_or(CreditError::Overflow)
+}
+
+/// When the first `installments` installments of `loan` have all fallen due.
+pub fn due_ts(loan: &TermLoan, installments: u32) -> Result<u64, CreditError> {
+    loan.period_secs
+        .checked_mul(installments as u64)
+        .and_then(|v| v.checked_add(loan.start_ts))
+        .ok_or(CreditError::Overflow)
+}
+
+/// Installment `number` (0-based) of `loan`, given the principal outstanding before it.
+fn installment(
+    loan: &TermLoan,
+    number: u32,
+    outstanding: i128,
+) -> Result<Installment, CreditError> {
+    let interest = interest::interest_for(outstanding, loan.interest_rate_bps, loan.period_secs)?;
+    let principal = if number + 1 == loan.term_periods {
+        outstanding
+    } else {
+        (loan.installment - interest).clamp(0, outstanding)
+    };
+    Ok(Installment {
+        number,
+        due_ts: due_ts(loan, number + 1)?,
+        payment: principal
+            .checked_add(interest)
+            .ok_or(CreditError::Overflow)?,
+        principal,
+        interest,
+        remaining_principal: outstanding - principal,
+    })
+}
+
+/// The next unpaid installment, or `None` once the loan is repaid.
+pub fn next_installment(loan: &TermLoan) -> Result<Option<Installment>, CreditError> {
+    if loan.outstanding_principal == 0 {
+        return Ok(None);
+    }
+    installment(loan, loan.installments_paid, loan.outstanding_principal).map(Some)
+}
+
+/// Every installment of `loan` from origination, including those already paid.
+pub fn schedule(env: &Env, loan: &TermLoan) -> Result<Vec<Installment>, CreditError> {
+    let mut installments = Vec::new(env);
+    let mut outstanding = loan.principal;
+    for number in 0..loan.term_periods {
+        let next = installment(loan, number, outstanding)?;
+        outstanding = next.remaining_principal;
+        installments.push_back(next);
+    }
+    Ok(installments)
+}
+
+/// Interest owed to pay the loan off at `now`: interest on the outstanding principal
+/// since the last paid installment's due date (or origination), including every period
+/// missed since.
+pub fn payoff_interest(loan: &TermLoan, now: u64) -> Result<i128, CreditError> {
+    let elapsed = now.saturating_sub(due_ts(loan, loan.installments_paid)?);
+    interest::interest_for(loan.outstanding_principal, loan.interest_rate_bps, elapsed)
+}
@@ -0,0 +1,58 @@
+//! Storage TTL management.
+//!
+//! Credit lines live in persistent storage and would be archived once their TTL runs out,
+//! hiding the debt of idle borrowers. Every read or write of a line, and every call that
+//! touches instance config, extends the relevant TTL according to the stored `TtlConfig`.
+
+use soroban_sdk::{Env, IntoVal, Val};
+
+use crate::types::TtlConfig;
+use crate::DataKey;
+
+/// Ledgers per day at ~5s close time.
+const DAY_IN_LEDGERS: u32 = 17_280;
+
+/// TTL settings used until the admin stores a `TtlConfig`.
+pub const DEFAULT_TTL_CONFIG: TtlConfig = TtlConfig {
+    instance_threshold: 7 * DAY_IN_LEDGERS,
+    instance_extend_to: 30 * DAY_IN_LEDGERS,
+    persistent_threshold: 30 * DAY_IN_LEDGERS,
+    persistent_extend_to: 120 * DAY_IN_LEDGERS,
+};
+
+pub fn config(env: &Env) -> TtlConfig {
+    env.storage()
+        .instance()
+        .get(&DataKey::TtlConfig)
+        .unwrap_or(DEFAULT_TTL_CONFIG)
+}
+
+/// Whether `config` is internally consistent and within the network's maximum TTL.
+pub fn is_valid(env: &Env, config: &TtlConfig) -> bool {
+    let max_ttl = env.storage().max_ttl();
+    config.instance_threshold <= config.instance_extend_to
+        && config.persistent_threshold <= config.persistent_extend_to
+        && config.instance_extend_to <= max_ttl
+        && config.persistent_extend_to <= max_ttl
+}
+
+/// Extend the contract instance (and with it all instance config) if it is close to expiring.
+pub fn extend_instance(env: &Env) {
+    let config = config(env);
+    env.storage()
+        .instance()
+        .extend_ttl(config.instance_threshold, config.instance_extend_to);
+}
+
+/// Extend the persistent entry under `key`, which must exist, along with the instance.
+pub fn extend_persistent<K: IntoVal<Env, Val>>(env: &Env, key: &K) {
+    let config = config(env);
+    env.storage().persistent().extend_ttl(
+        key,
+        config.persistent_threshold,
+        config.persistent_extend_to,
+    );
+    env.storage()
+        .instance()
+        .extend_ttl(config.instance_threshold, config.instance_extend_to);
+}
@@ -11,13 +11,309 @@ pub enum CreditStatus {
     Closed = 3,
 }
 
+/// Access-control roles granted by the admin, distinct from the admin itself.
+#[contracttype]
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum Role {
+    /// May open credit lines and update their risk parameters.
+    RiskEngine = 0,
+    /// May pause and unpause the contract in an emergency.
+    Guardian = 1,
+}
+
+/// Risk parameters the risk engine or admin can set on a credit line.
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct RiskParameters {
+    pub credit_limit: i128,
+    pub interest_rate_bps: u32,
+    pub risk_score: u32,
+}
+
+/// TTL thresholds and extension targets, in ledgers. An entry whose TTL drops below the
+/// threshold is extended to the target on the next read or write.
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct TtlConfig {
+    pub instance_threshold: u32,
+    pub instance_extend_to: u32,
+    pub persistent_threshold: u32,
+    pub persistent_extend_to: u32,
+}
+
+/// Per-asset liquidity and risk settings for lines opened with `open_asset_credit_line`.
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct AssetConfig {
+    /// Address that funds draws and receives repayments in this asset.
+    pub liquidity_source: Address,
+    /// Maximum credit limit of a line in this asset; 0 disables the cap.
+    pub max_borrower_limit: i128,
+    /// Maximum principal outstanding across lines in this asset; 0 disables the cap.
+    pub max_outstanding: i128,
+    /// Allowed interest rate range for lines in this asset.
+    pub min_interest_rate_bps: u32,
+    pub max_interest_rate_bps: u32,
+}
+
+/// Fee schedule set by the admin. Fees are added to the line's `accrued_fees`.
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct FeeConfig {
+    /// Address that receives the fee share of repayments.
+    pub fee_collector: Address,
+    /// One-time fee on opening a line, in basis points of its credit limit.
+    pub origination_fee_bps: u32,
+    /// Fee on each draw, in basis points of the drawn amount.
+    pub draw_fee_bps: u32,
+    /// Flat fee charged for each missed payment.
+    pub late_fee: i128,
+}
+
+/// Billing cycle settings set by the admin.
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct BillingConfig {
+    /// Length of a billing cycle in seconds.
+    pub period_secs: u64,
+    /// Time from a statement's close to its payment due date; at most `period_secs`.
+    pub due_after_secs: u64,
+    /// Minimum payment as basis points of the statement balance.
+    pub min_payment_bps: u32,
+    /// Minimum payment floor, applied when the percentage is smaller.
+    pub min_payment_floor: i128,
+}
+
+/// A line's billing state, returned by `get_statement`.
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Statement {
+    /// Start of the cycle in progress.
+    pub cycle_start: u64,
+    /// When the latest statement closed; 0 until the first cycle ends.
+    pub closed_at: u64,
+    /// Principal, interest and fees owed when the latest statement closed.
+    pub statement_balance: i128,
+    /// Minimum payment still owed for the latest statement, including any unpaid minimum
+    /// carried over from earlier statements.
+    pub minimum_due: i128,
+    /// When `minimum_due` must be paid by; 0 until the first cycle ends.
+    pub due_ts: u64,
+    /// Whether the late fee for missing this statement's minimum has been charged.
+    pub late_fee_charged: bool,
+}
+
+/// How far a line is behind on its minimum payments.
+#[contracttype]
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum DelinquencyBucket {
+    /// No minimum payment is past due.
+    Current = 0,
+    /// 1 to 29 days past due.
+    PastDue = 1,
+    Days30 = 2,
+    Days60 = 3,
+    /// 90 or more days past due.
+    Days90 = 4,
+}
+
+/// A line's delinquency, returned by `get_delinquency` and `process_delinquency`.
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Delinquency {
+    pub bucket: DelinquencyBucket,
+    /// Days since `past_due_since`, rounded up.
+    pub days_past_due: u32,
+    /// Due date of the oldest unpaid minimum payment; 0 while current.
+    pub past_due_since: u64,
+}
+
+/// Admin-configured exposure caps. Zero disables a cap.
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct ExposureCaps {
+    /// Maximum total principal outstanding, in basis points of the liquidity source's
+    /// token balance (may exceed 10000).
+    pub max_outstanding_reserve_bps: u32,
+    /// Maximum credit limit of any single line.
+    pub max_borrower_limit: i128,
+    /// Maximum sum of credit limits over lines that are not Closed.
+    pub max_total_committed_limit: i128,
+}
+
+/// Protocol-wide totals over all credit lines, returned by `get_portfolio_stats`.
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct PortfolioStats {
+    /// Sum of credit limits of lines that are not Closed.
+    pub total_credit_limit: i128,
+    pub total_utilized: i128,
+    /// Interest accrued onto lines and not yet repaid; excludes interest pending since
+    /// each line's last accrual.
+    pub total_accrued_interest: i128,
+    pub total_accrued_fees: i128,
+    /// Principal outstanding on term loans; not part of `total_utilized`.
+    pub total_term_principal: i128,
+    pub active_count: u32,
+    pub suspended_count: u32,
+    pub defaulted_count: u32,
+    pub closed_count: u32,
+}
+
+/// Fixed-term installment loan of a borrower; see the `term_loan` module.
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct TermLoan {
+    pub borrower: Address,
+    /// Amount disbursed at origination.
+    pub principal: i128,
+    /// Principal not yet repaid; 0 once the loan is repaid.
+    pub outstanding_principal: i128,
+    /// Annual interest rate in basis points.
+    pub interest_rate_bps: u32,
+    /// Number of installments.
+    pub term_periods: u32,
+    /// Seconds between installments.
+    pub period_secs: u64,
+    /// Equal installment amount; the last one may differ.
+    pub installment: i128,
+    pub installments_paid: u32,
+    /// Origination timestamp; installment `n` (0-based) is due `n + 1` periods later.
+    pub start_ts: u64,
+}
+
+/// One installment of a term loan's amortization schedule.
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Installment {
+    /// 0-based position in the schedule.
+    pub number: u32,
+    pub due_ts: u64,
+    /// `principal + interest`.
+    pub payment: i128,
+    pub principal: i128,
+    pub interest: i128,
+    /// Principal outstanding after this installment.
+    pub remaining_principal: i128,
+}
+
+/// Valuation settings of a collateral token, set by the admin.
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct CollateralConfig {
+    /// Liquidity token units per collateral unit, scaled by `collateral::PRICE_SCALE`. Used
+    /// while the token has no `OracleConfig`.
+    pub price: i128,
+    /// Minimum collateral value per unit of debt, in basis points (at least 10000).
+    pub min_collateral_ratio_bps: u32,
+    /// Collateral value per unit of debt below which a secured line can be liquidated, in
+    /// basis points: at least 10000 and at most `min_collateral_ratio_bps`, so a line drawn
+    /// to its full borrowing power has room to accrue interest.
+    pub liquidation_ratio_bps: u32,
+}
+
+/// Price feed of a collateral token, set by the admin.
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct OracleConfig {
+    /// SEP-40 price feed contract quoting the token in liquidity token units.
+    pub oracle: Address,
+    /// Oldest price accepted, in seconds before the current ledger.
+    pub max_age_secs: u64,
+    /// Largest accepted move from the feed's previous price, in basis points; 0 disables
+    /// the check.
+    pub max_deviation_bps: u32,
+}
+
+/// A borrower's collateral against their credit line debt, in liquidity token units.
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct CollateralPosition {
+    pub collateral_value: i128,
+    /// Debt the collateral can back: each token's value divided by its minimum ratio.
+    pub borrowing_power: i128,
+    /// Debt above which a secured line can be liquidated: each token's value divided by
+    /// its liquidation ratio.
+    pub liquidation_threshold: i128,
+    /// Principal, interest and fees owed on the line.
+    pub debt: i128,
+}
+
+/// Liquidation settings set by the admin; see `liquidation::DEFAULT_LIQUIDATION_CONFIG`.
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct LiquidationConfig {
+    /// Share of a line's debt one liquidation may repay, in basis points (1 ..= 10000).
+    pub close_factor_bps: u32,
+    /// Discount on seized collateral, in basis points (at most 5000).
+    pub discount_bps: u32,
+}
+
+/// Terms available to lines whose risk score falls in a range; see the `risk_tiers` module.
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct RiskTier {
+    /// Lowest risk score in the tier, inclusive.
+    pub min_score: u32,
+    /// Highest risk score in the tier, inclusive.
+    pub max_score: u32,
+    /// Largest credit limit a line in the tier may have; 0 disables the cap.
+    pub max_credit_limit: i128,
+    /// Lowest rate a line in the tier may be given or priced at, in basis points.
+    pub min_rate_bps: u32,
+    /// Risk spread added to the rate model's curve for lines in the tier, in basis points.
+    pub rate_spread_bps: u32,
+    /// Largest single draw; 0 disables the cap.
+    pub max_draw: i128,
+}
+
+/// Utilization-based rate model set by the admin; see the `rate_model` module.
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct RateModel {
+    /// Rate at zero utilization, in basis points.
+    pub base_rate_bps: u32,
+    /// Rate added between zero and the optimal utilization.
+    pub slope_low_bps: u32,
+    /// Rate added between the optimal and full utilization.
+    pub slope_high_bps: u32,
+    /// Utilization at the kink, in basis points (1 ..= 10000).
+    pub optimal_utilization_bps: u32,
+    /// Spread at the maximum risk score, scaled linearly by `risk_score`.
+    pub risk_spread_bps: u32,
+}
+
+/// How a line's effective rate is computed, in basis points.
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct RateBreakdown {
+    /// Share of the line's liquidity source lent out.
+    pub utilization_bps: u32,
+    pub base_rate_bps: u32,
+    /// Part of the rate from the utilization curve.
+    pub utilization_rate_bps: u32,
+    /// Part of the rate from the line's risk score.
+    pub risk_spread_bps: u32,
+    /// Sum of the parts, capped at 10000.
+    pub rate_bps: u32,
+}
+
 /// Stored credit line for a borrower.
 #[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
 pub struct CreditLineData {
     pub borrower: Address,
+    /// Token the line is drawn and repaid in; `None` uses the global liquidity token.
+    pub asset: Option<Address>,
     pub credit_limit: i128,
     pub utilized_amount: i128,
     pub interest_rate_bps: u32,
     pub risk_score: u32,
     pub status: CreditStatus,
+    /// Interest accrued and not yet repaid (tracked separately from principal).
+    pub accrued_interest: i128,
+    /// Fees charged to the line and not yet repaid.
+    pub accrued_fees: i128,
+    /// Ledger timestamp up to which interest has been accrued.
+    pub last_accrual_ts: u64,
 }
@@ -0,0 +1,42 @@
+use soroban_sdk::{contracttype, symbol_short, Address, Env};
+
+/// Event emitted when a lender deposits into or withdraws from the pool.
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct LiquidityEvent {
+    pub lender: Address,
+    /// Liquidity tokens deposited or withdrawn.
+    pub amount: i128,
+    /// Shares minted or burned.
+    pub shares: i128,
+    /// The lender's shares afterwards.
+    pub balance: i128,
+}
+
+/// Event emitted when the credit contract writes off principal the pool lent.
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct LossEvent {
+    /// Principal written off.
+    pub principal: i128,
+    /// Principal still lent out afterwards.
+    pub borrowed: i128,
+}
+
+/// Publish a deposit event.
+pub fn publish_deposit(env: &Env, event: LiquidityEvent) {
+    env.events()
+        .publish((symbol_short!("pool"), symbol_short!("deposit")), event);
+}
+
+/// Publish a withdrawal event.
+pub fn publish_withdraw(env: &Env, event: LiquidityEvent) {
+    env.events()
+        .publish((symbol_short!("pool"), symbol_short!("withdraw")), event);
+}
+
+/// Publish a loss event.
+pub fn publish_loss(env: &Env, event: LossEvent) {
+    env.events()
+        .publish((symbol_short!("pool"), symbol_short!("loss")), event);
+}
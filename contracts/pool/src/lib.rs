@@ -0,0 +1,631 @@
+#![no_std]
+
+//! Creditra lending pool: lender deposits backing the credit contract's draws.
+//!
+//! # Shares
+//! Lenders deposit the pool's liquidity token and receive shares of the pool's assets: the
+//! tokens it holds plus principal lent out through the credit contract. Interest and fees
+//! repaid into the pool raise the value of every share, so a withdrawal returns the
+//! lender's pro-rata part of the principal and the interest earned on it. Shares are
+//! recorded by the pool and are not transferable.
+//!
+//! The pool counts only tokens it has accounted for (deposits and reported repayments, less
+//! withdrawals and loans), never its token balance, so tokens sent to it directly cannot
+//! move the share price. Share prices are also computed with one virtual share and one
+//! virtual token, which keeps a first depositor from inflating the price of a share to
+//! round later deposits down to nothing.
+//!
+//! # Credit contract
+//! Only the credit contract set at `init` may `borrow`, which pays tokens out of the pool
+//! to a borrower. Repayments are transferred straight to the pool's address; the credit
+//! contract then reports the amount paid and its principal part with `record_repayment`.
+//! Principal it writes off is reported with `record_loss`, which lowers the share price
+//! for every lender at once instead of leaving the loss to whoever withdraws last.
+//!
+//! # Errors
+//! Every entrypoint returns `Result<_, PoolError>` with the stable codes of [`PoolError`].
+
+mod events;
+
+use soroban_sdk::{contract, contracterror, contractimpl, contracttype, token, Address, Env};
+
+use events::{publish_deposit, publish_loss, publish_withdraw, LiquidityEvent, LossEvent};
+
+/// Ledgers per day at ~5s close time.
+const DAY_IN_LEDGERS: u32 = 17_280;
+const INSTANCE_THRESHOLD: u32 = 7 * DAY_IN_LEDGERS;
+const INSTANCE_EXTEND_TO: u32 = 30 * DAY_IN_LEDGERS;
+const PERSISTENT_THRESHOLD: u32 = 30 * DAY_IN_LEDGERS;
+const PERSISTENT_EXTEND_TO: u32 = 120 * DAY_IN_LEDGERS;
+
+/// Stable error codes of the pool contract.
+#[contracterror]
+#[derive(Copy, Clone, Debug, Eq, PartialEq, PartialOrd, Ord)]
+#[repr(u32)]
+pub enum PoolError {
+    NotInitialized = 1,
+    AlreadyInitialized = 2,
+    /// Amount or number of shares is not positive.
+    InvalidAmount = 3,
+    /// The lender holds fewer shares than they tried to redeem.
+    InsufficientShares = 4,
+    /// The pool holds fewer tokens than requested; the rest is lent out.
+    InsufficientLiquidity = 5,
+    /// The deposit is too small to mint a share at the current share price.
+    ZeroShares = 6,
+    Overflow = 7,
+}
+
+/// Every key the pool stores.
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum DataKey {
+    Admin,
+    /// Liquidity token lent by the pool.
+    Token,
+    /// Credit contract allowed to borrow from the pool.
+    Credit,
+    /// Shares issued to all lenders.
+    TotalShares,
+    /// Principal lent out and not yet repaid.
+    Borrowed,
+    /// Tokens the pool has accounted for and holds.
+    Cash,
+    /// Persistent share balance of a lender.
+    Shares(Address),
+}
+
+fn extend_instance(env: &Env) {
+    env.storage()
+        .instance()
+        .extend_ttl(INSTANCE_THRESHOLD, INSTANCE_EXTEND_TO);
+}
+
+fn read_token(env: &Env) -> Result<Address, PoolError> {
+    let token = env
+        .storage()
+        .instance()
+        .get(&DataKey::Token)
+        .ok_or(PoolError::NotInitialized)?;
+    extend_instance(env);
+    Ok(token)
+}
+
+fn require_credit_auth(env: &Env) -> Result<(), PoolError> {
+    let credit: Address = env
+        .storage()
+        .instance()
+        .get(&DataKey::Credit)
+        .ok_or(PoolError::NotInitialized)?;
+    credit.require_auth();
+    Ok(())
+}
+
+fn read_i128(env: &Env, key: &DataKey) -> i128 {
+    env.storage().instance().get(key).unwrap_or(0)
+}
+
+fn read_shares(env: &Env, lender: &Address) -> i128 {
+    env.storage()
+        .persistent()
+        .get(&DataKey::Shares(lender.clone()))
+        .unwrap_or(0)
+}
+
+fn write_shares(env: &Env, lender: &Address, shares: i128) {
+    let key = DataKey::Shares(lender.clone());
+    if shares == 0 {
+        env.storage().persistent().remove(&key);
+        return;
+    }
+    env.storage().persistent().set(&key, &shares);
+    env.storage()
+        .persistent()
+        .extend_ttl(&key, PERSISTENT_THRESHOLD, PERSISTENT_EXTEND_TO);
+}
+
+/// Virtual shares and tokens added to the totals when pricing shares.
+const VIRTUAL_SHARES: i128 = 1;
+const VIRTUAL_ASSETS: i128 = 1;
+
+/// Tokens the pool holds, as accounted for.
+fn cash(env: &Env) -> i128 {
+    read_i128(env, &DataKey::Cash)
+}
+
+fn write_cash(env: &Env, cash: i128) {
+    env.storage().instance().set(&DataKey::Cash, &cash);
+}
+
+/// Tokens held plus principal lent out.
+fn total_assets(env: &Env) -> Result<i128, PoolError> {
+    cash(env)
+        .checked_add(read_i128(env, &DataKey::Borrowed))
+        .ok_or(PoolError::Overflow)
+}
+
+/// `amount * numerator / denominator`, rounded down.
+fn mul_div(amount: i128, numerator: i128, denominator: i128) -> Result<i128, PoolError> {
+    amount
+        .checked_mul(numerator)
+        .map(|v| v / denominator)
+        .ok_or(PoolError::Overflow)
+}
+
+/// Shares and assets to price shares against, including the virtual ones.
+fn share_price(env: &Env) -> Result<(i128, i128), PoolError> {
+    let shares = read_i128(env, &DataKey::TotalShares)
+        .checked_add(VIRTUAL_SHARES)
+        .ok_or(PoolError::Overflow)?;
+    let assets = total_assets(env)?
+        .checked_add(VIRTUAL_ASSETS)
+        .ok_or(PoolError::Overflow)?;
+    Ok((shares, assets))
+}
+
+#[contract]
+pub struct Pool;
+
+#[contractimpl]
+impl Pool {
+    /// @notice Sets the admin, the liquidity token and the credit contract allowed to borrow.
+    /// @dev Fails with `AlreadyInitialized` on a second call.
+    pub fn init(
+        env: Env,
+        admin: Address,
+        token: Address,
+        credit: Address,
+    ) -> Result<(), PoolError> {
+        let instance = env.storage().instance();
+        if instance.has(&DataKey::Admin) {
+            return Err(PoolError::AlreadyInitialized);
+        }
+        admin.require_auth();
+        instance.set(&DataKey::Admin, &admin);
+        instance.set(&DataKey::Token, &token);
+        instance.set(&DataKey::Credit, &credit);
+        extend_instance(&env);
+        Ok(())
+    }
+
+    /// @notice Replaces the credit contract allowed to borrow.
+    /// @dev Admin-only.
+    pub fn set_credit(env: Env, credit: Address) -> Result<(), PoolError> {
+        let admin: Address = env
+            .storage()
+            .instance()
+            .get(&DataKey::Admin)
+            .ok_or(PoolError::NotInitialized)?;
+        admin.require_auth();
+        env.storage().instance().set(&DataKey::Credit, &credit);
+        extend_instance(&env);
+        Ok(())
+    }
+
+    /// Deposit `amount` of the liquidity token and receive shares at the current share
+    /// price, rounded down (lender). The first deposit mints one share per token.
+    /// Returns the shares minted.
+    ///
+    /// # Errors
+    /// * `InvalidAmount` if `amount` <= 0.
+    /// * `ZeroShares` if `amount` is worth less than one share.
+    /// * `NotInitialized`, `Overflow`.
+    ///
+    /// Emits `(pool, deposit)` with a `LiquidityEvent`.
+    pub fn deposit(env: Env, lender: Address, amount: i128) -> Result<i128, PoolError> {
+        lender.require_auth();
+        if amount <= 0 {
+            return Err(PoolError::InvalidAmount);
+        }
+        let token = read_token(&env)?;
+        let (price_shares, price_assets) = share_price(&env)?;
+        let shares = mul_div(amount, price_shares, price_assets)?;
+        if shares == 0 {
+            return Err(PoolError::ZeroShares);
+        }
+
+        token::Client::new(&env, &token).transfer(
+            &lender,
+            &env.current_contract_address(),
+            &amount,
+        );
+        let balance = read_shares(&env, &lender)
+            .checked_add(shares)
+            .ok_or(PoolError::Overflow)?;
+        write_shares(&env, &lender, balance);
+        let total_shares = read_i128(&env, &DataKey::TotalShares)
+            .checked_add(shares)
+            .ok_or(PoolError::Overflow)?;
+        env.storage()
+            .instance()
+            .set(&DataKey::TotalShares, &total_shares);
+        write_cash(
+            &env,
+            cash(&env).checked_add(amount).ok_or(PoolError::Overflow)?,
+        );
+        publish_deposit(
+            &env,
+            LiquidityEvent {
+                lender,
+                amount,
+                shares,
+                balance,
+            },
+        );
+        Ok(shares)
+    }
+
+    /// Redeem `shares` for their part of the pool's assets, rounded down (lender).
+    /// Returns the tokens withdrawn.
+    ///
+    /// # Errors
+    /// * `InvalidAmount` if `shares` <= 0.
+    /// * `InsufficientShares` if the lender holds fewer shares.
+    /// * `InsufficientLiquidity` if the pool holds fewer tokens than the shares are worth.
+    /// * `NotInitialized`, `Overflow`.
+    ///
+    /// Emits `(pool, withdraw)` with a `LiquidityEvent`.
+    pub fn withdraw(env: Env, lender: Address, shares: i128) -> Result<i128, PoolError> {
+        lender.require_auth();
+        if shares <= 0 {
+            return Err(PoolError::InvalidAmount);
+        }
+        let token = read_token(&env)?;
+        let held = read_shares(&env, &lender);
+        if held < shares {
+            return Err(PoolError::InsufficientShares);
+        }
+        let (price_shares, price_assets) = share_price(&env)?;
+        let amount = mul_div(shares, price_assets, price_shares)?;
+        let cash = cash(&env);
+        if amount > cash {
+            return Err(PoolError::InsufficientLiquidity);
+        }
+
+        let balance = held - shares;
+        write_shares(&env, &lender, balance);
+        let total_shares = read_i128(&env, &DataKey::TotalShares);
+        env.storage()
+            .instance()
+            .set(&DataKey::TotalShares, &(total_shares - shares));
+        write_cash(&env, cash - amount);
+        token::Client::new(&env, &token).transfer(
+            &env.current_contract_address(),
+            &lender,
+            &amount,
+        );
+        publish_withdraw(
+            &env,
+            LiquidityEvent {
+                lender,
+                amount,
+                shares,
+                balance,
+            },
+        );
+        Ok(amount)
+    }
+
+    /// Lend `amount` of the pool's tokens to `recipient` (credit contract only).
+    ///
+    /// # Errors
+    /// * `InvalidAmount` if `amount` <= 0.
+    /// * `InsufficientLiquidity` if the pool holds fewer tokens.
+    /// * `NotInitialized`, `Overflow`.
+    pub fn borrow(env: Env, recipient: Address, amount: i128) -> Result<(), PoolError> {
+        require_credit_auth(&env)?;
+        if amount <= 0 {
+            return Err(PoolError::InvalidAmount);
+        }
+        let token = read_token(&env)?;
+        let cash = cash(&env);
+        if amount > cash {
+            return Err(PoolError::InsufficientLiquidity);
+        }
+        let borrowed = read_i128(&env, &DataKey::Borrowed)
+            .checked_add(amount)
+            .ok_or(PoolError::Overflow)?;
+        env.storage().instance().set(&DataKey::Borrowed, &borrowed);
+        write_cash(&env, cash - amount);
+        token::Client::new(&env, &token).transfer(
+            &env.current_contract_address(),
+            &recipient,
+            &amount,
+        );
+        Ok(())
+    }
+
+    /// Record that `amount` has been paid to the pool's address, of which `principal`
+    /// repays principal lent out and the rest is interest and fees (credit contract only).
+    /// Principal above what is lent out is ignored.
+    ///
+    /// # Errors
+    /// * `InvalidAmount` if `principal` < 0 or `amount` < `principal`.
+    /// * `NotInitialized`, `Overflow`.
+    pub fn record_repayment(env: Env, principal: i128, amount: i128) -> Result<(), PoolError> {
+        require_credit_auth(&env)?;
+        if principal < 0 || amount < principal {
+            return Err(PoolError::InvalidAmount);
+        }
+        let borrowed = read_i128(&env, &DataKey::Borrowed);
+        env.storage()
+            .instance()
+            .set(&DataKey::Borrowed, &(borrowed - principal.min(borrowed)));
+        write_cash(
+            &env,
+            cash(&env).checked_add(amount).ok_or(PoolError::Overflow)?,
+        );
+        extend_instance(&env);
+        Ok(())
+    }
+
+    /// Record that `principal` lent out will not be repaid (credit contract only). The
+    /// pool's assets drop by the principal written off, up to what is lent out.
+    ///
+    /// # Errors
+    /// * `InvalidAmount` if `principal` < 0.
+    /// * `NotInitialized`.
+    ///
+    /// Emits `(pool, loss)` with a `LossEvent`.
+    pub fn record_loss(env: Env, principal: i128) -> Result<(), PoolError> {
+        require_credit_auth(&env)?;
+        if principal < 0 {
+            return Err(PoolError::InvalidAmount);
+        }
+        let borrowed = read_i128(&env, &DataKey::Borrowed);
+        let principal = principal.min(borrowed);
+        let borrowed = borrowed - principal;
+        env.storage().instance().set(&DataKey::Borrowed, &borrowed);
+        extend_instance(&env);
+        publish_loss(
+            &env,
+            LossEvent {
+                principal,
+                borrowed,
+            },
+        );
+        Ok(())
+    }
+
+    /// @notice Returns the pool's liquidity token.
+    pub fn token(env: Env) -> Result<Address, PoolError> {
+        read_token(&env)
+    }
+
+    /// @notice Returns the tokens the pool holds and can lend or pay out.
+    pub fn available_liquidity(env: Env) -> i128 {
+        cash(&env)
+    }
+
+    /// @notice Returns the principal lent out and not yet repaid.
+    pub fn borrowed(env: Env) -> i128 {
+        read_i128(&env, &DataKey::Borrowed)
+    }
+
+    /// @notice Returns the pool's assets: tokens held plus principal lent out.
+    pub fn total_assets(env: Env) -> Result<i128, PoolError> {
+        total_assets(&env)
+    }
+
+    /// @notice Returns the shares issued to all lenders.
+    pub fn total_shares(env: Env) -> i128 {
+        read_i128(&env, &DataKey::TotalShares)
+    }
+
+    /// @notice Returns the shares held by `lender`.
+    pub fn shares(env: Env, lender: Address) -> i128 {
+        read_shares(&env, &lender)
+    }
+
+    /// @notice Returns the tokens `shares` would withdraw at the current share price.
+    pub fn preview_withdraw(env: Env, shares: i128) -> Result<i128, PoolError> {
+        let (price_shares, price_assets) = share_price(&env)?;
+        mul_div(shares, price_assets, price_shares)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use soroban_sdk::testutils::Address as _;
+    use soroban_sdk::testutils::Events;
+    use soroban_sdk::token::StellarAssetClient;
+    use soroban_sdk::{symbol_short, IntoVal, TryFromVal};
+
+    struct Setup<'a> {
+        pool: PoolClient<'a>,
+        token: token::Client<'a>,
+        credit: Address,
+    }
+
+    /// Initialized pool whose credit contract is a plain address, and `lenders` each
+    /// holding 10_000 of the liquidity token.
+    fn setup<'a>(env: &'a Env, lenders: &[&Address]) -> Setup<'a> {
+        env.mock_all_auths();
+        let token = env
+            .register_stellar_asset_contract_v2(Address::generate(env))
+            .address();
+        let credit = Address::generate(env);
+        let pool = PoolClient::new(env, &env.register(Pool, ()));
+        pool.init(&Address::generate(env), &token, &credit);
+        for lender in lenders {
+            StellarAssetClient::new(env, &token).mint(lender, &10_000);
+        }
+        Setup {
+            pool,
+            token: token::Client::new(env, &token),
+            credit,
+        }
+    }
+
+    /// Repay `principal` plus `interest` into the pool the way the credit contract does.
+    fn repay(env: &Env, setup: &Setup, principal: i128, interest: i128) {
+        StellarAssetClient::new(env, &setup.token.address)
+            .mint(&setup.pool.address, &(principal + interest));
+        setup
+            .pool
+            .record_repayment(&principal, &(principal + interest));
+    }
+
+    #[test]
+    fn test_deposit_mints_shares_at_share_price() {
+        let env = Env::default();
+        let (alice, bob) = (Address::generate(&env), Address::generate(&env));
+        let setup = setup(&env, &[&alice, &bob]);
+
+        assert_eq!(setup.pool.deposit(&alice, &1_000_i128), 1_000);
+        let event = env.events().all().last().unwrap();
+        assert_eq!(
+            event.1,
+            (symbol_short!("pool"), symbol_short!("deposit")).into_val(&env)
+        );
+        let payload = LiquidityEvent::try_from_val(&env, &event.2).unwrap();
+        assert_eq!(payload.shares, 1_000);
+        assert_eq!(payload.balance, 1_000);
+
+        // 100 of interest makes each share worth 1.1.
+        setup.pool.borrow(&Address::generate(&env), &500_i128);
+        repay(&env, &setup, 500, 100);
+        assert_eq!(setup.pool.deposit(&bob, &1_100_i128), 1_000);
+        assert_eq!(setup.pool.total_shares(), 2_000);
+        assert_eq!(setup.pool.total_assets(), 2_200);
+        assert_eq!(setup.token.balance(&setup.pool.address), 2_200);
+    }
+
+    #[test]
+    fn test_withdraw_includes_interest() {
+        let env = Env::default();
+        let (alice, bob) = (Address::generate(&env), Address::generate(&env));
+        let setup = setup(&env, &[&alice, &bob]);
+        setup.pool.deposit(&alice, &3_000_i128);
+        setup.pool.deposit(&bob, &1_000_i128);
+
+        let borrower = Address::generate(&env);
+        setup.pool.borrow(&borrower, &2_000_i128);
+        assert_eq!(setup.pool.borrowed(), 2_000);
+        assert_eq!(setup.pool.available_liquidity(), 2_000);
+        assert_eq!(setup.pool.total_assets(), 4_000);
+        repay(&env, &setup, 2_000, 400);
+        assert_eq!(setup.pool.borrowed(), 0);
+
+        // Share price (4_400 + 1) / (4_000 + 1), rounded down against the lender.
+        assert_eq!(setup.pool.preview_withdraw(&1_000_i128), 1_099);
+        assert_eq!(setup.pool.withdraw(&bob, &1_000_i128), 1_099);
+        let event = env.events().all().last().unwrap();
+        assert_eq!(
+            event.1,
+            (symbol_short!("pool"), symbol_short!("withdraw")).into_val(&env)
+        );
+        assert_eq!(setup.pool.withdraw(&alice, &3_000_i128), 3_300);
+        assert_eq!(setup.token.balance(&alice), 10_300);
+        assert_eq!(setup.token.balance(&bob), 10_099);
+        assert_eq!(setup.pool.total_shares(), 0);
+        assert_eq!(setup.pool.shares(&alice), 0);
+    }
+
+    #[test]
+    fn test_donation_cannot_inflate_share_price() {
+        let env = Env::default();
+        let (attacker, victim) = (Address::generate(&env), Address::generate(&env));
+        let setup = setup(&env, &[&attacker, &victim]);
+        StellarAssetClient::new(&env, &setup.token.address).mint(&victim, &5_000);
+
+        assert_eq!(setup.pool.deposit(&attacker, &1_i128), 1);
+        setup
+            .token
+            .transfer(&attacker, &setup.pool.address, &9_999_i128);
+        assert_eq!(setup.pool.total_assets(), 1);
+
+        assert_eq!(setup.pool.deposit(&victim, &15_000_i128), 15_000);
+        assert_eq!(setup.pool.withdraw(&attacker, &1_i128), 1);
+        assert_eq!(setup.pool.withdraw(&victim, &15_000_i128), 15_000);
+    }
+
+    #[test]
+    fn test_loss_lowers_share_price_for_every_lender() {
+        let env = Env::default();
+        let (alice, bob) = (Address::generate(&env), Address::generate(&env));
+        let setup = setup(&env, &[&alice, &bob]);
+        setup.pool.deposit(&alice, &3_000_i128);
+        setup.pool.deposit(&bob, &1_000_i128);
+        setup.pool.borrow(&Address::generate(&env), &2_000_i128);
+
+        setup.pool.record_loss(&1_000_i128);
+        let event = env.events().all().last().unwrap();
+        assert_eq!(
+            event.1,
+            (symbol_short!("pool"), symbol_short!("loss")).into_val(&env)
+        );
+        let payload = LossEvent::try_from_val(&env, &event.2).unwrap();
+        assert_eq!(payload.borrowed, 1_000);
+        assert_eq!(setup.pool.total_assets(), 3_000);
+
+        // An early exit takes its share of the loss with it.
+        assert_eq!(setup.pool.withdraw(&bob, &1_000_i128), 750);
+        repay(&env, &setup, 1_000, 0);
+        assert_eq!(setup.pool.withdraw(&alice, &3_000_i128), 2_250);
+    }
+
+    #[test]
+    fn test_withdraw_limited_to_liquidity_not_lent_out() {
+        let env = Env::default();
+        let alice = Address::generate(&env);
+        let setup = setup(&env, &[&alice]);
+        setup.pool.deposit(&alice, &1_000_i128);
+        setup.pool.borrow(&Address::generate(&env), &600_i128);
+
+        assert_eq!(
+            setup.pool.try_withdraw(&alice, &500_i128),
+            Err(Ok(PoolError::InsufficientLiquidity))
+        );
+        assert_eq!(setup.pool.withdraw(&alice, &400_i128), 400);
+        assert_eq!(
+            setup.pool.try_borrow(&alice, &401_i128),
+            Err(Ok(PoolError::InsufficientLiquidity))
+        );
+    }
+
+    #[test]
+    fn test_pool_validation() {
+        let env = Env::default();
+        let alice = Address::generate(&env);
+        let setup = setup(&env, &[&alice]);
+        assert_eq!(
+            setup.pool.try_deposit(&alice, &0_i128),
+            Err(Ok(PoolError::InvalidAmount))
+        );
+        assert_eq!(
+            setup.pool.try_withdraw(&alice, &0_i128),
+            Err(Ok(PoolError::InvalidAmount))
+        );
+        setup.pool.deposit(&alice, &1_000_i128);
+        assert_eq!(
+            setup.pool.try_withdraw(&alice, &1_001_i128),
+            Err(Ok(PoolError::InsufficientShares))
+        );
+        assert_eq!(
+            setup
+                .pool
+                .try_init(&alice, &setup.token.address, &setup.credit),
+            Err(Ok(PoolError::AlreadyInitialized))
+        );
+
+        // Share price of 2: a deposit of 1 buys no share.
+        repay(&env, &setup, 0, 1_000);
+        assert_eq!(
+            setup.pool.try_deposit(&alice, &1_i128),
+            Err(Ok(PoolError::ZeroShares))
+        );
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_borrow_requires_credit_contract() {
+        let env = Env::default();
+        let alice = Address::generate(&env);
+        let setup = setup(&env, &[&alice]);
+        setup.pool.deposit(&alice, &1_000_i128);
+        env.set_auths(&[]);
+        setup.pool.borrow(&alice, &100_i128);
+    }
+}